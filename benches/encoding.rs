@@ -0,0 +1,28 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use utid::encoding::{decode_base32, decode_uuid, encode_base32, encode_base32_into, encode_uuid};
+
+const ID: i128 = 0x0186_a5c3_2f1e_7b4d_9c0a_1b2c_3d4e_5f60;
+
+fn encoding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encoding");
+    group.throughput(Throughput::Elements(1));
+    let base32 = encode_base32(ID, 128);
+    let uuid = encode_uuid(ID);
+    let mut buf = [0; 26];
+
+    group.bench_function("encode_base32", |b| {
+        b.iter(|| encode_base32(black_box(ID), 128))
+    });
+    group.bench_function("encode_base32_into", |b| {
+        b.iter(|| encode_base32_into(black_box(ID), 128, &mut buf).map(str::len))
+    });
+    group.bench_function("decode_base32", |b| {
+        b.iter(|| decode_base32(black_box(&base32)))
+    });
+    group.bench_function("encode_uuid", |b| b.iter(|| encode_uuid(black_box(ID))));
+    group.bench_function("decode_uuid", |b| b.iter(|| decode_uuid(black_box(&uuid))));
+    group.finish();
+}
+
+criterion_group!(benches, encoding);
+criterion_main!(benches);
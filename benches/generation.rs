@@ -0,0 +1,137 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rand::RngCore;
+use time::{Date, Month, OffsetDateTime};
+use utid::clock::CoarseClock;
+use utid::presets::{event_id, object_id, secure_token, snowflake, ulid, uuid_v7, EventIdSpec};
+use utid::{DynamicSpec, Error};
+
+static CLOCK: CoarseClock = CoarseClock::new(Duration::from_micros(100));
+
+const THREADS: [usize; 3] = [1, 4, 8];
+const PER_THREAD: u64 = 10_000;
+
+fn epoch() -> Date {
+    Date::from_calendar_date(2023, Month::January, 1).unwrap()
+}
+
+fn fill(bytes: &mut [u8]) -> Result<(), Error> {
+    rand::thread_rng().fill_bytes(bytes);
+    Ok(())
+}
+
+fn event_ids() -> EventIdSpec {
+    event_id(epoch(), 7, OffsetDateTime::now_utc, fill)
+}
+
+/// The ULID layout built at runtime, to compare with the [`ulid`] fast path.
+fn dynamic_ulid() -> DynamicSpec {
+    ulid().config().build().unwrap()
+}
+
+fn single_threaded(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate");
+    group.throughput(Throughput::Elements(1));
+    let spec = object_id();
+    group.bench_function("object_id", |b| b.iter(|| spec.generate()));
+    let spec = event_ids();
+    group.bench_function("event_id", |b| b.iter(|| spec.generate()));
+    let spec = dynamic_ulid();
+    group.bench_function("ulid/dynamic", |b| b.iter(|| spec.generate()));
+    let spec = ulid();
+    group.bench_function("ulid/preset", |b| b.iter(|| spec.generate()));
+    let spec = ulid().with_clock(|| CLOCK.now());
+    CLOCK.start().unwrap();
+    group.bench_function("ulid/coarse_clock", |b| b.iter(|| spec.generate()));
+    let spec = uuid_v7();
+    group.bench_function("uuid_v7", |b| b.iter(|| spec.generate()));
+    let spec = secure_token(128).unwrap();
+    group.bench_function("secure_token", |b| b.iter(|| spec.generate()));
+    group.finish();
+}
+
+/// Time for `threads` threads to generate [`PER_THREAD`] IDs each from one shared spec.
+fn contended(threads: usize, iters: u64, generate: impl Fn() + Sync) -> Duration {
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..iters {
+        let start = Instant::now();
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| {
+                    for _ in 0..PER_THREAD {
+                        generate();
+                    }
+                });
+            }
+        });
+        elapsed += start.elapsed();
+    }
+    elapsed
+}
+
+fn multi_threaded(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate_contended");
+    group.sample_size(20);
+    let event_ids = event_ids();
+    let ulid = dynamic_ulid();
+    let snowflakes = snowflake(epoch(), 1).unwrap();
+    for threads in THREADS {
+        group.throughput(Throughput::Elements(threads as u64 * PER_THREAD));
+        group.bench_with_input(BenchmarkId::new("event_id", threads), &threads, |b, &n| {
+            b.iter_custom(|iters| {
+                contended(n, iters, || {
+                    black_box(event_ids.generate().unwrap());
+                })
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("ulid", threads), &threads, |b, &n| {
+            b.iter_custom(|iters| {
+                contended(n, iters, || {
+                    black_box(ulid.generate().unwrap());
+                })
+            })
+        });
+        // All threads share one sequence, so this measures contention on its atomic word.
+        group.bench_with_input(BenchmarkId::new("snowflake", threads), &threads, |b, &n| {
+            b.iter_custom(|iters| {
+                contended(n, iters, || {
+                    black_box(snowflakes.generate().unwrap());
+                })
+            })
+        });
+    }
+    group.finish();
+}
+
+fn decompose(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decompose");
+    group.throughput(Throughput::Elements(1));
+    let spec = object_id();
+    let id = spec.generate().unwrap();
+    group.bench_function("object_id", |b| b.iter(|| spec.decompose(black_box(id))));
+    let spec = event_ids();
+    let id = spec.generate().unwrap();
+    group.bench_function("event_id", |b| b.iter(|| spec.decompose(black_box(id))));
+    let spec = dynamic_ulid();
+    let id = spec.generate().unwrap();
+    group.bench_function("ulid/dynamic", |b| b.iter(|| spec.decompose(black_box(id))));
+    let spec = ulid();
+    group.bench_function("ulid/preset", |b| b.iter(|| spec.decompose(black_box(id))));
+    group.finish();
+}
+
+fn batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate_batch");
+    let mut ids = [0; 1024];
+    group.throughput(Throughput::Elements(ids.len() as u64));
+    let spec = ulid();
+    group.bench_function("ulid", |b| b.iter(|| spec.generate_batch(&mut ids)));
+    let spec = uuid_v7();
+    group.bench_function("uuid_v7", |b| b.iter(|| spec.generate_batch(&mut ids)));
+    group.finish();
+}
+
+criterion_group!(benches, single_threaded, multi_threaded, decompose, batch);
+criterion_main!(benches);
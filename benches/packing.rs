@@ -0,0 +1,124 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use time::{Date, Month, OffsetDateTime};
+use utid::{
+    ConstantSegment, Generator, Packing, RandomSegment, SegmentConfig, Spec3, Spec4, SpecConfig,
+    TimestampSegment, TimestampUnit,
+};
+
+fn spec() -> Spec3<i128, OffsetDateTime, i128, i128> {
+    let since = Date::from_calendar_date(2023, Month::January, 1).unwrap();
+    Spec3 {
+        segments: (
+            Box::new(TimestampSegment::new_with_utc_midnight(
+                48,
+                TimestampUnit::Milliseconds,
+                since,
+            )),
+            Box::new(ConstantSegment::new(16, 7)),
+            Box::new(RandomSegment::new(64)),
+        ),
+    }
+}
+
+/// Four segments, where laying them out per call costs the most.
+fn spec4() -> Spec4<i128, OffsetDateTime, i128, i128, i128> {
+    let since = Date::from_calendar_date(2023, Month::January, 1).unwrap();
+    Spec4 {
+        segments: (
+            Box::new(TimestampSegment::new_with_utc_midnight(
+                48,
+                TimestampUnit::Milliseconds,
+                since,
+            )),
+            Box::new(ConstantSegment::new(8, 3)),
+            Box::new(ConstantSegment::new(8, 7)),
+            Box::new(RandomSegment::new(64)),
+        ),
+    }
+}
+
+fn packing(c: &mut Criterion) {
+    let sizes = [48, 16, 64];
+    let values = [1 << 40, 7, 1 << 63];
+    let cached = Packing::new(sizes).unwrap();
+    let id = cached.join(values).unwrap();
+
+    c.bench_function("join/per_call", |b| {
+        b.iter(|| {
+            Packing::new(black_box(sizes))
+                .unwrap()
+                .join(black_box(values))
+        })
+    });
+    c.bench_function("join/cached", |b| b.iter(|| cached.join(black_box(values))));
+    c.bench_function("split/per_call", |b| {
+        b.iter(|| Packing::new(black_box(sizes)).unwrap().split(black_box(id)))
+    });
+    c.bench_function("split/cached", |b| b.iter(|| cached.split(black_box(id))));
+}
+
+/// Specs laying out their segments per call against the [`Packing`] a [`Generator`] computes
+/// once.
+fn generation(c: &mut Criterion) {
+    let spec = spec();
+    let generator = Generator::new(self::spec()).unwrap();
+    let id = spec.generate().unwrap();
+
+    c.bench_function("generate/spec", |b| b.iter(|| spec.generate()));
+    c.bench_function("generate/generator", |b| b.iter(|| generator.generate()));
+    c.bench_function("decompose/spec", |b| {
+        b.iter(|| spec.decompose(black_box(id)))
+    });
+    c.bench_function("decompose/generator", |b| {
+        b.iter(|| generator.decompose(black_box(id)))
+    });
+
+    let spec = spec4();
+    let generator = Generator::new(spec4()).unwrap();
+    let id = spec.generate().unwrap();
+    c.bench_function("decompose4/spec", |b| {
+        b.iter(|| spec.decompose(black_box(id)))
+    });
+    c.bench_function("decompose4/generator", |b| {
+        b.iter(|| generator.decompose(black_box(id)))
+    });
+}
+
+/// A layout of at most 64 bits, which splits in 64-bit arithmetic, against a 128-bit one of as
+/// many segments. The gap is widest on 32-bit targets, where 128-bit shifts are emulated.
+fn widths(c: &mut Criterion) {
+    let narrow = Packing::new([41, 10, 12]).unwrap();
+    let wide = Packing::new([48, 16, 64]).unwrap();
+    let narrow_id = narrow.join([1 << 40, 7, 1 << 11]).unwrap();
+    let wide_id = wide.join([1 << 40, 7, 1 << 63]).unwrap();
+    c.bench_function("split/64_bits", |b| {
+        b.iter(|| narrow.split(black_box(narrow_id)))
+    });
+    c.bench_function("split/128_bits", |b| {
+        b.iter(|| wide.split(black_box(wide_id)))
+    });
+
+    let dynamic = |sizes: [u8; 3]| {
+        SpecConfig {
+            segments: sizes
+                .iter()
+                .map(|&size| SegmentConfig::Random {
+                    size,
+                    os_rng: false,
+                })
+                .collect(),
+        }
+        .build()
+        .unwrap()
+    };
+    let (narrow, wide) = (dynamic([41, 10, 12]), dynamic([48, 16, 64]));
+    c.bench_function("decompose_dynamic/64_bits", |b| {
+        b.iter(|| narrow.decompose(black_box(narrow_id)))
+    });
+    c.bench_function("decompose_dynamic/128_bits", |b| {
+        b.iter(|| wide.decompose(black_box(wide_id)))
+    });
+}
+
+criterion_group!(benches, packing, generation, widths);
+criterion_main!(benches);
@@ -0,0 +1,17 @@
+//! Arbitrary IDs through arbitrary valid layouts: decomposing and verifying may fail but never
+//! panic, and IDs the layout generates itself always decompose.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use utid::{Layout, SpecConfig};
+
+fuzz_target!(|input: (SpecConfig, i128)| {
+    let (config, id) = input;
+    let spec = config.build().expect("arbitrary configs are valid");
+    let _ = spec.decompose(id);
+    let _ = spec.verify(id);
+    if let Ok(generated) = spec.generate() {
+        assert!(spec.decompose(generated).is_ok(), "{:?} {:#x}", config, generated);
+    }
+});
@@ -0,0 +1,26 @@
+//! Untrusted strings and bytes through every ID parser: none may panic, and whatever parses
+//! must come back unchanged from encoding it again.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use utid::encoding::{decode_base32, decode_uuid, encode_base32, encode_uuid};
+use utid::Utid;
+
+fuzz_target!(|input: &[u8]| {
+    if let Ok(id) = Utid::from_bytes(input) {
+        assert_eq!(input, id.to_bytes());
+    }
+    let Ok(text) = std::str::from_utf8(input) else {
+        return;
+    };
+    if let Ok(id) = decode_base32(text) {
+        assert_eq!(Some(id), decode_base32(&encode_base32(id, 128)).ok());
+    }
+    if let Ok(id) = text.parse::<Utid>() {
+        assert_eq!(Some(id), id.to_string().parse().ok());
+    }
+    if let Ok(id) = decode_uuid(text) {
+        assert_eq!(Some(id), decode_uuid(&encode_uuid(id)).ok());
+    }
+});
@@ -0,0 +1,56 @@
+/* C API of the utid crate, built with
+ * `cargo rustc --release --features ffi --crate-type cdylib` (or `staticlib`). */
+
+#ifndef UTID_H
+#define UTID_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define UTID_ENCODED_LEN 26
+
+typedef struct UtidSpec UtidSpec;
+
+/* An ID split into its upper and lower 64 bits. */
+typedef struct UtidId {
+    uint64_t hi;
+    uint64_t lo;
+} UtidId;
+
+typedef enum UtidStatus {
+    UTID_OK = 0,
+    UTID_NULL_POINTER = 1,
+    UTID_INVALID_UTF8 = 2,
+    UTID_INVALID_CONFIG = 3,
+    UTID_BUFFER_TOO_SMALL = 4,
+    UTID_PARSE_ERROR = 5,
+    UTID_VALUE_TOO_LARGE = 6,
+    UTID_CLOCK_REGRESSION = 7,
+    UTID_EPOCH_IN_FUTURE = 8,
+    UTID_EXHAUSTED = 9,
+    UTID_OVERFLOW = 10,
+    UTID_OTHER = 255,
+} UtidStatus;
+
+UtidStatus utid_spec_new_json(const char *json, UtidSpec **out);
+UtidStatus utid_spec_new_env(const char *prefix, UtidSpec **out);
+void utid_spec_free(UtidSpec *spec);
+size_t utid_spec_segment_count(const UtidSpec *spec);
+
+UtidStatus utid_generate(const UtidSpec *spec, UtidId *out);
+/* Timestamps decompose to nanoseconds since the Unix epoch. */
+UtidStatus utid_decompose(const UtidSpec *spec, UtidId id, UtidId *values, size_t len);
+
+/* `buf` must hold at least UTID_ENCODED_LEN + 1 bytes. */
+UtidStatus utid_encode(UtidId id, char *buf, size_t len);
+UtidStatus utid_parse(const char *encoded, UtidId *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* UTID_H */
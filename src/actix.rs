@@ -0,0 +1,67 @@
+//! Actix Web integration. [`Path`] extracts an ID from the route's path parameter and answers
+//! `400 Bad Request` with [`Error::to_json`] as the body when it doesn't parse.
+
+use std::future::{ready, Ready};
+use std::str::FromStr;
+
+use ::actix_web::dev::Payload;
+use ::actix_web::error::{ErrorNotFound, InternalError};
+use ::actix_web::{FromRequest, HttpRequest, HttpResponse};
+
+use crate::Error;
+
+/// Extracts the route's only path parameter as an ID, e.g. `Path<Utid>` or
+/// `Path<Prefixed<Order>>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Path<T>(pub T);
+
+impl<T: FromStr<Err = Error>> FromRequest for Path<T> {
+    type Error = ::actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let Some((_, raw)) = req.match_info().iter().next() else {
+            return ready(Err(ErrorNotFound("missing path parameter")));
+        };
+        ready(raw.parse().map(Path).map_err(|error: Error| {
+            let response = HttpResponse::BadRequest().json(error.to_json());
+            InternalError::from_response(error, response).into()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::actix_web::http::StatusCode;
+    use ::actix_web::{test, web, App};
+
+    use super::*;
+    use crate::Utid;
+
+    async fn get_order(uri: &str) -> (StatusCode, web::Bytes) {
+        let app = test::init_service(App::new().route(
+            "/orders/{id}",
+            web::get().to(|Path(id): Path<Utid>| async move { id.0.to_string() }),
+        ))
+        .await;
+        let response =
+            test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        (response.status(), test::read_body(response).await)
+    }
+
+    #[actix_web::test]
+    async fn extracts_ids() {
+        let (status, body) = get_order("/orders/000000000000000000000000C1").await;
+        assert_eq!(StatusCode::OK, status);
+        assert_eq!("385", body);
+    }
+
+    #[actix_web::test]
+    async fn rejects_malformed_ids() {
+        let (status, body) = get_order("/orders/0U").await;
+        assert_eq!(StatusCode::BAD_REQUEST, status);
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!("parse_error", body["code"]);
+        assert_eq!(1, body["position"]);
+    }
+}
@@ -0,0 +1,78 @@
+//! Axum integration. [`Path`] extracts an ID from the route's path parameter and answers
+//! `400 Bad Request` with [`Error::to_json`] as the body when it doesn't parse.
+
+use std::str::FromStr;
+
+use ::axum::extract::{FromRequestParts, Path as RawPath};
+use ::axum::http::request::Parts;
+use ::axum::http::{header, StatusCode};
+use ::axum::response::{IntoResponse, Response};
+
+use crate::Error;
+
+/// Extracts the route's only path parameter as an ID, e.g. `Path<Utid>` or
+/// `Path<Prefixed<Order>>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Path<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for Path<T>
+where
+    T: FromStr<Err = Error> + Send,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let RawPath(raw) = RawPath::<String>::from_request_parts(parts, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        raw.parse().map(Path).map_err(|error: Error| {
+            (
+                StatusCode::BAD_REQUEST,
+                [(header::CONTENT_TYPE, "application/json")],
+                error.to_json().to_string(),
+            )
+                .into_response()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::axum::body::{to_bytes, Body};
+    use ::axum::http::Request;
+    use ::axum::routing::get;
+    use ::axum::Router;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::Utid;
+
+    async fn get_order(uri: &str) -> (StatusCode, String) {
+        let app = Router::new().route(
+            "/orders/{id}",
+            get(|Path(id): Path<Utid>| async move { id.0.to_string() }),
+        );
+        let request = Request::get(uri).body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn extracts_ids() {
+        let (status, body) = get_order("/orders/000000000000000000000000C1").await;
+        assert_eq!(StatusCode::OK, status);
+        assert_eq!("385", body);
+    }
+
+    #[tokio::test]
+    async fn rejects_malformed_ids() {
+        let (status, body) = get_order("/orders/0U").await;
+        assert_eq!(StatusCode::BAD_REQUEST, status);
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!("parse_error", body["code"]);
+        assert_eq!(1, body["position"]);
+    }
+}
@@ -0,0 +1,80 @@
+use std::error::Error;
+use std::hint::black_box;
+use std::thread;
+use std::time::Instant;
+
+use time::Duration;
+
+use crate::simulate::parse_duration;
+use crate::spec::SpecArgs;
+
+/// Every this many calls, one latency is kept for the percentiles; timing every call is cheap,
+/// keeping every one is not.
+const SAMPLE_EVERY: u64 = 16;
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    spec: SpecArgs,
+    /// Threads generating at the same time.
+    #[arg(long, default_value_t = 1)]
+    threads: usize,
+    /// How long every thread generates, e.g. `3s` or `500ms`.
+    #[arg(long, value_parser = parse_duration, default_value = "3s")]
+    duration: Duration,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let loaded = args.spec.load()?;
+    let duration = std::time::Duration::try_from(args.duration)?;
+    let threads = args.threads.max(1);
+    let started = Instant::now();
+    let results = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut count = 0u64;
+                    let mut samples = Vec::new();
+                    let started = Instant::now();
+                    while started.elapsed() < duration {
+                        let call = Instant::now();
+                        black_box(loaded.spec.generate()?);
+                        let elapsed = call.elapsed();
+                        if count.is_multiple_of(SAMPLE_EVERY) {
+                            samples.push(elapsed);
+                        }
+                        count += 1;
+                    }
+                    Ok::<_, utid::Error>((count, samples))
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().expect("benchmark thread panicked"))
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+    let elapsed = started.elapsed();
+
+    let count: u64 = results.iter().map(|(count, _)| count).sum();
+    let mut samples: Vec<_> = results
+        .into_iter()
+        .flat_map(|(_, samples)| samples)
+        .collect();
+    if samples.is_empty() {
+        return Err("no ID was generated within the duration".into());
+    }
+    samples.sort_unstable();
+    let percentile = |percent: usize| samples[(samples.len() - 1) * percent / 100];
+
+    println!("threads     {}", threads);
+    println!("ids         {}", count);
+    println!(
+        "throughput  {:.0} ids/s",
+        count as f64 / elapsed.as_secs_f64()
+    );
+    println!("p50         {:?}", percentile(50));
+    println!("p99         {:?}", percentile(99));
+    println!("max         {:?}", percentile(100));
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use std::error::Error;
+
+use utid::Layout;
+
+use crate::spec::SpecArgs;
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    spec: SpecArgs,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let loaded = args.spec.load()?;
+    println!(
+        "ok: {} segments, {} bits",
+        loaded.spec.segments().len(),
+        loaded.size()
+    );
+    print!("{}", loaded.spec);
+    Ok(())
+}
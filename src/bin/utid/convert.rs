@@ -0,0 +1,30 @@
+use std::error::Error;
+
+use crate::spec::Encoding;
+
+#[derive(clap::Args)]
+pub struct Args {
+    id: String,
+    /// How the ID is written; guessed from its shape if left out.
+    #[arg(long, value_name = "ENCODING")]
+    from: Option<Encoding>,
+    #[arg(long, value_name = "ENCODING")]
+    to: Encoding,
+    /// Width of the ID, which sets how far base32 and hex output is padded.
+    #[arg(long, default_value_t = 128, value_parser = clap::value_parser!(u8).range(1..=128))]
+    bits: u8,
+}
+
+/// Re-encodes the raw bits of an ID; no spec is involved, so nothing about the ID is checked
+/// beyond it fitting in `bits`.
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let from = args
+        .from
+        .unwrap_or_else(|| Encoding::detect(&args.id, args.bits));
+    let id = from.decode(&args.id)?;
+    if args.bits < 128 && (id as u128) >> args.bits != 0 {
+        return Err(format!("the ID does not fit in {} bits", args.bits).into());
+    }
+    println!("{}", args.to.encode(id, args.bits));
+    Ok(())
+}
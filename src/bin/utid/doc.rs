@@ -0,0 +1,35 @@
+use std::error::Error;
+
+use clap::ValueEnum;
+use utid::Layout;
+
+use crate::spec::SpecArgs;
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// The bit diagram in a code block, then the segment table.
+    Markdown,
+    /// A standalone image of the bit diagram and the segment table.
+    Svg,
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    spec: SpecArgs,
+    #[arg(long, value_enum, default_value_t = Format::Markdown)]
+    format: Format,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let loaded = args.spec.load()?;
+    match args.format {
+        Format::Markdown => print!(
+            "```text\n{}```\n\n{}",
+            loaded.spec,
+            loaded.spec.describe_markdown()
+        ),
+        Format::Svg => print!("{}", loaded.spec.describe_svg()),
+    }
+    Ok(())
+}
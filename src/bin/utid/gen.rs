@@ -0,0 +1,90 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+use utid::Layout;
+
+use crate::spec::{format_value, kind, Encoding, SpecArgs};
+
+/// How each generated ID is written.
+#[derive(Clone, Copy, ValueEnum)]
+enum Output {
+    /// The encoded ID alone, one per line.
+    Raw,
+    /// One JSON object per line, with the ID and its decoded segments.
+    Ndjson,
+    /// A header row, then the ID and its decoded segments.
+    Csv,
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    spec: SpecArgs,
+    /// How many IDs to print.
+    #[arg(short = 'n', long, default_value_t = 1)]
+    count: u64,
+    /// Encoding of the IDs; defaults to the one usual for the layout.
+    #[arg(long, value_name = "ENCODING")]
+    format: Option<Encoding>,
+    #[arg(long, value_enum, default_value_t = Output::Raw)]
+    output: Output,
+    /// Write to this file instead of stdout.
+    #[arg(long, value_name = "FILE")]
+    file: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let loaded = args.spec.load()?;
+    let encoding = args.format.unwrap_or(loaded.encoding);
+    let out: Box<dyn Write> = match &args.file {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut out = BufWriter::new(out);
+    let kinds: Vec<&str> = loaded
+        .spec
+        .segments()
+        .into_iter()
+        .map(|segment| kind(segment))
+        .collect();
+    if let Output::Csv = args.output {
+        write!(out, "id")?;
+        for (index, kind) in kinds.iter().enumerate() {
+            write!(out, ",{}_{}", kind, index)?;
+        }
+        writeln!(out)?;
+    }
+    for _ in 0..args.count {
+        let id = loaded.spec.generate()?;
+        let encoded = encoding.encode(id, loaded.size());
+        match args.output {
+            Output::Raw => writeln!(out, "{}", encoded)?,
+            Output::Ndjson => {
+                let segments = kinds
+                    .iter()
+                    .zip(loaded.spec.decompose(id)?)
+                    .map(|(kind, value)| {
+                        Ok(serde_json::json!({ "kind": kind, "value": format_value(value)? }))
+                    })
+                    .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+                serde_json::to_writer(
+                    &mut out,
+                    &serde_json::json!({ "id": encoded, "segments": segments }),
+                )?;
+                writeln!(out)?;
+            }
+            Output::Csv => {
+                write!(out, "{}", encoded)?;
+                for value in loaded.spec.decompose(id)? {
+                    write!(out, ",{}", format_value(value)?)?;
+                }
+                writeln!(out)?;
+            }
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use std::error::Error;
+
+use utid::{Layout, Segment, SegmentConfig, TimestampUnit};
+
+use crate::spec::{format_value, kind, Encoding, SpecArgs};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// The ID, as decimal, hex (`0x` optional), Crockford base32 or a UUID.
+    id: String,
+    #[command(flatten)]
+    spec: SpecArgs,
+    /// How the ID is written, if guessing from its shape picks the wrong encoding.
+    #[arg(long, value_name = "ENCODING")]
+    input: Option<Encoding>,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let loaded = args.spec.load()?;
+    let size = loaded.size();
+    let encoding = args
+        .input
+        .unwrap_or_else(|| Encoding::detect(&args.id, size));
+    let id = encoding.decode(&args.id)?;
+    let values = loaded.spec.decompose(id)?;
+
+    println!("id       {}", loaded.encoding.encode(id, size));
+    println!("decimal  {}", id as u128);
+    println!("hex      {}", Encoding::Hex.encode(id, size));
+    println!();
+    println!("{:<16} {:<9} {:<40} value", "segment", "bits", "raw");
+    let mut offset = u32::from(size);
+    for (segment, value) in loaded.spec.segments().iter().zip(values) {
+        let bits = u32::from(segment.size());
+        let high = offset.saturating_sub(1);
+        offset -= bits;
+        let raw = (id as u128).checked_shr(offset).unwrap_or(0) & mask(bits);
+        println!(
+            "{:<16} {:<9} {:<40} {}",
+            name(*segment),
+            format!("{}-{}", high, offset),
+            raw,
+            format_value(value)?
+        );
+    }
+    Ok(())
+}
+
+fn mask(bits: u32) -> u128 {
+    u128::MAX.checked_shr(128 - bits).unwrap_or(0)
+}
+
+fn name(segment: &dyn Segment) -> String {
+    match segment.config() {
+        Some(SegmentConfig::Timestamp { unit, .. }) => format!(
+            "timestamp ({})",
+            match unit {
+                TimestampUnit::Seconds => "s",
+                TimestampUnit::Milliseconds => "ms",
+                TimestampUnit::Microseconds => "µs",
+                TimestampUnit::Nanoseconds => "ns",
+            }
+        ),
+        _ => kind(segment).to_string(),
+    }
+}
@@ -0,0 +1,134 @@
+//! Maps the config keys [`ConfigError::Invalid`] reports, e.g. `segments[1].size`, back to lines
+//! of the spec file, since the spec is built from the parsed config and has no positions left.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use utid::ConfigError;
+
+/// Renders `error` for the spec file at `path`, prefixing each layout problem with the line it
+/// was found at where that can be told.
+pub fn describe(path: &Path, error: ConfigError) -> String {
+    let errors = match error {
+        ConfigError::Invalid(errors) => errors,
+        error => return format!("{}: {}", path.display(), error),
+    };
+    let source = std::fs::read_to_string(path).unwrap_or_default();
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    let mut message = format!("{}: invalid spec config", path.display());
+    for (key, error) in errors {
+        match line(&source, extension, &key) {
+            Some(line) => {
+                message += &format!("\n  {}:{}: {}: {}", path.display(), line, key, error)
+            }
+            None => message += &format!("\n  {}: {}: {}", path.display(), key, error),
+        }
+    }
+    message
+}
+
+/// One-based line of `key` in `source`.
+fn line(source: &str, extension: Option<&str>, key: &str) -> Option<usize> {
+    let (segment, field) = match key.strip_prefix("segments[") {
+        Some(rest) => {
+            let (index, field) = rest.split_once("].")?;
+            (Some(index.parse().ok()?), field)
+        }
+        None => (None, key),
+    };
+    let offset = match (extension, segment) {
+        (_, None) => source.find(field)?,
+        (Some("toml"), Some(segment)) => toml_offset(source, segment, field)?,
+        (Some("json"), Some(segment)) => json_offset(source, segment, field)?,
+        _ => return None,
+    };
+    Some(source[..offset].matches('\n').count() + 1)
+}
+
+#[derive(serde::Deserialize)]
+struct TomlSegments {
+    #[serde(default)]
+    segments: Vec<BTreeMap<String, toml::Spanned<toml::Value>>>,
+}
+
+fn toml_offset(source: &str, segment: usize, field: &str) -> Option<usize> {
+    let config: TomlSegments = toml::from_str(source).ok()?;
+    Some(config.segments.get(segment)?.get(field)?.span().start)
+}
+
+/// Scans for `field` of the `segment`th object in the top-level `segments` array, skipping over
+/// strings, as `serde_json` keeps no positions.
+fn json_offset(source: &str, segment: usize, field: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_segments = false;
+    let mut current = None;
+    let mut key: Option<(&str, usize)> = None;
+    let mut chars = source.char_indices();
+    while let Some((offset, char)) = chars.next() {
+        match char {
+            '"' => {
+                let start = offset + 1;
+                let mut end = start;
+                let mut escaped = false;
+                for (offset, char) in chars.by_ref() {
+                    end = offset;
+                    match char {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+                key = Some((&source[start..end], offset));
+                continue;
+            }
+            ':' => match key {
+                Some(("segments", _)) if depth == 1 => in_segments = true,
+                Some((name, offset)) if depth == 3 && current == Some(segment) && name == field => {
+                    return Some(offset);
+                }
+                _ => {}
+            },
+            '{' | '[' => {
+                depth += 1;
+                if char == '{' && depth == 3 && in_segments {
+                    current = Some(current.map_or(0, |index| index + 1));
+                }
+            }
+            '}' | ']' => {
+                if depth == 2 && char == ']' {
+                    in_segments = false;
+                }
+                depth -= 1;
+            }
+            _ => {}
+        }
+        if !char.is_whitespace() {
+            key = None;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_toml_fields() {
+        let source = "[[segments]]\nkind = \"random\"\nsize = 8\n\n[[segments]]\nkind = \"constant\"\nsize = 8\nvalue = 300\n";
+        assert_eq!(Some(3), line(source, Some("toml"), "segments[0].size"));
+        assert_eq!(Some(8), line(source, Some("toml"), "segments[1].value"));
+        assert_eq!(Some(1), line(source, Some("toml"), "segments"));
+        assert_eq!(None, line(source, Some("toml"), "segments[2].size"));
+    }
+
+    #[test]
+    fn finds_json_fields() {
+        let source = "{\n  \"segments\": [\n    {\"kind\": \"random\", \"size\": 8},\n    {\n      \"kind\": \"constant\",\n      \"note\": \"size\",\n      \"size\": 8,\n      \"value\": 300\n    }\n  ]\n}\n";
+        assert_eq!(Some(3), line(source, Some("json"), "segments[0].size"));
+        assert_eq!(Some(7), line(source, Some("json"), "segments[1].size"));
+        assert_eq!(Some(8), line(source, Some("json"), "segments[1].value"));
+        assert_eq!(None, line(source, Some("json"), "segments[2].size"));
+    }
+}
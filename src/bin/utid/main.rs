@@ -0,0 +1,72 @@
+//! `utid`: generates and inspects IDs of the built-in presets or of a spec config file.
+
+mod bench;
+mod check;
+mod convert;
+mod doc;
+mod gen;
+mod inspect;
+mod locate;
+mod range;
+mod simulate;
+mod spec;
+mod validate;
+
+use std::error::Error;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+
+#[derive(Parser)]
+#[command(
+    name = "utid",
+    version,
+    about = "Generate and inspect unique tailored IDs"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Measure generation throughput and latency on this machine.
+    Bench(bench::Args),
+    /// Validate a layout and print its bit diagram.
+    Check(check::Args),
+    /// Print freshly generated IDs, one per line.
+    Gen(gen::Args),
+    /// Decompose an ID into its segments.
+    Inspect(inspect::Args),
+    /// Estimate collision odds and timestamp exhaustion at a generation rate.
+    Simulate(simulate::Args),
+    /// Print the smallest and largest IDs of a time window.
+    Range(range::Args),
+    /// Check IDs against a layout and report every failure.
+    Validate(validate::Args),
+    /// Re-encode an ID, e.g. from hex to base32.
+    Convert(convert::Args),
+    /// Render the layout's bit diagram and segment table.
+    Doc(doc::Args),
+}
+
+fn main() -> ExitCode {
+    let result: Result<(), Box<dyn Error>> = match Cli::parse().command {
+        Command::Bench(args) => bench::run(args),
+        Command::Check(args) => check::run(args),
+        Command::Gen(args) => gen::run(args),
+        Command::Inspect(args) => inspect::run(args),
+        Command::Simulate(args) => simulate::run(args),
+        Command::Range(args) => range::run(args),
+        Command::Validate(args) => validate::run(args),
+        Command::Convert(args) => convert::run(args),
+        Command::Doc(args) => doc::run(args),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::error::Error;
+
+use time::format_description::well_known::Rfc3339;
+use time::{Date, Duration, Month, OffsetDateTime};
+use utid::{Layout, SegmentConfig, TimestampUnit};
+
+use crate::spec::{Encoding, SpecArgs};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    spec: SpecArgs,
+    /// Start of the window, as a `YYYY-MM-DD` date (midnight UTC) or an RFC 3339 timestamp.
+    #[arg(long, value_parser = parse_instant)]
+    from: OffsetDateTime,
+    /// End of the window, excluded.
+    #[arg(long, value_parser = parse_instant)]
+    to: OffsetDateTime,
+}
+
+/// Prints the smallest and largest ID any generator of the spec can issue within the window:
+/// timestamp segments at the window's first and last tick, constants at their value and every
+/// other bit cleared, respectively set.
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    if args.to <= args.from {
+        return Err("--to must be after --from".into());
+    }
+    let loaded = args.spec.load()?;
+    let config = loaded
+        .spec
+        .config()
+        .ok_or("the spec has segments without a config")?;
+    if !config
+        .segments
+        .iter()
+        .any(|segment| matches!(segment, SegmentConfig::Timestamp { .. }))
+    {
+        return Err("the spec has no timestamp segment".into());
+    }
+    let last = args.to - Duration::NANOSECOND;
+    let min = bound(&config.segments, args.from, false)?;
+    let max = bound(&config.segments, last, true)?;
+
+    let size = loaded.size();
+    let mut encodings = vec![
+        ("decimal", Encoding::Decimal),
+        ("hex", Encoding::Hex),
+        ("base32", Encoding::Base32),
+    ];
+    if size == 128 {
+        encodings.push(("uuid", Encoding::Uuid));
+    }
+    for (name, id) in [("min", min), ("max", max)] {
+        for (encoding_name, encoding) in &encodings {
+            println!(
+                "{}  {:<8} {}",
+                name,
+                encoding_name,
+                encoding.encode(id, size)
+            );
+        }
+    }
+    Ok(())
+}
+
+fn bound(
+    segments: &[SegmentConfig],
+    at: OffsetDateTime,
+    upper: bool,
+) -> Result<i128, Box<dyn Error>> {
+    let mut id = 0u128;
+    for (index, segment) in segments.iter().enumerate() {
+        let size = u32::from(segment.size());
+        let mask = u128::MAX.checked_shr(128 - size).unwrap_or(0);
+        let value = match segment {
+            SegmentConfig::Timestamp { unit, epoch, .. } => {
+                let since = at - epoch.midnight().assume_utc();
+                let ticks = since.whole_nanoseconds().div_euclid(unit_nanos(*unit));
+                u128::try_from(ticks)
+                    .ok()
+                    .filter(|ticks| ticks & !mask == 0)
+                    .ok_or_else(|| {
+                        format!(
+                            "segment {}: {} is outside the timestamp range",
+                            index,
+                            at.format(&Rfc3339).unwrap_or_default()
+                        )
+                    })?
+            }
+            SegmentConfig::Constant { value, .. } => *value as u128 & mask,
+            SegmentConfig::Random { .. } if upper => mask,
+            SegmentConfig::Random { .. } => 0,
+        };
+        id = id.checked_shl(size).unwrap_or(0) | value;
+    }
+    Ok(id as i128)
+}
+
+fn unit_nanos(unit: TimestampUnit) -> i128 {
+    match unit {
+        TimestampUnit::Seconds => 1_000_000_000,
+        TimestampUnit::Milliseconds => 1_000_000,
+        TimestampUnit::Microseconds => 1_000,
+        TimestampUnit::Nanoseconds => 1,
+    }
+}
+
+fn parse_instant(text: &str) -> Result<OffsetDateTime, String> {
+    if let Ok(instant) = OffsetDateTime::parse(text, &Rfc3339) {
+        return Ok(instant);
+    }
+    let date = || {
+        let mut parts = text.splitn(3, '-');
+        let year = parts.next()?.parse().ok()?;
+        let month = Month::try_from(parts.next()?.parse::<u8>().ok()?).ok()?;
+        let day = parts.next()?.parse().ok()?;
+        Date::from_calendar_date(year, month, day).ok()
+    };
+    date()
+        .map(|date| date.midnight().assume_utc())
+        .ok_or_else(|| format!("{:?} is neither YYYY-MM-DD nor RFC 3339", text))
+}
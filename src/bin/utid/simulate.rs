@@ -0,0 +1,155 @@
+use std::error::Error;
+
+use time::{Duration, OffsetDateTime};
+use utid::Layout;
+
+use crate::spec::SpecArgs;
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    spec: SpecArgs,
+    /// IDs generated per unit of time, e.g. `50000/s`, `3M/h` or `200` for per second.
+    #[arg(long, value_parser = parse_rate)]
+    rate: f64,
+    /// How long IDs are generated at that rate, e.g. `30d`, `12h` or `1y`.
+    #[arg(long, value_parser = parse_window)]
+    duration: Duration,
+}
+
+/// Sums the per-tick [collision estimate](Layout::collision_probability) over every tick of
+/// the window.
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let loaded = args.spec.load()?;
+    let seconds = args.duration.as_seconds_f64();
+    let total = args.rate * seconds;
+    let ticks = loaded
+        .spec
+        .collision_probability(0.0)
+        .tick_seconds
+        .map_or(1.0, |tick| (seconds / tick).max(1.0));
+    let per_tick = total / ticks;
+    let estimate = loaded.spec.collision_probability(per_tick);
+    let random_bits = estimate.random_bits as i32;
+    let expected = ticks * estimate.expected_collisions;
+    let probability = -(-expected).exp_m1();
+
+    println!("ids generated          {:.4e}", total);
+    match estimate.tick_seconds {
+        Some(tick) => println!("ids per tick           {:.4e} ({}s ticks)", per_tick, tick),
+        None => println!("ids per tick           {:.4e} (no timestamp)", per_tick),
+    }
+    println!("random bits            {}", random_bits);
+    println!("expected collisions    {:.4e}", expected);
+    println!("collision probability  {:.4e}", probability);
+    if per_tick > 2f64.powi(random_bits) {
+        println!(
+            "warning: {:.4e} ids per tick exceed the {} values the random bits hold",
+            per_tick,
+            2f64.powi(random_bits)
+        );
+    }
+
+    let end = OffsetDateTime::now_utc()
+        .checked_add(args.duration)
+        .ok_or("the window ends past the latest representable date")?;
+    match loaded.spec.timestamp_exhaustion_date() {
+        Some(exhausted) if exhausted < end => println!(
+            "warning: timestamps run out on {}, before the window ends on {}",
+            exhausted.date(),
+            end.date()
+        ),
+        Some(exhausted) => println!("timestamps run out     {}", exhausted.date()),
+        None => {}
+    }
+    Ok(())
+}
+
+/// A number with an optional `k`, `M` or `G` multiplier.
+fn parse_count(text: &str) -> Result<f64, String> {
+    let (number, multiplier) = match text.char_indices().last() {
+        Some((index, 'k')) => (&text[..index], 1e3),
+        Some((index, 'M')) => (&text[..index], 1e6),
+        Some((index, 'G')) => (&text[..index], 1e9),
+        _ => (text, 1.0),
+    };
+    match number.parse::<f64>() {
+        Ok(number) if number.is_finite() && number >= 0.0 => Ok(number * multiplier),
+        _ => Err(format!("{:?} is not a count", text)),
+    }
+}
+
+fn parse_rate(text: &str) -> Result<f64, String> {
+    let (count, per) = text.split_once('/').unwrap_or((text, "s"));
+    let per = parse_duration(
+        if per.starts_with(|char: char| char.is_ascii_digit()) {
+            per.to_string()
+        } else {
+            format!("1{}", per)
+        }
+        .as_str(),
+    )?;
+    Ok(parse_count(count)? / per.as_seconds_f64())
+}
+
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let split = text
+        .find(|char: char| !char.is_ascii_digit() && char != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let seconds = match unit {
+        "ms" => 1e-3,
+        "s" | "" => 1.0,
+        "m" | "min" => 60.0,
+        "h" => 3600.0,
+        "d" => 86_400.0,
+        "w" => 604_800.0,
+        "y" => 31_536_000.0,
+        _ => return Err(format!("unknown unit {:?} in {:?}", unit, text)),
+    };
+    match number.parse::<f64>() {
+        Ok(number) if number > 0.0 => match Duration::checked_seconds_f64(number * seconds) {
+            Some(duration) if duration.is_zero() => {
+                Err(format!("{:?} is shorter than a nanosecond", text))
+            }
+            Some(duration) => Ok(duration),
+            None => Err(format!("{:?} is too long", text)),
+        },
+        _ => Err(format!("{:?} is not a duration", text)),
+    }
+}
+
+/// A [duration](parse_duration) whose window, starting now, ends at a representable date.
+fn parse_window(text: &str) -> Result<Duration, String> {
+    let duration = parse_duration(text)?;
+    match OffsetDateTime::now_utc().checked_add(duration) {
+        Some(_) => Ok(duration),
+        None => Err(format!(
+            "{:?} ends past the latest representable date",
+            text
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rates_and_durations() {
+        assert_eq!(Ok(50_000.0), parse_rate("50000/s"));
+        assert_eq!(Ok(50_000.0), parse_rate("50k"));
+        assert_eq!(Ok(1_000.0), parse_rate("3.6M/h"));
+        assert_eq!(Ok(2.0), parse_rate("120/min"));
+        assert_eq!(Ok(Duration::days(30)), parse_duration("30d"));
+        assert_eq!(Ok(Duration::milliseconds(1500)), parse_duration("1.5s"));
+        assert!(parse_duration("3 parsecs").is_err());
+        assert!(parse_rate("many/s").is_err());
+        assert!(parse_duration("1e30y").is_err());
+        assert!(parse_duration("9999999999999999999999y").is_err());
+        assert!(parse_duration("100000000y").is_ok());
+        assert!(parse_duration("0.0000000001s").is_err());
+        assert!(parse_window("100000000y").is_err());
+        assert!(parse_window("30d").is_ok());
+    }
+}
@@ -0,0 +1,148 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+use time::format_description::well_known::Rfc3339;
+use utid::encoding::{decode_base32, decode_uuid, encode_base32, encode_uuid};
+use utid::presets::{object_id, ulid, uuid_v7};
+use utid::{DynamicSpec, Layout, Segment, SegmentConfig, Value};
+
+use crate::locate;
+
+/// What a segment holds, from its config.
+pub fn kind(segment: &dyn Segment) -> &'static str {
+    match segment.config() {
+        Some(SegmentConfig::Timestamp { .. }) => "timestamp",
+        Some(SegmentConfig::Random { .. }) => "random",
+        Some(SegmentConfig::Constant { .. }) => "constant",
+        None => "segment",
+    }
+}
+
+/// A decoded segment value: timestamps as RFC 3339, everything else in decimal.
+pub fn format_value(value: Value) -> Result<String, Box<dyn Error>> {
+    Ok(match value {
+        Value::Timestamp(timestamp) => timestamp.format(&Rfc3339)?,
+        Value::Integer(value) => value.to_string(),
+    })
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Preset {
+    Ulid,
+    UuidV7,
+    ObjectId,
+}
+
+/// How IDs are written.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Encoding {
+    Base32,
+    Uuid,
+    Hex,
+    Decimal,
+}
+
+impl Encoding {
+    /// Renders the low `bits` of `id`, padded to a fixed width where the encoding allows.
+    pub fn encode(self, id: i128, bits: u8) -> String {
+        match self {
+            Encoding::Base32 => encode_base32(id, bits),
+            Encoding::Uuid => encode_uuid(id),
+            Encoding::Hex => format!(
+                "{:0width$x}",
+                id as u128,
+                width = usize::from(bits).div_ceil(4)
+            ),
+            Encoding::Decimal => (id as u128).to_string(),
+        }
+    }
+
+    /// Guesses how `text` is written: `0x` marks hex, hyphens or braces a UUID and digits alone
+    /// decimal, so base32 IDs of only digits need `--from base32`. Otherwise a string as long as
+    /// base32 for `bits` is taken as base32, as [`Encoding::encode`] would write it, and hex
+    /// digits alone are hex.
+    pub fn detect(text: &str, bits: u8) -> Encoding {
+        let base32_len = usize::from(bits).div_ceil(5);
+        if text.starts_with("0x") || text.starts_with("0X") {
+            Encoding::Hex
+        } else if text.contains(['-', '{']) {
+            Encoding::Uuid
+        } else if text.bytes().all(|byte| byte.is_ascii_digit()) {
+            Encoding::Decimal
+        } else if text.len() == base32_len {
+            Encoding::Base32
+        } else if text.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            Encoding::Hex
+        } else {
+            Encoding::Base32
+        }
+    }
+
+    pub fn decode(self, text: &str) -> Result<i128, Box<dyn Error>> {
+        Ok(match self {
+            Encoding::Base32 => decode_base32(text)?,
+            Encoding::Uuid => decode_uuid(text)?,
+            Encoding::Hex => {
+                let digits = text
+                    .strip_prefix("0x")
+                    .or_else(|| text.strip_prefix("0X"))
+                    .unwrap_or(text);
+                u128::from_str_radix(digits, 16)? as i128
+            }
+            Encoding::Decimal => match text.parse::<u128>() {
+                Ok(id) => id as i128,
+                Err(_) => text.parse::<i128>()?,
+            },
+        })
+    }
+}
+
+/// Selects the layout IDs are generated with or decoded by.
+#[derive(clap::Args)]
+#[group(required = true, multiple = false)]
+pub struct SpecArgs {
+    /// A built-in layout.
+    #[arg(long)]
+    preset: Option<Preset>,
+    /// A spec config file in JSON or TOML, picked by its extension.
+    #[arg(long, value_name = "FILE")]
+    spec: Option<PathBuf>,
+}
+
+/// A spec with the encoding its IDs are usually written in.
+pub struct Loaded {
+    pub spec: DynamicSpec,
+    pub encoding: Encoding,
+}
+
+impl Loaded {
+    pub fn size(&self) -> u8 {
+        self.spec.size()
+    }
+}
+
+impl SpecArgs {
+    pub fn load(&self) -> Result<Loaded, Box<dyn Error>> {
+        let (config, encoding) = match (self.preset, &self.spec) {
+            (Some(Preset::Ulid), _) => (ulid().config(), Encoding::Base32),
+            (Some(Preset::UuidV7), _) => (uuid_v7().config(), Encoding::Uuid),
+            (Some(Preset::ObjectId), _) => (
+                object_id().config().ok_or("object_id has no config")?,
+                Encoding::Hex,
+            ),
+            (None, Some(path)) => {
+                return Ok(Loaded {
+                    spec: DynamicSpec::from_path(path)
+                        .map_err(|error| locate::describe(path, error))?,
+                    encoding: Encoding::Base32,
+                })
+            }
+            (None, None) => return Err("either --preset or --spec is required".into()),
+        };
+        let spec = config
+            .build()
+            .map_err(|errors| format!("invalid preset: {:?}", errors))?;
+        Ok(Loaded { spec, encoding })
+    }
+}
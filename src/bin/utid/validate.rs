@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use utid::{Check, Layout};
+
+use crate::spec::{kind, Encoding, Loaded, SpecArgs};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// An ID, a file with one ID per line, or `-` for IDs on stdin.
+    input: String,
+    #[command(flatten)]
+    spec: SpecArgs,
+    /// How the IDs are written, if guessing from their shape picks the wrong encoding.
+    #[arg(long, value_name = "ENCODING")]
+    encoding: Option<Encoding>,
+}
+
+/// Runs [`Layout::verify`] over every ID: the width check, constants, and each segment's own
+/// checks such as timestamps not lying in the future. Fails if any ID does.
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let loaded = args.spec.load()?;
+    let lines: Box<dyn BufRead> = if args.input == "-" {
+        Box::new(io::stdin().lock())
+    } else if Path::new(&args.input).is_file() {
+        Box::new(BufReader::new(std::fs::File::open(&args.input)?))
+    } else {
+        return match failures(&loaded, args.encoding, &args.input).as_slice() {
+            [] => {
+                println!("valid");
+                Ok(())
+            }
+            failures => {
+                for failure in failures {
+                    println!("{}", failure);
+                }
+                Err("the ID is invalid".into())
+            }
+        };
+    };
+
+    let mut checked = 0;
+    let mut reasons = BTreeMap::<String, usize>::new();
+    let mut invalid = 0;
+    for (number, line) in lines.lines().enumerate() {
+        let line = line?;
+        let id = line.trim();
+        if id.is_empty() {
+            continue;
+        }
+        checked += 1;
+        let failures = failures(&loaded, args.encoding, id);
+        if !failures.is_empty() {
+            invalid += 1;
+        }
+        for failure in failures {
+            println!("line {}: {}: {}", number + 1, id, failure);
+            *reasons.entry(failure).or_default() += 1;
+        }
+    }
+    println!(
+        "checked {}, valid {}, invalid {}",
+        checked,
+        checked - invalid,
+        invalid
+    );
+    for (reason, count) in &reasons {
+        println!("{:>8}  {}", count, reason);
+    }
+    if invalid > 0 {
+        return Err(format!("{} of {} IDs are invalid", invalid, checked).into());
+    }
+    Ok(())
+}
+
+/// Every check `text` fails, each as `check: reason`.
+fn failures(loaded: &Loaded, encoding: Option<Encoding>, text: &str) -> Vec<String> {
+    let encoding = encoding.unwrap_or_else(|| Encoding::detect(text, loaded.size()));
+    let id = match encoding.decode(text) {
+        Ok(id) => id,
+        Err(error) => return vec![format!("parse: {}", error)],
+    };
+    let segments = loaded.spec.segments();
+    loaded
+        .spec
+        .verify(id)
+        .failures()
+        .filter_map(|(check, result)| {
+            let error = result.as_ref().err()?;
+            Some(match check {
+                Check::Encoding => format!("parse: {}", error),
+                Check::Width => format!("width: {}", error),
+                Check::Segment(index) => {
+                    format!("segment {} ({}): {}", index, kind(segments[*index]), error)
+                }
+            })
+        })
+        .collect()
+}
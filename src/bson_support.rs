@@ -0,0 +1,91 @@
+//! MongoDB interop for [`Utid`]. IDs convert to and from `Binary` of the UUID subtype, 16 bytes
+//! big-endian, and IDs of up to 96 bits, such as those of [`presets::object_id`], to and from
+//! `ObjectId`.
+//!
+//! [`presets::object_id`]: crate::presets::object_id
+
+use ::bson::oid::ObjectId;
+use ::bson::spec::BinarySubtype;
+use ::bson::{Binary, Bson};
+
+use crate::encoding::{from_uuid_bytes, uuid_bytes};
+use crate::{Error, Utid};
+
+impl From<Utid> for Binary {
+    fn from(id: Utid) -> Self {
+        Binary {
+            subtype: BinarySubtype::Uuid,
+            bytes: uuid_bytes(id.0).to_vec(),
+        }
+    }
+}
+
+impl From<Utid> for Bson {
+    fn from(id: Utid) -> Self {
+        Bson::Binary(id.into())
+    }
+}
+
+impl TryFrom<Binary> for Utid {
+    type Error = Error;
+
+    fn try_from(binary: Binary) -> Result<Self, Self::Error> {
+        if binary.subtype != BinarySubtype::Uuid {
+            return Err(Error::NonCanonical);
+        }
+        from_uuid_bytes(&binary.bytes).map(Utid)
+    }
+}
+
+impl TryFrom<Utid> for ObjectId {
+    type Error = Error;
+
+    fn try_from(id: Utid) -> Result<Self, Self::Error> {
+        if (id.0 as u128) >> 96 != 0 {
+            return Err(Error::ValueTooLarge {
+                value: id.0 as u128,
+                size: 96,
+            });
+        }
+        let mut bytes = [0; 12];
+        bytes.copy_from_slice(&id.0.to_be_bytes()[4..]);
+        Ok(ObjectId::from_bytes(bytes))
+    }
+}
+
+impl From<ObjectId> for Utid {
+    fn from(oid: ObjectId) -> Self {
+        let mut bytes = [0; 16];
+        bytes[4..].copy_from_slice(&oid.bytes());
+        Utid(i128::from_be_bytes(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presets;
+
+    #[test]
+    fn binary_round_trip() {
+        let id = Utid(-0x0123_4567_89ab_cdef);
+        let binary = Binary::from(id);
+        assert_eq!(BinarySubtype::Uuid, binary.subtype);
+        assert_eq!(id, Utid::try_from(binary).unwrap());
+
+        let generic = Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: vec![0; 16],
+        };
+        assert!(Utid::try_from(generic).is_err());
+    }
+
+    #[test]
+    fn object_id_round_trip() {
+        let id = Utid(presets::object_id().generate().unwrap());
+        let oid = ObjectId::try_from(id).unwrap();
+        assert_eq!(id, Utid::from(oid));
+        assert_eq!(&id.0.to_be_bytes()[4..], &oid.bytes()[..]);
+        assert!(ObjectId::try_from(Utid(1 << 96)).is_err());
+    }
+}
@@ -0,0 +1,263 @@
+//! Sizing estimates for a layout: how likely its random bits are to collide at a given rate,
+//! and which of its segments runs out first.
+//!
+//! IDs can only collide with IDs of the same timestamp tick and constant values, so the
+//! birthday bound is taken per tick over the random bits.
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::vec::Vec;
+
+use time::{Duration, OffsetDateTime};
+
+use crate::{Segment, SegmentConfig};
+
+/// Probability of any collision, over a year of generating at the safe rate, that
+/// [`CollisionEstimate::safe_ids_per_tick`] keeps under.
+pub const SAFE_COLLISION_PROBABILITY: f64 = 1e-6;
+
+const SECONDS_PER_YEAR: f64 = 31_536_000.0;
+
+/// Birthday-bound collision estimate for IDs sharing a timestamp tick.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct CollisionEstimate {
+    /// Random bits of the layout, summed over its random segments.
+    pub random_bits: u32,
+    /// Length of a tick of the most significant timestamp segment in seconds; `None` without
+    /// one, when every ID shares one tick.
+    pub tick_seconds: Option<f64>,
+    /// Probability that two IDs of one tick collide, at the estimated IDs per tick.
+    pub probability: f64,
+    /// Expected colliding pairs among the IDs of one tick.
+    pub expected_collisions: f64,
+    /// Most IDs per tick keeping the probability of any collision over a year under
+    /// [`SAFE_COLLISION_PROBABILITY`]. Without a timestamp, the most IDs ever generated.
+    pub safe_ids_per_tick: f64,
+    /// `safe_ids_per_tick` as IDs per second, if the layout has a timestamp.
+    pub safe_rate: Option<f64>,
+}
+
+/// What stops a layout from generating at the reported rate first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "snake_case"))]
+pub enum Limit {
+    /// The timestamp bits run out.
+    Lifetime,
+    /// The rate exceeds what the per-tick counter holds, so IDs run ahead of the clock.
+    Counter,
+    /// The probability of any collision reaches [`SAFE_COLLISION_PROBABILITY`].
+    Collisions,
+}
+
+/// Capacity of a layout at an expected rate, segment by segment, with the factor limiting it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct CapacityReport {
+    /// Expected IDs per second.
+    pub rate: f64,
+    /// When the timestamp bits run out, if the layout has a timestamp.
+    pub exhaustion_date: Option<OffsetDateTime>,
+    /// Most IDs per second the per-tick counter holds, if the layout has one.
+    pub max_rate: Option<f64>,
+    /// Collision risk of the random bits at `rate`, unless IDs are told apart by a counter.
+    /// Without a timestamp, `probability` is for a year's worth of IDs.
+    pub collisions: Option<CollisionEstimate>,
+    /// How long generating at `rate` stays within every limit; `None` if nothing ever limits it.
+    pub lasts: Option<Duration>,
+    pub limit: Option<Limit>,
+}
+
+pub(crate) fn capacity_report(segments: &[&dyn Segment], rate: f64) -> CapacityReport {
+    let (tick_seconds, random_bits) = inspect(segments);
+    let exhaustion_date = segments
+        .iter()
+        .filter_map(|segment| segment.exhaustion_date())
+        .min();
+    report(tick_seconds, random_bits, 0, exhaustion_date, rate)
+}
+
+/// The report for a layout whose `tick_seconds`-long ticks also hold a `counter_bits` counter.
+pub(crate) fn report(
+    tick_seconds: Option<f64>,
+    random_bits: u32,
+    counter_bits: u32,
+    exhaustion_date: Option<OffsetDateTime>,
+    rate: f64,
+) -> CapacityReport {
+    let max_rate = match (counter_bits, tick_seconds) {
+        (0, _) | (_, None) => None,
+        (bits, Some(tick)) => Some(2f64.powi(bits as i32) / tick),
+    };
+    let collisions = (counter_bits == 0 || random_bits > 0).then(|| {
+        estimate(
+            tick_seconds,
+            random_bits,
+            rate * tick_seconds.unwrap_or(SECONDS_PER_YEAR),
+        )
+    });
+
+    let mut limits = Vec::new();
+    if let Some(date) = exhaustion_date {
+        let left = date - OffsetDateTime::now_utc();
+        limits.push((Limit::Lifetime, left.max(Duration::ZERO)));
+    }
+    if max_rate.is_some_and(|max| rate > max) {
+        limits.push((Limit::Counter, Duration::ZERO));
+    }
+    if collisions.is_some() && rate > 0.0 {
+        // Expected collisions after t seconds: t / tick * (rate * tick)² / 2 / values, or
+        // (rate * t)² / 2 / values without a timestamp; solve for the safe probability.
+        let values = 2f64.powi(random_bits as i32);
+        let seconds = match tick_seconds {
+            Some(tick) => 2.0 * SAFE_COLLISION_PROBABILITY * values / (rate * rate * tick),
+            None => (2.0 * SAFE_COLLISION_PROBABILITY * values).sqrt() / rate,
+        };
+        limits.push((Limit::Collisions, Duration::seconds_f64(seconds.min(1e15))));
+    }
+    let first = limits.into_iter().min_by_key(|(_, lasts)| *lasts);
+    CapacityReport {
+        rate,
+        exhaustion_date,
+        max_rate,
+        collisions,
+        lasts: first.map(|(_, lasts)| lasts),
+        limit: first.map(|(limit, _)| limit),
+    }
+}
+
+/// Tick length of the most significant timestamp segment in seconds, and the random bits.
+fn inspect(segments: &[&dyn Segment]) -> (Option<f64>, u32) {
+    let mut tick_seconds = None;
+    let mut random_bits = 0u32;
+    for segment in segments {
+        match segment.config() {
+            Some(SegmentConfig::Timestamp { unit, .. }) if tick_seconds.is_none() => {
+                tick_seconds = unit.to_nano(1).map(|nanos| nanos as f64 / 1e9);
+            }
+            Some(SegmentConfig::Random { size, .. }) => random_bits += u32::from(size),
+            _ => {}
+        }
+    }
+    (tick_seconds, random_bits)
+}
+
+pub(crate) fn collision_probability(
+    segments: &[&dyn Segment],
+    ids_per_tick: f64,
+) -> CollisionEstimate {
+    let (tick_seconds, random_bits) = inspect(segments);
+    estimate(tick_seconds, random_bits, ids_per_tick)
+}
+
+fn estimate(tick_seconds: Option<f64>, random_bits: u32, ids_per_tick: f64) -> CollisionEstimate {
+    let values = 2f64.powi(random_bits as i32);
+    let pairs = ids_per_tick * (ids_per_tick - 1.0).max(0.0) / 2.0;
+    let expected_collisions = pairs / values;
+    let ticks = tick_seconds.map_or(1.0, |tick| SECONDS_PER_YEAR / tick);
+    // Expected collisions over `ticks` ticks of n IDs are ticks * n² / 2 / values; solve for n.
+    let safe_ids_per_tick = (2.0 * SAFE_COLLISION_PROBABILITY * values / ticks).sqrt();
+    CollisionEstimate {
+        random_bits,
+        tick_seconds,
+        probability: -(-expected_collisions).exp_m1(),
+        expected_collisions,
+        safe_ids_per_tick,
+        safe_rate: tick_seconds.map(|tick| safe_ids_per_tick / tick),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+
+    use super::*;
+    use crate::{
+        ConstantSegment, Layout, RandomSegment, Spec2, Spec3, TimestampSegment, TimestampUnit,
+    };
+
+    #[test]
+    fn birthday_bound_per_tick() {
+        let spec = Spec3 {
+            segments: (
+                Box::new(TimestampSegment::new(
+                    48,
+                    TimestampUnit::Milliseconds,
+                    OffsetDateTime::UNIX_EPOCH,
+                )),
+                Box::new(ConstantSegment::new(16, 7)),
+                Box::new(RandomSegment::new(64)),
+            ),
+        };
+        let estimate = spec.collision_probability(1.0);
+        assert_eq!(64, estimate.random_bits);
+        assert_eq!(Some(1e-3), estimate.tick_seconds);
+        assert_eq!(0.0, estimate.probability);
+
+        // 2^32 IDs in one tick make about 2^63 pairs of 2^64 values: half a collision.
+        let estimate = spec.collision_probability(2f64.powi(32));
+        assert!((estimate.probability - -(-0.5f64).exp_m1()).abs() < 1e-6);
+
+        // Sustained for a year of ms ticks, the safe rate expects SAFE_COLLISION_PROBABILITY
+        // collisions.
+        let safe = estimate.safe_ids_per_tick;
+        let expected = SECONDS_PER_YEAR * 1e3 * safe * safe / 2.0 / 2f64.powi(64);
+        assert!((expected - SAFE_COLLISION_PROBABILITY).abs() < 1e-12);
+        assert_eq!(Some(safe * 1e3), estimate.safe_rate);
+    }
+
+    #[test]
+    fn one_tick_without_a_timestamp() {
+        let spec = Spec2 {
+            segments: (
+                Box::new(ConstantSegment::new(8, 1)),
+                Box::new(RandomSegment::new(32)),
+            ),
+        };
+        let estimate = spec.collision_probability(65_536.0);
+        assert_eq!(None, estimate.safe_rate);
+        assert!((estimate.safe_ids_per_tick - (2e-6 * 2f64.powi(32)).sqrt()).abs() < 1e-9);
+        assert!(estimate.probability > 0.39 && estimate.probability < 0.40);
+    }
+
+    #[test]
+    fn reports_the_first_limit() {
+        let spec = |random| Spec2 {
+            segments: (
+                Box::new(TimestampSegment::new_with_utc_midnight(
+                    48,
+                    TimestampUnit::Milliseconds,
+                    time::Date::from_calendar_date(2023, time::Month::January, 1).unwrap(),
+                )),
+                Box::new(RandomSegment::new(random)),
+            ),
+        };
+        let report = spec(80).capacity_report(1_000.0);
+        assert_eq!(Some(Limit::Lifetime), report.limit);
+        assert_eq!(None, report.max_rate);
+        assert!(report.lasts.unwrap() > Duration::days(365 * 7_000));
+        let left = report.exhaustion_date.unwrap() - OffsetDateTime::now_utc();
+        assert!((left - report.lasts.unwrap()).abs() < Duration::SECOND);
+
+        let report = spec(16).capacity_report(1_000.0);
+        assert_eq!(Some(Limit::Collisions), report.limit);
+        assert!(report.lasts.unwrap() < Duration::milliseconds(1));
+        assert!(report.collisions.unwrap().safe_rate.unwrap() < 1.0);
+    }
+
+    #[cfg(target_has_atomic = "64")]
+    #[test]
+    fn snowflakes_are_limited_by_their_sequence() {
+        let snowflake = crate::presets::snowflake(
+            time::Date::from_calendar_date(2020, time::Month::January, 1).unwrap(),
+            1,
+        )
+        .unwrap();
+        let report = snowflake.capacity_report(5e6);
+        assert_eq!(Some(4.096e6), report.max_rate);
+        assert_eq!(None, report.collisions);
+        assert_eq!(Some(Limit::Counter), report.limit);
+        assert_eq!(Some(Duration::ZERO), report.lasts);
+        assert_eq!(Some(Limit::Lifetime), snowflake.capacity_report(1e6).limit);
+    }
+}
@@ -0,0 +1,84 @@
+//! `chrono` interop, for code bases that have not moved to `time`. Epochs convert from
+//! [`DateTime<Utc>`] and [`TimestampSegment`] decodes to it.
+
+use ::chrono::{DateTime, Utc};
+use time::OffsetDateTime;
+
+use crate::{DateTimeType, Error, TimestampSegmentAs};
+
+pub fn from_chrono(datetime: DateTime<Utc>) -> Result<OffsetDateTime, Error> {
+    // A leap second's nanoseconds exceed one second; they roll over into the next one.
+    let nanos = i128::from(datetime.timestamp()) * 1_000_000_000
+        + i128::from(datetime.timestamp_subsec_nanos());
+    Ok(OffsetDateTime::from_unix_timestamp_nanos(nanos)?)
+}
+
+pub fn to_chrono(datetime: OffsetDateTime) -> Result<DateTime<Utc>, Error> {
+    DateTime::from_timestamp(datetime.unix_timestamp(), datetime.nanosecond())
+        .ok_or(Error::Overflow)
+}
+
+/// A [`crate::TimestampSegment`] that decodes to [`DateTime<Utc>`].
+pub type TimestampSegment = TimestampSegmentAs<DateTime<Utc>>;
+
+impl DateTimeType for DateTime<Utc> {
+    fn from_time(datetime: OffsetDateTime) -> Result<Self, Error> {
+        to_chrono(datetime)
+    }
+
+    fn to_time(&self) -> Result<OffsetDateTime, Error> {
+        from_chrono(*self)
+    }
+
+    fn latest() -> Self {
+        DateTime::<Utc>::MAX_UTC
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::chrono::{NaiveDate, TimeZone};
+
+    use super::*;
+    use crate::{RandomSegment, Spec2, SpecSegment, TimestampUnit};
+
+    #[test]
+    fn round_trips_instants() {
+        let datetime = Utc.with_ymd_and_hms(2023, 5, 17, 12, 30, 45).unwrap()
+            + ::chrono::Duration::nanoseconds(123_456_789);
+        let converted = from_chrono(datetime).unwrap();
+        assert_eq!(1_684_326_645_123_456_789, converted.unix_timestamp_nanos());
+        assert_eq!(datetime, to_chrono(converted).unwrap());
+    }
+
+    #[test]
+    fn decodes_to_chrono() {
+        let epoch = NaiveDate::from_ymd_opt(2023, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc();
+        let spec = Spec2::<i128, DateTime<Utc>, i128> {
+            segments: (
+                Box::new(TimestampSegment::new(48, TimestampUnit::Milliseconds, epoch).unwrap()),
+                Box::new(RandomSegment::new(80)),
+            ),
+        };
+        let (timestamp, _) = spec.decompose(spec.generate().unwrap()).unwrap();
+        assert!(
+            (to_chrono(OffsetDateTime::now_utc()).unwrap() - timestamp)
+                .num_seconds()
+                .abs()
+                < 2
+        );
+        assert!(spec.segments.0.config().is_some());
+    }
+
+    #[test]
+    fn epochs_off_midnight_have_no_config() {
+        let epoch = Utc.with_ymd_and_hms(2023, 1, 1, 6, 0, 0).unwrap();
+        let segment = TimestampSegment::new(48, TimestampUnit::Milliseconds, epoch).unwrap();
+        assert!(segment.config().is_none());
+        assert_eq!(epoch, segment.decode(0).unwrap());
+    }
+}
@@ -0,0 +1,265 @@
+//! Clocks to pass to [`TimestampSegment::with_clock`](crate::TimestampSegment::with_clock).
+
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use time::OffsetDateTime;
+
+use crate::Error;
+
+/// The system time, cached and refreshed every `interval` by a background thread or whenever
+/// [`refresh`](Self::refresh) is called, so that services generating millions of IDs per second
+/// read an atomic instead of the system clock. Timestamps lag by up to `interval`, plus
+/// scheduling delays of the updater, and never go backwards.
+///
+/// Clocks are passed as function pointers, so declare it as a `static` and pass a function
+/// reading it, such as `|| CLOCK.now()`.
+#[derive(Debug)]
+pub struct CoarseClock {
+    /// Nanoseconds since the Unix epoch; zero until first read.
+    nanos: AtomicI64,
+    interval: Duration,
+    started: AtomicBool,
+}
+
+impl CoarseClock {
+    pub const fn new(interval: Duration) -> Self {
+        Self {
+            nanos: AtomicI64::new(0),
+            interval,
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// The cached time, read from the system clock only if it was never read before.
+    pub fn now(&self) -> OffsetDateTime {
+        match self.nanos.load(Ordering::Relaxed) {
+            0 => self.refresh(),
+            nanos => from_nanos(nanos),
+        }
+    }
+
+    /// Reads the system clock into the cache, for callers refreshing on their own schedule,
+    /// e.g. once per event loop tick. Returns the new cached time.
+    pub fn refresh(&self) -> OffsetDateTime {
+        let nanos =
+            i64::try_from(OffsetDateTime::now_utc().unix_timestamp_nanos()).unwrap_or(i64::MAX);
+        let previous = self.nanos.fetch_max(nanos, Ordering::Relaxed);
+        from_nanos(previous.max(nanos))
+    }
+
+    /// Spawns the thread refreshing the cache every `interval`, unless it is running already.
+    pub fn start(&'static self) -> Result<(), Error> {
+        if self.started.swap(true, Ordering::Relaxed) {
+            return Ok(());
+        }
+        self.refresh();
+        thread::Builder::new()
+            .name("utid-clock".into())
+            .spawn(move || loop {
+                thread::sleep(self.interval);
+                self.refresh();
+            })
+            .map(drop)
+            .map_err(|_| {
+                self.started.store(false, Ordering::Relaxed);
+                Error::Unsupported {
+                    reason: "cannot spawn the clock updater thread",
+                }
+            })
+    }
+}
+
+/// Something a [`DriftMonitor`] noticed about the clock it watches. Durations are positive when
+/// the clock moved ahead of the monotonic reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockAnomaly {
+    /// The clock read `by` earlier than it did on the previous read.
+    Backwards { by: time::Duration },
+    /// Between two reads, the clock moved `by` more than the monotonic reference did.
+    Jump { by: time::Duration },
+    /// Since the last anomaly, or the first read, the clock slowly gained `by` on the
+    /// monotonic reference, e.g. while NTP slews it.
+    Drift { by: time::Duration },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Reading {
+    monotonic: Instant,
+    wall: OffsetDateTime,
+}
+
+/// The system time, compared on every read against a monotonic reference, so jumps and drift
+/// are reported when they happen rather than found later as IDs stored out of order. Anomalies
+/// beyond `tolerance` clear the [health flag](Self::is_healthy) and call the callback, if any;
+/// readings are passed through unchanged.
+///
+/// Clocks are passed as function pointers, so declare it as a `static` and pass a function
+/// reading it, such as `|| CLOCK.now()`. Clocks other than the system one can be watched by
+/// passing their readings to [`observe`](Self::observe).
+#[derive(Debug)]
+pub struct DriftMonitor {
+    tolerance: Duration,
+    callback: Option<fn(ClockAnomaly)>,
+    /// The read anomalies are measured from, and the previous read.
+    readings: Mutex<Option<(Reading, Reading)>>,
+    healthy: AtomicBool,
+    anomalies: AtomicU64,
+}
+
+impl DriftMonitor {
+    pub const fn new(tolerance: Duration) -> Self {
+        Self {
+            tolerance,
+            callback: None,
+            readings: Mutex::new(None),
+            healthy: AtomicBool::new(true),
+            anomalies: AtomicU64::new(0),
+        }
+    }
+
+    /// Calls `callback` with every anomaly, on the thread reading the clock.
+    pub const fn with_callback(mut self, callback: fn(ClockAnomaly)) -> Self {
+        self.callback = Some(callback);
+        self
+    }
+
+    /// Reads the system clock and checks it.
+    pub fn now(&self) -> OffsetDateTime {
+        self.observe(OffsetDateTime::now_utc())
+    }
+
+    /// Checks a reading of the watched clock, taken just now, and returns it.
+    pub fn observe(&self, wall: OffsetDateTime) -> OffsetDateTime {
+        self.observe_at(Instant::now(), wall);
+        wall
+    }
+
+    fn observe_at(&self, monotonic: Instant, wall: OffsetDateTime) -> Option<ClockAnomaly> {
+        let reading = Reading { monotonic, wall };
+        let mut readings = self.readings.lock().unwrap_or_else(PoisonError::into_inner);
+        let (anchor, last) = readings.get_or_insert((reading, reading));
+        let gained = |since: &Reading| {
+            let elapsed = monotonic.saturating_duration_since(since.monotonic);
+            (wall - since.wall) - elapsed
+        };
+        let tolerance = self.tolerance;
+        let anomaly = if wall < last.wall {
+            Some(ClockAnomaly::Backwards {
+                by: last.wall - wall,
+            })
+        } else if gained(last).unsigned_abs() > tolerance {
+            Some(ClockAnomaly::Jump { by: gained(last) })
+        } else if gained(anchor).unsigned_abs() > tolerance {
+            Some(ClockAnomaly::Drift { by: gained(anchor) })
+        } else {
+            None
+        };
+        if anomaly.is_some() {
+            *anchor = reading;
+        }
+        *last = reading;
+        drop(readings);
+
+        if let Some(anomaly) = anomaly {
+            self.healthy.store(false, Ordering::Relaxed);
+            self.anomalies.fetch_add(1, Ordering::Relaxed);
+            if let Some(callback) = self.callback {
+                callback(anomaly);
+            }
+        }
+        anomaly
+    }
+
+    /// False once an anomaly was seen, until [`reset`](Self::reset).
+    pub fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::Relaxed)
+    }
+
+    pub fn reset(&self) {
+        self.healthy.store(true, Ordering::Relaxed);
+    }
+
+    /// Number of anomalies seen so far.
+    pub fn anomalies(&self) -> u64 {
+        self.anomalies.load(Ordering::Relaxed)
+    }
+}
+
+fn from_nanos(nanos: i64) -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp_nanos(i128::from(nanos))
+        .unwrap_or(OffsetDateTime::UNIX_EPOCH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SpecSegment, TimestampSegment, TimestampUnit};
+
+    #[test]
+    fn caches_until_refreshed() {
+        let clock = CoarseClock::new(Duration::from_secs(3600));
+        let first = clock.now();
+        thread::sleep(Duration::from_millis(2));
+        assert_eq!(first, clock.now());
+        let refreshed = clock.refresh();
+        assert!(refreshed > first);
+        assert_eq!(refreshed, clock.now());
+    }
+
+    #[test]
+    fn updates_in_the_background() {
+        static CLOCK: CoarseClock = CoarseClock::new(Duration::from_millis(1));
+        fn coarse_now() -> OffsetDateTime {
+            CLOCK.now()
+        }
+        CLOCK.start().unwrap();
+        CLOCK.start().unwrap();
+        let segment =
+            TimestampSegment::new(64, TimestampUnit::Nanoseconds, OffsetDateTime::UNIX_EPOCH)
+                .with_clock(coarse_now);
+        let first = segment.encode().unwrap();
+        thread::sleep(Duration::from_millis(50));
+        let later = segment.encode().unwrap();
+        assert!(later > first);
+        assert!((OffsetDateTime::now_utc() - coarse_now()).whole_milliseconds() < 50);
+    }
+
+    #[test]
+    fn reports_jumps_and_drift() {
+        static REPORTED: AtomicU64 = AtomicU64::new(0);
+        let monitor = DriftMonitor::new(Duration::from_millis(10)).with_callback(|_| {
+            REPORTED.fetch_add(1, Ordering::Relaxed);
+        });
+        let start = Instant::now();
+        let wall = OffsetDateTime::UNIX_EPOCH;
+        let at = |millis: u64| start + Duration::from_millis(millis);
+        let ms = time::Duration::milliseconds;
+
+        assert_eq!(None, monitor.observe_at(at(0), wall));
+        assert_eq!(None, monitor.observe_at(at(100), wall + ms(105)));
+        assert!(monitor.is_healthy());
+        // 6ms more: still within tolerance between reads, but 11ms over since the first.
+        assert_eq!(
+            Some(ClockAnomaly::Drift { by: ms(11) }),
+            monitor.observe_at(at(200), wall + ms(211))
+        );
+        assert!(!monitor.is_healthy());
+        monitor.reset();
+
+        assert_eq!(
+            Some(ClockAnomaly::Jump { by: ms(-50) }),
+            monitor.observe_at(at(300), wall + ms(261))
+        );
+        assert_eq!(
+            Some(ClockAnomaly::Backwards { by: ms(1) }),
+            monitor.observe_at(at(300), wall + ms(260))
+        );
+        assert_eq!(None, monitor.observe_at(at(400), wall + ms(360)));
+        assert_eq!(3, monitor.anomalies());
+        assert_eq!(3, REPORTED.load(Ordering::Relaxed));
+        assert!(!monitor.is_healthy());
+    }
+}
@@ -0,0 +1,87 @@
+//! Column representations of a [`Utid`] for the SQL integrations. The type picks the column:
+//! [`Utid`] binds as `uuid`, [`UtidBytes`] as 16-byte `bytea` and [`UtidBigInt`] as `int8`.
+//! All three sort like the unsigned ID.
+
+use crate::{Error, Utid};
+
+/// An ID bound as 16 big-endian bytes, for `bytea` columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow),
+    diesel(sql_type = diesel::sql_types::Binary)
+)]
+pub struct UtidBytes(pub Utid);
+
+impl From<Utid> for UtidBytes {
+    fn from(id: Utid) -> Self {
+        UtidBytes(id)
+    }
+}
+
+impl From<UtidBytes> for Utid {
+    fn from(id: UtidBytes) -> Self {
+        id.0
+    }
+}
+
+/// An ID of a spec up to 63 bits wide, for `int8` columns. Checked when built, so binding
+/// never fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow),
+    diesel(sql_type = diesel::sql_types::BigInt)
+)]
+pub struct UtidBigInt(i64);
+
+impl UtidBigInt {
+    pub fn get(self) -> i64 {
+        self.0
+    }
+}
+
+/// Fails for IDs outside `0..=i64::MAX`.
+impl TryFrom<Utid> for UtidBigInt {
+    type Error = Error;
+
+    fn try_from(id: Utid) -> Result<Self, Self::Error> {
+        match i64::try_from(id) {
+            Ok(value) if value >= 0 => Ok(UtidBigInt(value)),
+            _ => Err(Error::ValueTooLarge {
+                value: id.0 as u128,
+                size: 63,
+            }),
+        }
+    }
+}
+
+impl TryFrom<i64> for UtidBigInt {
+    type Error = Error;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        UtidBigInt::try_from(Utid(value.into()))
+    }
+}
+
+impl From<UtidBigInt> for Utid {
+    fn from(id: UtidBigInt) -> Self {
+        Utid(id.0.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn big_ints_hold_63_bits() {
+        let id = Utid(i64::MAX.into());
+        assert_eq!(id, Utid::from(UtidBigInt::try_from(id).unwrap()));
+        assert!(matches!(
+            UtidBigInt::try_from(Utid(1 << 63)),
+            Err(Error::ValueTooLarge { size: 63, .. })
+        ));
+        assert!(UtidBigInt::try_from(-1i64).is_err());
+    }
+}
@@ -0,0 +1,250 @@
+use alloc::vec::Vec;
+
+use crate::{SegmentConfig, SpecConfig};
+
+/// How IDs generated under the old spec fare under the new one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Compatibility {
+    Identical,
+    /// Every old ID still decodes to the same segment values, though new IDs may differ.
+    DecodeCompatible,
+    /// Some old IDs decode to different values.
+    Breaking,
+}
+
+/// One difference between two layouts; `segment` indexes the old spec unless noted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// No segment of the new spec starts at the old segment's bit offset.
+    Moved {
+        segment: usize,
+    },
+    Retyped {
+        segment: usize,
+    },
+    Resized {
+        segment: usize,
+        from: u8,
+        to: u8,
+    },
+    /// Same kind and offset, different unit, epoch, clock skew, constant or random source.
+    Reconfigured {
+        segment: usize,
+    },
+    /// A segment of the new spec, at this index, above the old spec's width. Old IDs read as 0.
+    Added {
+        segment: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompatReport {
+    pub compatibility: Compatibility,
+    pub changes: Vec<(Change, Compatibility)>,
+}
+
+/// Segments with their bit offset from the least significant end.
+fn offsets(config: &SpecConfig) -> Vec<(u32, &SegmentConfig)> {
+    let mut offset = 0u32;
+    let mut segments: Vec<(u32, &SegmentConfig)> = config
+        .segments
+        .iter()
+        .rev()
+        .map(|segment| {
+            let start = offset;
+            offset += u32::from(segment.size());
+            (start, segment)
+        })
+        .collect();
+    segments.reverse();
+    segments
+}
+
+fn same_kind(old: &SegmentConfig, new: &SegmentConfig) -> bool {
+    core::mem::discriminant(old) == core::mem::discriminant(new)
+}
+
+fn reconfigured(old: &SegmentConfig, new: &SegmentConfig) -> Option<Compatibility> {
+    match (old, new) {
+        (
+            SegmentConfig::Timestamp {
+                unit: old_unit,
+                epoch: old_epoch,
+                ..
+            },
+            SegmentConfig::Timestamp { unit, epoch, .. },
+        ) if old_unit != unit || old_epoch != epoch => Some(Compatibility::Breaking),
+        (
+            SegmentConfig::Timestamp {
+                max_skew: old_max_skew,
+                ..
+            },
+            SegmentConfig::Timestamp { max_skew, .. },
+        ) if old_max_skew != max_skew => Some(Compatibility::DecodeCompatible),
+        (
+            SegmentConfig::Random {
+                os_rng: old_os_rng, ..
+            },
+            SegmentConfig::Random { os_rng, .. },
+        ) if old_os_rng != os_rng => Some(Compatibility::DecodeCompatible),
+        // Constants decode to whatever the ID holds, so old IDs keep their old value.
+        (
+            SegmentConfig::Constant {
+                value: old_value, ..
+            },
+            SegmentConfig::Constant { value, .. },
+        ) if old_value != value => Some(Compatibility::DecodeCompatible),
+        _ => None,
+    }
+}
+
+pub(crate) fn compat(old: &SpecConfig, new: &SpecConfig) -> CompatReport {
+    let old_segments = offsets(old);
+    let new_segments = offsets(new);
+    let mut changes = Vec::new();
+
+    for (index, (offset, segment)) in old_segments.iter().enumerate() {
+        let Some((_, replacement)) = new_segments.iter().find(|(start, _)| start == offset) else {
+            changes.push((Change::Moved { segment: index }, Compatibility::Breaking));
+            continue;
+        };
+        if !same_kind(segment, replacement) {
+            changes.push((Change::Retyped { segment: index }, Compatibility::Breaking));
+            continue;
+        }
+        let (from, to) = (segment.size(), replacement.size());
+        if from != to {
+            // Only the most significant segment can grow without covering bits of another one.
+            let compatibility = if to > from && index == 0 {
+                Compatibility::DecodeCompatible
+            } else {
+                Compatibility::Breaking
+            };
+            changes.push((
+                Change::Resized {
+                    segment: index,
+                    from,
+                    to,
+                },
+                compatibility,
+            ));
+        }
+        if let Some(compatibility) = reconfigured(segment, replacement) {
+            changes.push((Change::Reconfigured { segment: index }, compatibility));
+        }
+    }
+
+    let old_width: u32 = old.segments.iter().map(|s| u32::from(s.size())).sum();
+    for (index, (offset, _)) in new_segments.iter().enumerate() {
+        if *offset >= old_width {
+            changes.push((
+                Change::Added { segment: index },
+                Compatibility::DecodeCompatible,
+            ));
+        }
+    }
+
+    let compatibility = changes
+        .iter()
+        .map(|(_, compatibility)| *compatibility)
+        .max()
+        .unwrap_or(Compatibility::Identical);
+    CompatReport {
+        compatibility,
+        changes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Date, Duration};
+
+    use super::*;
+    use crate::{SpecError, TimestampUnit};
+
+    fn timestamp(size: u8) -> SegmentConfig {
+        SegmentConfig::Timestamp {
+            size,
+            unit: TimestampUnit::Milliseconds,
+            epoch: Date::from_calendar_date(2023, time::Month::January, 1).unwrap(),
+            max_skew: Duration::MINUTE,
+        }
+    }
+
+    fn random(size: u8) -> SegmentConfig {
+        SegmentConfig::Random {
+            size,
+            os_rng: false,
+        }
+    }
+
+    fn spec(segments: Vec<SegmentConfig>) -> SpecConfig {
+        SpecConfig { segments }
+    }
+
+    #[test]
+    fn classify_changes() {
+        let old = spec(vec![timestamp(48), random(80)]);
+        assert_eq!(
+            Compatibility::Identical,
+            old.compat(&old).unwrap().compatibility
+        );
+
+        let widened = spec(vec![timestamp(52), random(76)]);
+        let report = old.compat(&widened).unwrap();
+        assert_eq!(Compatibility::Breaking, report.compatibility);
+        assert_eq!(
+            (Change::Moved { segment: 0 }, Compatibility::Breaking),
+            report.changes[0]
+        );
+
+        let narrower = spec(vec![timestamp(48), random(72)]);
+        let grown = spec(vec![timestamp(56), random(72)]);
+        assert_eq!(
+            vec![(
+                Change::Resized {
+                    segment: 0,
+                    from: 48,
+                    to: 56
+                },
+                Compatibility::DecodeCompatible
+            )],
+            narrower.compat(&grown).unwrap().changes
+        );
+
+        let versioned = spec(vec![
+            SegmentConfig::Constant { size: 8, value: 1 },
+            timestamp(48),
+            random(72),
+        ]);
+        let report = narrower.compat(&versioned).unwrap();
+        assert_eq!(Compatibility::DecodeCompatible, report.compatibility);
+        assert_eq!(
+            (
+                Change::Added { segment: 0 },
+                Compatibility::DecodeCompatible
+            ),
+            report.changes[0]
+        );
+
+        let swapped = spec(vec![random(48), timestamp(80)]);
+        assert_eq!(
+            (Change::Retyped { segment: 1 }, Compatibility::Breaking),
+            old.compat(&swapped).unwrap().changes[1]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_layouts() {
+        let old = spec(vec![timestamp(48), random(80)]);
+        let too_wide = spec(vec![timestamp(56), random(80)]);
+        assert_eq!(
+            Err(vec![SpecError::TooWide { size: 136 }]),
+            old.compat(&too_wide)
+        );
+        assert_eq!(
+            Err(vec![SpecError::TooWide { size: 136 }]),
+            too_wide.compat(&old)
+        );
+    }
+}
@@ -0,0 +1,678 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use time::{Date, Duration, OffsetDateTime};
+
+use crate::{
+    compat, describe, mask, unpack, CompatReport, ConstantSegment, Error, Layout, RandomSegment,
+    Segment, SpecError, SpecSegment, TimestampSegment, TimestampUnit, Utid,
+};
+
+/// Plain description of a built-in segment, enough to rebuild it exactly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)
+)]
+pub enum SegmentConfig {
+    Timestamp {
+        size: u8,
+        unit: TimestampUnit,
+        /// Counting starts at midnight UTC of this date.
+        epoch: Date,
+        /// How far ahead of the verifying host's clock a timestamp may lie. Serialized in
+        /// milliseconds, so finer skew fails to serialize.
+        #[cfg_attr(
+            feature = "serde",
+            serde(
+                rename = "max_skew_ms",
+                default = "default_max_skew",
+                with = "max_skew_ms"
+            )
+        )]
+        max_skew: Duration,
+    },
+    Random {
+        size: u8,
+        #[cfg_attr(feature = "serde", serde(default))]
+        os_rng: bool,
+    },
+    Constant {
+        size: u8,
+        #[cfg_attr(feature = "serde", serde(with = "constant_value"))]
+        value: i128,
+    },
+}
+
+impl SegmentConfig {
+    pub fn size(&self) -> u8 {
+        match self {
+            SegmentConfig::Timestamp { size, .. }
+            | SegmentConfig::Random { size, .. }
+            | SegmentConfig::Constant { size, .. } => *size,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn default_max_skew() -> Duration {
+    Duration::MINUTE
+}
+
+#[cfg(feature = "serde")]
+mod max_skew_ms {
+    use serde::{de, ser, Deserialize, Deserializer, Serializer};
+    use time::Duration;
+
+    /// Fails for sub-millisecond skew rather than truncating it, so round trips keep the spec.
+    pub fn serialize<S: Serializer>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        if value.subsec_nanoseconds() % 1_000_000 != 0 {
+            return Err(ser::Error::custom("max skew must be whole milliseconds"));
+        }
+        let millis = u64::try_from(value.whole_milliseconds())
+            .map_err(|_| ser::Error::custom("max skew must be non-negative and fit in u64 ms"))?;
+        serializer.serialize_u64(millis)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let millis = u64::deserialize(deserializer)?;
+        i64::try_from(millis)
+            .map(Duration::milliseconds)
+            .map_err(|_| de::Error::custom("max skew out of range"))
+    }
+}
+
+/// Internally tagged enums buffer their fields in a form that has no 128-bit integers, so
+/// constants outside the `i64` range travel as decimal strings.
+#[cfg(feature = "serde")]
+mod constant_value {
+    use core::fmt;
+
+    use serde::{de, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &i128, serializer: S) -> Result<S::Ok, S::Error> {
+        match i64::try_from(*value) {
+            Ok(value) => serializer.serialize_i64(value),
+            Err(_) => serializer.collect_str(value),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i128, D::Error> {
+        deserializer.deserialize_any(Visitor)
+    }
+
+    struct Visitor;
+
+    impl de::Visitor<'_> for Visitor {
+        type Value = i128;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an integer or a decimal string")
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<i128, E> {
+            Ok(value.into())
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<i128, E> {
+            Ok(value.into())
+        }
+
+        fn visit_i128<E: de::Error>(self, value: i128) -> Result<i128, E> {
+            Ok(value)
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<i128, E> {
+            value.parse().map_err(E::custom)
+        }
+    }
+}
+
+/// Segments of a spec from the most significant to the least, e.g. to persist next to the IDs
+/// a spec generated and decode them long after the code that defined it has changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(deny_unknown_fields)
+)]
+pub struct SpecConfig {
+    pub segments: Vec<SegmentConfig>,
+}
+
+impl SpecConfig {
+    /// JSON Schema (draft 2020-12) of the serialized form, for validating spec configs in CI
+    /// pipelines and editors.
+    #[cfg(feature = "json")]
+    pub fn json_schema() -> serde_json::Value {
+        let size = serde_json::json!({ "type": "integer", "minimum": 1, "maximum": 128 });
+        serde_json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "utid spec",
+            "type": "object",
+            "required": ["segments"],
+            "additionalProperties": false,
+            "properties": {
+                "segments": {
+                    "description": "Segments from the most significant to the least.",
+                    "type": "array",
+                    "minItems": 1,
+                    "items": {
+                        "oneOf": [
+                            {
+                                "type": "object",
+                                "required": ["kind", "size", "unit", "epoch"],
+                                "additionalProperties": false,
+                                "properties": {
+                                    "kind": { "const": "timestamp" },
+                                    "size": size,
+                                    "unit": {
+                                        "enum": ["seconds", "milliseconds", "microseconds", "nanoseconds"]
+                                    },
+                                    "epoch": {
+                                        "description": "Counting starts at midnight UTC of this date.",
+                                        "type": "string",
+                                        "format": "date"
+                                    },
+                                    "max_skew_ms": {
+                                        "description": "How far ahead of the verifying host's clock a timestamp may lie. Defaults to one minute.",
+                                        "type": "integer",
+                                        "minimum": 0
+                                    }
+                                }
+                            },
+                            {
+                                "type": "object",
+                                "required": ["kind", "size"],
+                                "additionalProperties": false,
+                                "properties": {
+                                    "kind": { "const": "random" },
+                                    "size": size,
+                                    "os_rng": { "type": "boolean", "default": false }
+                                }
+                            },
+                            {
+                                "type": "object",
+                                "required": ["kind", "size", "value"],
+                                "additionalProperties": false,
+                                "properties": {
+                                    "kind": { "const": "constant" },
+                                    "size": size,
+                                    "value": {
+                                        "description": "Values outside the 64-bit range are decimal strings.",
+                                        "oneOf": [
+                                            { "type": "integer" },
+                                            { "type": "string", "pattern": "^-?[0-9]+$" }
+                                        ]
+                                    }
+                                }
+                            }
+                        ]
+                    }
+                }
+            }
+        })
+    }
+
+    /// Short hash identifying the ID format, e.g. to label metrics per spec across a fleet. It
+    /// covers every segment's kind, width, unit, epoch and value, but not which RNG a random
+    /// segment draws from or how much clock skew a timestamp tolerates, so specs that produce
+    /// interchangeable IDs share a fingerprint.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Sha256::new();
+        for segment in &self.segments {
+            match segment {
+                SegmentConfig::Timestamp {
+                    size, unit, epoch, ..
+                } => {
+                    hasher.update([0, *size, *unit as u8]);
+                    hasher.update(epoch.to_julian_day().to_be_bytes());
+                }
+                SegmentConfig::Random { size, .. } => hasher.update([1, *size]),
+                SegmentConfig::Constant { size, value } => {
+                    hasher.update([2, *size]);
+                    hasher.update(value.to_be_bytes());
+                }
+            }
+        }
+        let mut fingerprint = [0; 8];
+        fingerprint.copy_from_slice(&hasher.finalize()[..8]);
+        u64::from_be_bytes(fingerprint)
+    }
+
+    /// Classifies the changes from this layout to `other`, e.g. to gate deployments that change
+    /// the ID format. Fails with the layout violations of whichever is invalid, `self` first.
+    pub fn compat(&self, other: &SpecConfig) -> Result<CompatReport, Vec<SpecError>> {
+        self.validate()?;
+        other.validate()?;
+        Ok(compat::compat(self, other))
+    }
+
+    fn validate(&self) -> Result<(), Vec<SpecError>> {
+        let segments: Vec<AnySegment> = self.segments.iter().map(AnySegment::new).collect();
+        let segments: Vec<&dyn Segment> = segments
+            .iter()
+            .map(|segment| segment as &dyn Segment)
+            .collect();
+        crate::validation::validate(&segments)
+    }
+
+    /// Builds a spec from the description, reporting every layout violation.
+    pub fn build(&self) -> Result<DynamicSpec, Vec<SpecError>> {
+        let segments: Vec<AnySegment> = self.segments.iter().map(AnySegment::new).collect();
+        let sizes: Vec<u8> = segments.iter().map(AnySegment::size).collect();
+        let spec = DynamicSpec {
+            size: sizes.iter().map(|size| u32::from(*size)).sum(),
+            sizes,
+            exhaustion_date: segments
+                .iter()
+                .filter_map(AnySegment::exhaustion_date)
+                .min(),
+            segments,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        };
+        spec.validate()?;
+        Ok(spec)
+    }
+}
+
+/// What one segment of a [`DynamicSpec`] decodes to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    Timestamp(OffsetDateTime),
+    Integer(i128),
+}
+
+/// A spec whose layout is only known at runtime, built from a [`SpecConfig`]. Its sizes, total
+/// size and exhaustion date are computed once when it is built.
+#[derive(Clone)]
+pub struct DynamicSpec {
+    segments: Vec<AnySegment>,
+    sizes: Vec<u8>,
+    size: u32,
+    exhaustion_date: Option<OffsetDateTime>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<crate::metrics_support::SpecMetrics>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AnySegment {
+    Timestamp(TimestampSegment),
+    Random(RandomSegment),
+    Constant(ConstantSegment<i128>),
+}
+
+impl AnySegment {
+    fn new(config: &SegmentConfig) -> Self {
+        match config {
+            SegmentConfig::Timestamp {
+                size,
+                unit,
+                epoch,
+                max_skew,
+            } => AnySegment::Timestamp(
+                TimestampSegment::new_with_utc_midnight(*size, *unit, *epoch)
+                    .with_max_skew(*max_skew),
+            ),
+            SegmentConfig::Random { size, os_rng } => AnySegment::Random(if *os_rng {
+                RandomSegment::with_os_rng(*size)
+            } else {
+                RandomSegment::new(*size)
+            }),
+            SegmentConfig::Constant { size, value } => {
+                AnySegment::Constant(ConstantSegment::new(*size, *value))
+            }
+        }
+    }
+
+    fn encode(&self) -> Result<i128, Error> {
+        match self {
+            AnySegment::Timestamp(segment) => segment.encode(),
+            AnySegment::Random(segment) => segment.encode(),
+            AnySegment::Constant(segment) => segment.encode(),
+        }
+    }
+
+    fn decode(&self, encoded: i128) -> Result<Value, Error> {
+        match self {
+            AnySegment::Timestamp(segment) => segment.decode(encoded).map(Value::Timestamp),
+            AnySegment::Random(segment) => segment.decode(encoded).map(Value::Integer),
+            AnySegment::Constant(segment) => segment.decode(encoded).map(Value::Integer),
+        }
+    }
+}
+
+impl Segment for AnySegment {
+    fn size(&self) -> u8 {
+        match self {
+            AnySegment::Timestamp(segment) => segment.size(),
+            AnySegment::Random(segment) => segment.size(),
+            AnySegment::Constant(segment) => segment.size(),
+        }
+    }
+
+    fn encode(&self) -> Result<i128, Error> {
+        AnySegment::encode(self)
+    }
+
+    fn verify(&self, encoded: i128) -> Result<(), Error> {
+        match self {
+            AnySegment::Timestamp(segment) => segment.verify(encoded),
+            AnySegment::Random(segment) => segment.verify(encoded),
+            AnySegment::Constant(segment) => segment.verify(encoded),
+        }
+    }
+
+    fn fixed(&self) -> Option<i128> {
+        match self {
+            AnySegment::Timestamp(segment) => segment.fixed(),
+            AnySegment::Random(segment) => segment.fixed(),
+            AnySegment::Constant(segment) => segment.fixed(),
+        }
+    }
+
+    fn exhaustion_date(&self) -> Option<OffsetDateTime> {
+        match self {
+            AnySegment::Timestamp(segment) => segment.exhaustion_date(),
+            AnySegment::Random(segment) => segment.exhaustion_date(),
+            AnySegment::Constant(segment) => segment.exhaustion_date(),
+        }
+    }
+
+    fn config(&self) -> Option<SegmentConfig> {
+        match self {
+            AnySegment::Timestamp(segment) => segment.config(),
+            AnySegment::Random(segment) => segment.config(),
+            AnySegment::Constant(segment) => segment.config(),
+        }
+    }
+
+    fn keys_partition(&self) -> bool {
+        match self {
+            AnySegment::Timestamp(segment) => segment.keys_partition(),
+            AnySegment::Random(segment) => segment.keys_partition(),
+            AnySegment::Constant(segment) => segment.keys_partition(),
+        }
+    }
+
+    fn label(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl DynamicSpec {
+    /// Records generation counters and latencies as [`Generator::with_metrics`] does, labelled
+    /// with the spec's [fingerprint](SpecConfig::fingerprint).
+    ///
+    /// [`Generator::with_metrics`]: crate::Generator::with_metrics
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self) -> Self {
+        let fingerprint = self.config().map(|config| config.fingerprint());
+        self.metrics = Some(crate::metrics_support::SpecMetrics::new(fingerprint));
+        self
+    }
+
+    /// Packs each segment's value as it is encoded, without allocating.
+    pub fn generate(&self) -> Result<i128, Error> {
+        #[cfg(feature = "tracing")]
+        let _span = crate::trace::generate_span(self.segments.len()).entered();
+        #[cfg(feature = "metrics")]
+        let result = match &self.metrics {
+            Some(metrics) => metrics.time(|| self.pack()),
+            None => self.pack(),
+        };
+        #[cfg(not(feature = "metrics"))]
+        let result = self.pack();
+        #[cfg(feature = "tracing")]
+        crate::trace::generated(&result);
+        result
+    }
+
+    fn pack(&self) -> Result<i128, Error> {
+        if self.size > 128 {
+            return Err(Error::SizeExceeded {
+                size: self.size,
+                max: 128,
+            });
+        }
+        let mut id = 0u128;
+        for (index, segment) in self.segments.iter().enumerate() {
+            let size = segment.size();
+            let value = segment.encode().map_err(|e| e.in_segment(index))? as u128;
+            if value & !mask(size) != 0 {
+                return Err(Error::ValueTooLarge { value, size }.in_segment(index));
+            }
+            id = id.checked_shl(u32::from(size)).unwrap_or(0) | value;
+        }
+        Ok(id as i128)
+    }
+
+    /// Generates one ID per element of `ids`, so batch callers can reuse one buffer.
+    pub fn generate_into(&self, ids: &mut [i128]) -> Result<(), Error> {
+        for id in ids {
+            *id = self.generate()?;
+        }
+        Ok(())
+    }
+
+    /// [`generate_into`](Self::generate_into) writing each ID as [`Utid::to_bytes`] does.
+    pub fn generate_bytes_into(&self, ids: &mut [[u8; 16]]) -> Result<(), Error> {
+        for id in ids {
+            *id = Utid(self.generate()?).to_bytes();
+        }
+        Ok(())
+    }
+
+    pub fn decompose(&self, generated: i128) -> Result<Vec<Value>, Error> {
+        unpack(&self.sizes, generated)?
+            .into_iter()
+            .zip(&self.segments)
+            .enumerate()
+            .map(|(index, (value, segment))| {
+                segment
+                    .decode(value as i128)
+                    .map_err(|e| e.in_segment(index))
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for DynamicSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let segments = self.segments();
+        writeln!(f, "{}", describe::summary(&segments))?;
+        f.write_str(&describe::diagram(&segments))
+    }
+}
+
+impl fmt::Debug for DynamicSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynamicSpec")
+            .field("sizes", &self.sizes())
+            .finish()
+    }
+}
+
+/// Specs with the same segments are equal, whatever metrics they report to.
+impl PartialEq for DynamicSpec {
+    fn eq(&self, other: &Self) -> bool {
+        self.segments == other.segments
+    }
+}
+
+impl Eq for DynamicSpec {}
+
+impl Layout for DynamicSpec {
+    fn segments(&self) -> Vec<&dyn Segment> {
+        self.segments
+            .iter()
+            .map(|segment| segment as &dyn Segment)
+            .collect()
+    }
+
+    fn sizes(&self) -> Vec<u8> {
+        self.sizes.clone()
+    }
+
+    fn timestamp_exhaustion_date(&self) -> Option<OffsetDateTime> {
+        self.exhaustion_date
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Date;
+
+    use super::*;
+    use crate::Spec3;
+
+    fn spec() -> Spec3<i128, OffsetDateTime, i128, i128> {
+        Spec3 {
+            segments: (
+                Box::new(
+                    TimestampSegment::new_with_utc_midnight(
+                        48,
+                        TimestampUnit::Milliseconds,
+                        Date::from_calendar_date(2023, time::Month::January, 1).unwrap(),
+                    )
+                    .with_max_skew(Duration::SECOND),
+                ),
+                Box::new(ConstantSegment::new(16, 7)),
+                Box::new(RandomSegment::with_os_rng(64)),
+            ),
+        }
+    }
+
+    #[test]
+    fn fingerprints_ignore_the_rng() {
+        let config = spec().config().unwrap();
+        let mut thread_rng = config.clone();
+        thread_rng.segments[2] = SegmentConfig::Random {
+            size: 64,
+            os_rng: false,
+        };
+        assert_eq!(config.fingerprint(), thread_rng.fingerprint());
+        let mut lenient = config.clone();
+        if let SegmentConfig::Timestamp { max_skew, .. } = &mut lenient.segments[0] {
+            *max_skew = Duration::HOUR;
+        }
+        assert_eq!(config.fingerprint(), lenient.fingerprint());
+
+        let mut other_value = config.clone();
+        other_value.segments[1] = SegmentConfig::Constant { size: 16, value: 8 };
+        assert_ne!(config.fingerprint(), other_value.fingerprint());
+        let mut swapped = config.clone();
+        swapped.segments.swap(1, 2);
+        assert_ne!(config.fingerprint(), swapped.fingerprint());
+    }
+
+    #[test]
+    fn rebuild_from_config() {
+        let spec = spec();
+        let config = spec.config().unwrap();
+        let rebuilt = config.build().unwrap();
+        assert_eq!(Some(config), rebuilt.config());
+        assert_eq!(rebuilt, rebuilt.clone());
+        assert_eq!(spec.sizes(), rebuilt.sizes());
+        assert_eq!(
+            spec.timestamp_exhaustion_date(),
+            rebuilt.timestamp_exhaustion_date()
+        );
+        assert_eq!(
+            "DynamicSpec { sizes: [48, 16, 64] }",
+            format!("{:?}", rebuilt)
+        );
+
+        let id = spec.generate().unwrap();
+        let (timestamp, constant, random) = spec.decompose(id).unwrap();
+        assert_eq!(
+            vec![
+                Value::Timestamp(timestamp),
+                Value::Integer(constant),
+                Value::Integer(random)
+            ],
+            rebuilt.decompose(id).unwrap()
+        );
+        assert!(rebuilt.verify(rebuilt.generate().unwrap()).is_valid());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip() {
+        let config = spec().config().unwrap();
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(
+            concat!(
+                r#"{"segments":[{"kind":"timestamp","size":48,"unit":"milliseconds","#,
+                r#""epoch":"2023-01-01","max_skew_ms":1000},{"kind":"constant","size":16,"value":7},"#,
+                r#"{"kind":"random","size":64,"os_rng":true}]}"#
+            ),
+            json
+        );
+        assert_eq!(config, serde_json::from_str(&json).unwrap());
+
+        let config: SpecConfig =
+            serde_json::from_str(r#"{"segments":[{"kind":"random","size":64}]}"#).unwrap();
+        assert!(config.build().is_ok());
+        let timestamp: SegmentConfig = serde_json::from_str(
+            r#"{"kind":"timestamp","size":48,"unit":"seconds","epoch":"2023-01-01"}"#,
+        )
+        .unwrap();
+        assert!(
+            matches!(timestamp, SegmentConfig::Timestamp { max_skew, .. } if max_skew == Duration::MINUTE)
+        );
+        let SegmentConfig::Timestamp { epoch, .. } = timestamp else {
+            unreachable!()
+        };
+        let fine = SegmentConfig::Timestamp {
+            size: 48,
+            unit: TimestampUnit::Seconds,
+            epoch,
+            max_skew: Duration::microseconds(1_500),
+        };
+        assert!(serde_json::to_string(&fine).is_err());
+
+        // Like the schema, loading rejects unknown fields, e.g. misspelled ones.
+        for json in [
+            r#"{"segments":[{"kind":"random","size":64}],"version":2}"#,
+            r#"{"segments":[{"kind":"random","size":64,"osrng":true}]}"#,
+        ] {
+            assert!(
+                serde_json::from_str::<SpecConfig>(json).is_err(),
+                "{}",
+                json
+            );
+        }
+
+        let wide = SegmentConfig::Constant {
+            size: 128,
+            value: i128::MIN,
+        };
+        let json = serde_json::to_string(&wide).unwrap();
+        assert_eq!(wide, serde_json::from_str(&json).unwrap());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn schema_covers_every_field() {
+        let schema = SpecConfig::json_schema();
+        let variants = schema["properties"]["segments"]["items"]["oneOf"]
+            .as_array()
+            .unwrap();
+        let config = serde_json::to_value(spec().config().unwrap()).unwrap();
+        for segment in config["segments"].as_array().unwrap() {
+            let variant = variants
+                .iter()
+                .find(|variant| variant["properties"]["kind"]["const"] == segment["kind"])
+                .unwrap();
+            for field in segment.as_object().unwrap().keys() {
+                assert!(variant["properties"].get(field).is_some(), "{}", field);
+            }
+        }
+    }
+}
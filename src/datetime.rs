@@ -0,0 +1,63 @@
+use core::marker::PhantomData;
+
+use time::OffsetDateTime;
+
+use crate::{Error, SegmentConfig, SpecSegment, TimestampSegment, TimestampUnit};
+
+/// A date-time type other than [`OffsetDateTime`] that timestamps decode to, e.g. `SystemTime`
+/// or `chrono`'s `DateTime<Utc>`.
+pub trait DateTimeType: Clone + Sized {
+    fn from_time(datetime: OffsetDateTime) -> Result<Self, Error>;
+    fn to_time(&self) -> Result<OffsetDateTime, Error>;
+    /// The latest instant both this type and `time` represent.
+    fn latest() -> Self;
+}
+
+/// A [`TimestampSegment`] that decodes to `D`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimestampSegmentAs<D>(pub TimestampSegment, PhantomData<fn() -> D>);
+
+impl<D: DateTimeType> TimestampSegmentAs<D> {
+    pub fn new(size: u8, unit: TimestampUnit, since: D) -> Result<Self, Error> {
+        Ok(TimestampSegment::new(size, unit, since.to_time()?).into())
+    }
+}
+
+impl<D> From<TimestampSegment> for TimestampSegmentAs<D> {
+    fn from(segment: TimestampSegment) -> Self {
+        Self(segment, PhantomData)
+    }
+}
+
+impl<D: DateTimeType> SpecSegment<i128, D> for TimestampSegmentAs<D> {
+    fn size(&self) -> u8 {
+        self.0.size
+    }
+
+    /// Saturates at [`DateTimeType::latest`].
+    fn upper_bound(&self) -> D {
+        D::from_time(self.0.upper_bound()).unwrap_or_else(|_| D::latest())
+    }
+
+    fn encode(&self) -> Result<i128, Error> {
+        self.0.encode()
+    }
+
+    fn decode(&self, encoded: i128) -> Result<D, Error> {
+        D::from_time(self.0.decode(encoded)?)
+    }
+
+    fn verify(&self, encoded: i128) -> Result<(), Error> {
+        self.0.verify(encoded)
+    }
+
+    fn exhaustion_date(&self) -> Option<OffsetDateTime> {
+        self.0.exhaustion_date()
+    }
+
+    fn config(&self) -> Option<SegmentConfig> {
+        self.0.config()
+    }
+
+    crate::clone_box!(i128, D);
+}
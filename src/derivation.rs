@@ -0,0 +1,182 @@
+use alloc::vec::Vec;
+
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use time::{Duration, OffsetDateTime};
+
+use crate::{join, split, Error, Segment};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameHash {
+    /// What UUIDv5 uses; pick it to interoperate, not for new schemes.
+    Sha1,
+    Sha256,
+}
+
+impl NameHash {
+    fn digest(&self, namespace: i128, name: &[u8]) -> u128 {
+        let namespace = namespace.to_be_bytes();
+        let digest = match self {
+            NameHash::Sha1 => Sha1::new()
+                .chain_update(namespace)
+                .chain_update(name)
+                .finalize()
+                .to_vec(),
+            NameHash::Sha256 => Sha256::new()
+                .chain_update(namespace)
+                .chain_update(name)
+                .finalize()
+                .to_vec(),
+        };
+        let mut truncated = [0u8; 16];
+        truncated.copy_from_slice(&digest[..16]);
+        u128::from_be_bytes(truncated)
+    }
+}
+
+pub(crate) fn derive(
+    segments: &[&dyn Segment],
+    hash: NameHash,
+    namespace: i128,
+    name: &[u8],
+) -> Result<i128, Error> {
+    let sizes: Vec<u8> = segments.iter().map(|segment| segment.size()).collect();
+    let total: u32 = sizes.iter().map(|size| u32::from(*size)).sum();
+    if total > 128 {
+        return Err(Error::SizeExceeded {
+            size: total,
+            max: 128,
+        });
+    }
+    // Keep the leading hash bits, like UUIDv5 truncating the digest.
+    let digest = hash
+        .digest(namespace, name)
+        .checked_shr(128 - total)
+        .unwrap_or(0);
+    let values: Vec<u128> = split(&sizes, digest as i128)
+        .into_iter()
+        .zip(segments)
+        .map(
+            |(value, segment)| match (segment.fixed(), segment.exhaustion_date()) {
+                (Some(fixed), _) => fixed as u128,
+                // A hashed timestamp could lie in the future and fail to verify; the epoch never does.
+                (None, Some(_)) => 0,
+                (None, None) => value,
+            },
+        )
+        .collect();
+    join(&sizes, &values)
+}
+
+/// Derives an ID from `payload` and the `window`-wide time bucket it arrives in, so a retried
+/// request maps to the same ID until the bucket rolls over.
+pub(crate) fn idempotency_key(
+    segments: &[&dyn Segment],
+    payload: &[u8],
+    window: Duration,
+    now: OffsetDateTime,
+) -> Result<i128, Error> {
+    let window = window.whole_nanoseconds();
+    if window <= 0 {
+        return Err(Error::InvalidArgument {
+            reason: "the window is shorter than a nanosecond",
+        });
+    }
+    let bucket = now.unix_timestamp_nanos().div_euclid(window);
+    derive(segments, NameHash::Sha256, bucket, payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Date, Duration, OffsetDateTime};
+
+    use super::idempotency_key;
+    use crate::{
+        ConstantSegment, Error, Layout, NameHash, RandomSegment, Spec2, TimestampSegment,
+        TimestampUnit,
+    };
+
+    #[test]
+    fn derive_is_deterministic() {
+        let spec = Spec2 {
+            segments: (
+                Box::new(ConstantSegment::new(8, 5)),
+                Box::new(RandomSegment::new(120)),
+            ),
+        };
+        let namespace = 0x6ba7b810_9dad_11d1_80b4_00c04fd430c8;
+
+        let id = spec
+            .derive(NameHash::Sha256, namespace, b"order-42")
+            .unwrap();
+        assert_eq!(
+            id,
+            spec.derive(NameHash::Sha256, namespace, b"order-42")
+                .unwrap()
+        );
+        assert_ne!(
+            id,
+            spec.derive(NameHash::Sha256, namespace, b"order-43")
+                .unwrap()
+        );
+        assert_ne!(
+            id,
+            spec.derive(NameHash::Sha1, namespace, b"order-42").unwrap()
+        );
+        assert_eq!(5, spec.decompose(id).unwrap().0);
+        assert!(spec.verify(id).is_valid());
+    }
+
+    #[test]
+    fn derived_timestamps_verify() {
+        let epoch = Date::from_calendar_date(2023, time::Month::January, 1).unwrap();
+        let spec = Spec2 {
+            segments: (
+                Box::new(TimestampSegment::new_with_utc_midnight(
+                    48,
+                    TimestampUnit::Milliseconds,
+                    epoch,
+                )),
+                Box::new(RandomSegment::new(80)),
+            ),
+        };
+        for name in 0..32u8 {
+            let id = spec.derive(NameHash::Sha256, 0, &[name]).unwrap();
+            assert!(spec.verify(id).is_valid(), "{}", name);
+            assert_eq!(epoch.midnight().assume_utc(), spec.decompose(id).unwrap().0);
+        }
+    }
+
+    #[test]
+    fn idempotency_key_within_window() {
+        let spec = Spec2 {
+            segments: (
+                Box::new(ConstantSegment::new(8, 5)),
+                Box::new(RandomSegment::new(56)),
+            ),
+        };
+        let window = Duration::MINUTE;
+        let start = OffsetDateTime::from_unix_timestamp(1_700_000_040).unwrap();
+        let key = |payload: &[u8], now| idempotency_key(&spec.segments(), payload, window, now);
+
+        let first = key(b"{\"amount\":10}", start).unwrap();
+        assert_eq!(
+            first,
+            key(b"{\"amount\":10}", start + Duration::seconds(59)).unwrap()
+        );
+        assert_ne!(
+            first,
+            key(b"{\"amount\":10}", start + Duration::seconds(60)).unwrap()
+        );
+        assert_ne!(first, key(b"{\"amount\":11}", start).unwrap());
+        assert_eq!(5, spec.decompose(first).unwrap().0);
+        assert!(matches!(
+            spec.idempotency_key(b"", Duration::ZERO),
+            Err(Error::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            idempotency_key(&spec.segments(), b"", -Duration::MINUTE, start),
+            Err(Error::InvalidArgument { .. })
+        ));
+    }
+}
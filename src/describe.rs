@@ -0,0 +1,603 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use time::{Duration, OffsetDateTime};
+
+use crate::{split, upper_bound, Segment, SegmentConfig, TimestampUnit};
+
+struct Row {
+    name: String,
+    bits: String,
+    range: String,
+    epoch: String,
+    example: String,
+}
+
+fn unit_name(unit: TimestampUnit) -> &'static str {
+    match unit {
+        TimestampUnit::Seconds => "s",
+        TimestampUnit::Milliseconds => "ms",
+        TimestampUnit::Microseconds => "µs",
+        TimestampUnit::Nanoseconds => "ns",
+    }
+}
+
+fn rows(segments: &[&dyn Segment]) -> Vec<Row> {
+    let mut offset: u32 = segments
+        .iter()
+        .map(|segment| u32::from(segment.size()))
+        .sum();
+    segments
+        .iter()
+        .map(|segment| {
+            let size = segment.size();
+            let high = offset.saturating_sub(1);
+            offset = offset.saturating_sub(u32::from(size));
+            let config = segment.config();
+            let (name, epoch) = match &config {
+                Some(SegmentConfig::Timestamp { unit, epoch, .. }) => (
+                    format!("timestamp ({})", unit_name(*unit)),
+                    epoch.to_string(),
+                ),
+                Some(SegmentConfig::Random { .. }) => ("random".to_string(), "–".to_string()),
+                Some(SegmentConfig::Constant { .. }) => ("constant".to_string(), "–".to_string()),
+                None => ("custom".to_string(), "–".to_string()),
+            };
+            let range = match (&config, segment.exhaustion_date(), segment.fixed()) {
+                (Some(SegmentConfig::Timestamp { epoch, .. }), Some(until), _) => {
+                    format!("{} – {}", epoch, until.date())
+                }
+                (_, _, Some(value)) => format!("= {}", value),
+                _ => format!("0 – {}", upper_bound(size)),
+            };
+            Row {
+                name: named(*segment, name),
+                bits: format!("{}–{} ({})", high, offset, size),
+                range,
+                epoch,
+                example: segment
+                    .encode()
+                    .map_or_else(|_| "–".to_string(), |value| value.to_string()),
+            }
+        })
+        .collect()
+}
+
+/// `kind` prefixed with the segment's label, if it has one.
+fn named(segment: &dyn Segment, kind: String) -> String {
+    match segment.label() {
+        Some(label) => format!("{}: {}", label, kind),
+        None => kind,
+    }
+}
+
+fn label(segment: &dyn Segment) -> String {
+    let kind = segment.label().unwrap_or(match segment.config() {
+        Some(SegmentConfig::Timestamp { .. }) => "timestamp",
+        Some(SegmentConfig::Random { .. }) => "random",
+        Some(SegmentConfig::Constant { .. }) => "constant",
+        None => "segment",
+    });
+    format!("{} ({})", kind, segment.size())
+}
+
+/// One line with the total width and each segment's name, width and fixed value, e.g.
+/// `64 bits: timestamp (s) 40, constant 8 = 3, random 16`.
+pub(crate) fn summary(segments: &[&dyn Segment]) -> String {
+    let total: u32 = segments
+        .iter()
+        .map(|segment| u32::from(segment.size()))
+        .sum();
+    let parts: Vec<String> = rows(segments)
+        .into_iter()
+        .zip(segments)
+        .map(|(row, segment)| match segment.fixed() {
+            Some(value) => format!("{} {} = {}", row.name, segment.size(), value),
+            None => format!("{} {}", row.name, segment.size()),
+        })
+        .collect();
+    format!("{} bits: {}", total, parts.join(", "))
+}
+
+const ROW_BITS: u32 = 32;
+
+/// RFC-style diagram, 32 bits per row with bit 0 the most significant one.
+pub(crate) fn diagram(segments: &[&dyn Segment]) -> String {
+    let mut spans = Vec::new();
+    let mut start = 0u32;
+    for segment in segments {
+        let end = start + u32::from(segment.size());
+        if end > start {
+            spans.push((start, end, label(*segment)));
+        }
+        start = end;
+    }
+    let total = start;
+
+    let columns = total.min(ROW_BITS);
+    let mut diagram = String::new();
+    for digit in [10, 1] {
+        for bit in 0..columns {
+            let value = if digit == 10 { bit / 10 } else { bit % 10 };
+            if digit == 1 || bit % 10 == 0 {
+                diagram += &format!(" {}", value);
+            } else {
+                diagram += "  ";
+            }
+        }
+        diagram = diagram.trim_end().to_string() + "\n";
+    }
+    let border = |bits: u32| format!("{}+\n", "+-".repeat(bits as usize));
+
+    for row in (0..total).step_by(ROW_BITS as usize) {
+        let row_end = (row + ROW_BITS).min(total);
+        diagram += &border(row_end - row);
+        for (start, end, label) in &spans {
+            let (from, to) = ((*start).max(row), (*end).min(row_end));
+            if from >= to {
+                continue;
+            }
+            let width = (2 * (to - from) - 1) as usize;
+            let text: String = label.chars().take(width).collect();
+            diagram += &format!("|{:^width$}", text, width = width);
+        }
+        diagram += "|\n";
+    }
+    if total > 0 {
+        diagram += &border(total - (total - 1) / ROW_BITS * ROW_BITS);
+    }
+    diagram
+}
+
+const HEADERS: [&str; 6] = ["#", "Segment", "Bits", "Range", "Epoch", "Example"];
+
+pub(crate) fn markdown(segments: &[&dyn Segment]) -> String {
+    let mut table = format!("| {} |\n|{}\n", HEADERS.join(" | "), "---|".repeat(6));
+    for (index, row) in rows(segments).iter().enumerate() {
+        table += &format!(
+            "| {} | {} | {} | {} | {} | {} |\n",
+            index, row.name, row.bits, row.range, row.epoch, row.example
+        );
+    }
+    table
+}
+
+pub(crate) fn html(segments: &[&dyn Segment]) -> String {
+    let mut table = String::from("<table>\n  <tr>");
+    for header in HEADERS {
+        table += &format!("<th>{}</th>", header);
+    }
+    table += "</tr>\n";
+    for (index, row) in rows(segments).iter().enumerate() {
+        table += &format!(
+            "  <tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            index,
+            escape(&row.name),
+            escape(&row.bits),
+            escape(&row.range),
+            escape(&row.epoch),
+            escape(&row.example)
+        );
+    }
+    table + "</table>\n"
+}
+
+const BIT_WIDTH: u32 = 20;
+const ROW_HEIGHT: u32 = 32;
+const LINE_HEIGHT: u32 = 20;
+const MARGIN: u32 = 10;
+/// Rough advance of a 12px sans-serif character, for sizing table columns.
+const CHAR_WIDTH: usize = 7;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// The diagram of [`diagram`] as boxes, 32 bits per row, followed by the table of [`markdown`].
+pub(crate) fn svg(segments: &[&dyn Segment]) -> String {
+    let total: u32 = segments
+        .iter()
+        .map(|segment| u32::from(segment.size()))
+        .sum();
+    let columns = total.min(ROW_BITS);
+    let row_count = total.div_ceil(ROW_BITS);
+    let mut body = String::new();
+
+    for bit in 0..columns {
+        body += &format!(
+            "  <text x=\"{}\" y=\"{}\" font-size=\"10\" text-anchor=\"middle\">{}</text>\n",
+            MARGIN + bit * BIT_WIDTH + BIT_WIDTH / 2,
+            MARGIN + 10,
+            bit
+        );
+    }
+    let top = MARGIN + LINE_HEIGHT;
+    let mut start = 0u32;
+    for segment in segments {
+        let end = start + u32::from(segment.size());
+        let label = label(*segment);
+        for row in (start / ROW_BITS)..end.div_ceil(ROW_BITS) {
+            let from = start.max(row * ROW_BITS) - row * ROW_BITS;
+            let to = end.min((row + 1) * ROW_BITS) - row * ROW_BITS;
+            if from >= to {
+                continue;
+            }
+            let (x, y) = (MARGIN + from * BIT_WIDTH, top + row * ROW_HEIGHT);
+            let width = (to - from) * BIT_WIDTH;
+            body += &format!(
+                "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"black\"/>\n",
+                x, y, width, ROW_HEIGHT
+            );
+            body += &format!(
+                "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" dominant-baseline=\"middle\">{}</text>\n",
+                x + width / 2,
+                y + ROW_HEIGHT / 2,
+                escape(&label.chars().take(width as usize / CHAR_WIDTH).collect::<String>())
+            );
+        }
+        start = end;
+    }
+
+    let rows = rows(segments);
+    let cells: Vec<[String; 6]> = rows
+        .iter()
+        .enumerate()
+        .map(|(index, row)| {
+            [
+                index.to_string(),
+                row.name.clone(),
+                row.bits.clone(),
+                row.range.clone(),
+                row.epoch.clone(),
+                row.example.clone(),
+            ]
+        })
+        .collect();
+    let mut x = MARGIN as usize;
+    let mut lefts = [0; 6];
+    for (column, left) in lefts.iter_mut().enumerate() {
+        *left = x;
+        let widest = cells
+            .iter()
+            .map(|cells| cells[column].chars().count())
+            .chain([HEADERS[column].len()])
+            .max()
+            .unwrap_or_default();
+        x += (widest + 2) * CHAR_WIDTH;
+    }
+    let table_top = top + row_count * ROW_HEIGHT + LINE_HEIGHT * 2;
+    for (line, texts) in [HEADERS.map(String::from)].iter().chain(&cells).enumerate() {
+        let weight = if line == 0 {
+            " font-weight=\"bold\""
+        } else {
+            ""
+        };
+        for (text, left) in texts.iter().zip(lefts) {
+            body += &format!(
+                "  <text x=\"{}\" y=\"{}\"{}>{}</text>\n",
+                left,
+                table_top + line as u32 * LINE_HEIGHT,
+                weight,
+                escape(text)
+            );
+        }
+    }
+
+    let width = (2 * MARGIN + columns * BIT_WIDTH).max(x as u32 + MARGIN);
+    let height = table_top + cells.len() as u32 * LINE_HEIGHT + MARGIN;
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\" font-size=\"12\">\n{}</svg>\n",
+        body,
+        w = width,
+        h = height
+    )
+}
+
+/// Readable length of `duration`, in its largest whole unit from days down to milliseconds.
+fn human(duration: Duration) -> String {
+    let duration = duration.abs();
+    let units = [
+        (duration.whole_days(), "day"),
+        (duration.whole_hours(), "hour"),
+        (duration.whole_minutes(), "minute"),
+        (duration.whole_seconds(), "second"),
+    ];
+    let (count, unit) = units
+        .into_iter()
+        .find(|(count, _)| *count > 0)
+        .unwrap_or((duration.whole_milliseconds() as i64, "millisecond"));
+    format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" })
+}
+
+/// `timestamp` in RFC 3339, to the millisecond.
+fn rfc3339(timestamp: OffsetDateTime) -> String {
+    format!(
+        "{}T{:02}:{:02}:{:02}.{:03}Z",
+        timestamp.date(),
+        timestamp.hour(),
+        timestamp.minute(),
+        timestamp.second(),
+        timestamp.millisecond()
+    )
+}
+
+/// Line-per-segment breakdown of `id`: its bits grouped by segment, then each segment's bits,
+/// raw and decoded value and anything odd about it. Timestamps further ahead of `now` than their
+/// segment's skew allows are flagged, if the time is known.
+pub(crate) fn explain(segments: &[&dyn Segment], id: i128, now: Option<OffsetDateTime>) -> String {
+    let sizes: Vec<u8> = segments.iter().map(|segment| segment.size()).collect();
+    let total = sizes.iter().map(|size| u32::from(*size)).sum::<u32>();
+    let values = split(&sizes, id);
+    let mut text = format!("id {:#x} ({} bits)\n", id as u128, total);
+    if total < 128 && (id as u128) >> total != 0 {
+        text += &format!("! bits set above the {}-bit layout\n", total);
+    }
+    let groups: Vec<String> = sizes
+        .iter()
+        .zip(&values)
+        .map(|(size, value)| format!("{:0width$b}", value, width = usize::from(*size)))
+        .collect();
+    text += &format!("binary {}\n", groups.join(" "));
+
+    let mut offset = total;
+    for (index, (segment, value)) in segments.iter().zip(&values).enumerate() {
+        let size = segment.size();
+        let high = offset.saturating_sub(1);
+        offset = offset.saturating_sub(u32::from(size));
+        let mut flags = Vec::new();
+        let config = segment.config();
+        let (name, decoded) = match config {
+            Some(SegmentConfig::Timestamp {
+                unit,
+                epoch,
+                max_skew,
+                ..
+            }) => {
+                let timestamp = unit
+                    .to_nano(*value as i128)
+                    .and_then(|nanos| {
+                        nanos.checked_add(epoch.midnight().assume_utc().unix_timestamp_nanos())
+                    })
+                    .and_then(|nanos| OffsetDateTime::from_unix_timestamp_nanos(nanos).ok());
+                match (timestamp, now) {
+                    (Some(timestamp), Some(now)) if timestamp - now > max_skew => flags.push(
+                        format!("timestamp is {} in the future", human(timestamp - now)),
+                    ),
+                    (None, _) => flags.push("timestamp out of range".to_string()),
+                    _ => {}
+                }
+                (
+                    format!("timestamp ({})", unit_name(unit)),
+                    timestamp.map(rfc3339),
+                )
+            }
+            Some(SegmentConfig::Random { .. }) => ("random".to_string(), None),
+            Some(SegmentConfig::Constant { .. }) => ("constant".to_string(), None),
+            None => ("custom".to_string(), None),
+        };
+        if let Some(fixed) = segment.fixed().filter(|fixed| *fixed as u128 != *value) {
+            flags.push(format!("expected {}", fixed));
+        } else if matches!(config, Some(SegmentConfig::Timestamp { .. })) {
+            // Checked against `now` above; verifying would check again against the clock.
+        } else if let Err(error) = segment.verify(*value as i128) {
+            flags.push(error.to_string());
+        }
+        let bits = format!("{}–{}", high, offset);
+        let name = named(*segment, name);
+        text += &format!("{}  {:<16} bits {:<7}  {}", index, name, bits, value);
+        if let Some(decoded) = decoded {
+            text += &format!(" = {}", decoded);
+        }
+        for flag in flags {
+            text += &format!("  ! {}", flag);
+        }
+        text += "\n";
+    }
+    text
+}
+
+/// The effective layout as one logfmt line: segments, width, epochs, constant values such as
+/// node ids, RNG kinds and the [fingerprint](crate::SpecConfig::fingerprint). Settings no
+/// segment has are logged as `none`; layouts with custom segments have a `custom` fingerprint.
+pub(crate) fn configuration(segments: &[&dyn Segment]) -> String {
+    let mut layout = Vec::new();
+    let mut epochs = Vec::new();
+    let mut nodes = Vec::new();
+    let mut rngs = Vec::new();
+    let mut configs = Vec::new();
+    for segment in segments {
+        let config = segment.config();
+        match &config {
+            Some(SegmentConfig::Timestamp {
+                size, unit, epoch, ..
+            }) => {
+                layout.push(format!("timestamp:{}:{}", size, unit_name(*unit)));
+                epochs.push(epoch.to_string());
+            }
+            Some(SegmentConfig::Random { size, os_rng }) => {
+                layout.push(format!("random:{}", size));
+                rngs.push(if *os_rng { "os" } else { "thread" }.to_string());
+            }
+            Some(SegmentConfig::Constant { size, value }) => {
+                layout.push(format!("constant:{}", size));
+                nodes.push(value.to_string());
+            }
+            None => layout.push(format!("custom:{}", segment.size())),
+        }
+        configs.push(config);
+    }
+    let fingerprint = configs.into_iter().collect::<Option<Vec<_>>>().map_or_else(
+        || "custom".to_string(),
+        |segments| format!("{:016x}", crate::SpecConfig { segments }.fingerprint()),
+    );
+    let list = |values: Vec<String>| match values.is_empty() {
+        true => "none".to_string(),
+        false => values.join(","),
+    };
+    format!(
+        "layout={} bits={} epoch={} node={} rng={} fingerprint={}",
+        list(layout),
+        segments
+            .iter()
+            .map(|segment| u32::from(segment.size()))
+            .sum::<u32>(),
+        list(epochs),
+        list(nodes),
+        list(rngs),
+        fingerprint,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Date, Duration};
+
+    use crate::{
+        ConstantSegment, Labeled, Layout, RandomSegment, Spec, Spec3, TimestampSegment,
+        TimestampUnit,
+    };
+
+    #[test]
+    fn bit_diagram() {
+        let spec = Spec3 {
+            segments: (
+                Box::new(TimestampSegment::new_with_utc_midnight(
+                    40,
+                    TimestampUnit::Seconds,
+                    Date::from_calendar_date(2023, time::Month::January, 1).unwrap(),
+                )),
+                Box::new(ConstantSegment::new(8, 3)),
+                Box::new(RandomSegment::new(16)),
+            ),
+        };
+        let expected = concat!(
+            "64 bits: timestamp (s) 40, constant 8 = 3, random 16\n",
+            " 0                   1                   2                   3\n",
+            " 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1\n",
+            "+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+\n",
+            "|                        timestamp (40)                         |\n",
+            "+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+\n",
+            "|timestamp (40) | constant (8)  |          random (16)          |\n",
+            "+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+\n",
+        );
+        assert_eq!(expected, spec.to_string());
+    }
+
+    #[test]
+    fn explains_ids() {
+        let spec = Spec3 {
+            segments: (
+                Box::new(TimestampSegment::new_with_utc_midnight(
+                    16,
+                    TimestampUnit::Seconds,
+                    Date::from_calendar_date(2023, time::Month::January, 1).unwrap(),
+                )),
+                Box::new(ConstantSegment::new(4, 3)),
+                Box::new(RandomSegment::new(4)),
+            ),
+        };
+        let id = 3_723 << 8 | 2 << 4 | 0xa;
+        let now = Date::from_calendar_date(2023, time::Month::January, 1)
+            .unwrap()
+            .midnight()
+            .assume_utc();
+        let expected = concat!(
+            "id 0xe8b2a (24 bits)\n",
+            "binary 0000111010001011 0010 1010\n",
+            "0  timestamp (s)    bits 23–8     3723 = 2023-01-01T01:02:03.000Z",
+            "  ! timestamp is 1 hour in the future\n",
+            "1  constant         bits 7–4      2  ! expected 3\n",
+            "2  random           bits 3–0      10\n",
+        );
+        assert_eq!(expected, super::explain(&spec.segments(), id, Some(now)));
+        // Within the segment's skew of `now`.
+        let soon = now + Duration::seconds(3_723 - 30);
+        assert!(!super::explain(&spec.segments(), id, Some(soon)).contains("future"));
+        assert!(spec
+            .explain(1 << 24)
+            .contains("! bits set above the 24-bit layout\n"));
+    }
+
+    #[test]
+    fn configuration_line() {
+        let spec = Spec3 {
+            segments: (
+                Box::new(TimestampSegment::new_with_utc_midnight(
+                    40,
+                    TimestampUnit::Seconds,
+                    Date::from_calendar_date(2023, time::Month::January, 1).unwrap(),
+                )),
+                Box::new(ConstantSegment::new(8, 3)),
+                Box::new(RandomSegment::with_os_rng(16)),
+            ),
+        };
+        let fingerprint = spec.config().unwrap().fingerprint();
+        assert_eq!(
+            format!(
+                "layout=timestamp:40:s,constant:8,random:16 bits=64 epoch=2023-01-01 node=3 \
+                 rng=os fingerprint={:016x}",
+                fingerprint
+            ),
+            spec.configuration()
+        );
+    }
+
+    #[test]
+    fn markdown_table() {
+        let spec = Spec3 {
+            segments: (
+                Box::new(TimestampSegment::new_with_utc_midnight(
+                    32,
+                    TimestampUnit::Seconds,
+                    Date::from_calendar_date(2023, time::Month::January, 1).unwrap(),
+                )),
+                Box::new(ConstantSegment::new(8, 3)),
+                Box::new(RandomSegment::new(24)),
+            ),
+        };
+        let markdown = spec.describe_markdown();
+        let lines: Vec<&str> = markdown.lines().collect();
+        assert_eq!("| # | Segment | Bits | Range | Epoch | Example |", lines[0]);
+        assert!(lines[2].starts_with(
+            "| 0 | timestamp (s) | 63–32 (32) | 2023-01-01 – 2159-02-07 | 2023-01-01 | "
+        ));
+        assert_eq!("| 1 | constant | 31–24 (8) | = 3 | – | 3 |", lines[3]);
+        assert!(lines[4].starts_with("| 2 | random | 23–0 (24) | 0 – 16777215 | – | "));
+        assert_eq!(4, spec.describe_html().matches("<tr>").count());
+
+        let spec = Spec {
+            segment: Box::new(Labeled::new("<b>", ConstantSegment::new(8, 3))),
+        };
+        assert!(spec
+            .describe_html()
+            .contains("<td>&lt;b&gt;: constant</td>"));
+    }
+
+    #[test]
+    fn svg_diagram() {
+        let spec = Spec3 {
+            segments: (
+                Box::new(TimestampSegment::new_with_utc_midnight(
+                    40,
+                    TimestampUnit::Seconds,
+                    Date::from_calendar_date(2023, time::Month::January, 1).unwrap(),
+                )),
+                Box::new(ConstantSegment::new(8, 3)),
+                Box::new(RandomSegment::new(16)),
+            ),
+        };
+        let svg = spec.describe_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"660\""));
+        // The timestamp spans both rows, so it is drawn twice.
+        assert_eq!(4, svg.matches("<rect").count());
+        assert_eq!(2, svg.matches(">timestamp (40)<").count());
+        assert!(svg.contains(
+            "<rect x=\"330\" y=\"62\" width=\"320\" height=\"32\" fill=\"none\" stroke=\"black\"/>"
+        ));
+        assert!(svg.contains(">= 3</text>"));
+    }
+}
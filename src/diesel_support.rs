@@ -0,0 +1,85 @@
+//! Diesel support for [`Utid`] on Postgres. The Rust type is checked against the column type at
+//! compile time: [`Utid`] maps to `Uuid`, [`UtidBytes`] to `Binary`, both carrying all 128 bits
+//! big-endian, and [`UtidBigInt`] to `BigInt`. A `UtidBigInt` can only hold IDs of specs up to
+//! 63 bits wide, so binding never fails on width.
+
+use std::io::Write;
+
+use ::diesel::deserialize::{self, FromSql};
+use ::diesel::pg::{Pg, PgValue};
+use ::diesel::serialize::{self, IsNull, Output, ToSql};
+use ::diesel::sql_types::{BigInt, Binary, Uuid};
+
+use crate::encoding::{from_uuid_bytes, uuid_bytes};
+use crate::{Utid, UtidBigInt, UtidBytes};
+
+impl ToSql<Uuid, Pg> for Utid {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(&uuid_bytes(self.0))?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Uuid, Pg> for Utid {
+    fn from_sql(value: PgValue<'_>) -> deserialize::Result<Self> {
+        Ok(Utid(from_uuid_bytes(value.as_bytes())?))
+    }
+}
+
+impl ToSql<Binary, Pg> for UtidBytes {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(&uuid_bytes(self.0 .0))?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Binary, Pg> for UtidBytes {
+    fn from_sql(value: PgValue<'_>) -> deserialize::Result<Self> {
+        Ok(UtidBytes(Utid(from_uuid_bytes(value.as_bytes())?)))
+    }
+}
+
+impl ToSql<BigInt, Pg> for UtidBigInt {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(&self.get().to_be_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<BigInt, Pg> for UtidBigInt {
+    fn from_sql(value: PgValue<'_>) -> deserialize::Result<Self> {
+        let id = <i64 as FromSql<BigInt, Pg>>::from_sql(value)?;
+        Ok(UtidBigInt::try_from(id)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::diesel::prelude::*;
+
+    use crate::{Utid, UtidBigInt, UtidBytes};
+
+    ::diesel::table! {
+        orders (id) {
+            id -> Uuid,
+            raw_id -> Binary,
+            legacy_id -> BigInt,
+        }
+    }
+
+    #[test]
+    fn binds_in_queries() {
+        let legacy = UtidBigInt::try_from(Utid(7)).unwrap();
+        let query = orders::table
+            .filter(orders::id.eq(Utid(42)))
+            .or_filter(orders::raw_id.eq(UtidBytes(Utid(9))))
+            .or_filter(orders::legacy_id.eq(legacy))
+            .select(orders::id);
+        let sql = ::diesel::debug_query::<::diesel::pg::Pg, _>(&query).to_string();
+        assert!(
+            sql.contains("binds: [Utid(42), UtidBytes(Utid(9)), UtidBigInt(7)]"),
+            "{}",
+            sql
+        );
+    }
+}
@@ -0,0 +1,260 @@
+#[cfg(feature = "sqids")]
+pub use sqids::Sqids;
+
+use alloc::format;
+use alloc::string::String;
+
+use crate::Error;
+
+/// Crockford's base32 alphabet, which leaves out I, L, O and U so IDs read back unambiguously.
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Encodes the low `bits` of `value` as Crockford base32, most significant digit first, padded
+/// to a fixed length so encoded IDs of one spec sort like their numeric values.
+pub fn encode_base32(value: i128, bits: u8) -> String {
+    let value = value as u128;
+    let digits = u32::from(bits).div_ceil(5);
+    (0..digits)
+        .rev()
+        .map(|digit| {
+            let index = value.checked_shr(digit * 5).unwrap_or(0) & 0x1f;
+            CROCKFORD[index as usize] as char
+        })
+        .collect()
+}
+
+/// [`encode_base32`] into `buf` rather than a new `String`, for callers that can't allocate.
+/// `buf` must hold at least `bits / 5` rounded up bytes; returns the encoded prefix of it.
+pub fn encode_base32_into(value: i128, bits: u8, buf: &mut [u8]) -> Result<&str, Error> {
+    let value = value as u128;
+    let digits = usize::from(bits).div_ceil(5);
+    let len = buf.len();
+    let buf = buf.get_mut(..digits).ok_or(Error::SizeTooSmall {
+        size: len as u32,
+        min: digits as u32,
+    })?;
+    for (digit, symbol) in buf.iter_mut().rev().enumerate() {
+        let index = value.checked_shr(digit as u32 * 5).unwrap_or(0) & 0x1f;
+        *symbol = CROCKFORD[index as usize];
+    }
+    core::str::from_utf8(buf).map_err(|_| Error::NonCanonical)
+}
+
+/// Decodes Crockford base32, accepting lowercase and the usual `I`/`L` -> `1`, `O` -> `0`
+/// substitutions. Hyphens are ignored.
+pub fn decode_base32(encoded: &str) -> Result<i128, Error> {
+    let result = parse_base32(encoded);
+    #[cfg(feature = "tracing")]
+    crate::trace::parsed("base32", encoded, &result);
+    #[cfg(feature = "metrics")]
+    if result.is_err() {
+        crate::metrics_support::parse_failure("base32");
+    }
+    result
+}
+
+fn parse_base32(encoded: &str) -> Result<i128, Error> {
+    let mut value = 0u128;
+    for (pos, c) in encoded.char_indices().filter(|(_, c)| *c != '-') {
+        let digit = match c.to_ascii_uppercase() {
+            'O' => 0,
+            'I' | 'L' => 1,
+            upper => CROCKFORD
+                .iter()
+                .position(|symbol| *symbol as char == upper)
+                .ok_or(Error::ParseError { pos, char: c })? as u128,
+        };
+        if value >> 123 != 0 {
+            return Err(Error::Overflow);
+        }
+        value = (value << 5) | digit;
+    }
+    Ok(value as i128)
+}
+
+/// The 16 bytes Postgres stores for a `uuid` column, big-endian. Postgres compares `uuid`s
+/// bytewise, so index order is the numeric order of the unsigned ID: with the timestamp in the
+/// most significant segment, rows index in generation order, up to clock resolution.
+pub fn uuid_bytes(value: i128) -> [u8; 16] {
+    value.to_be_bytes()
+}
+
+/// Inverse of [`uuid_bytes`]; fails unless given exactly 16 bytes.
+pub fn from_uuid_bytes(bytes: &[u8]) -> Result<i128, Error> {
+    let bytes: [u8; 16] = bytes.try_into().map_err(|_| Error::SizeMismatch {
+        expected: 128,
+        actual: (bytes.len() * 8) as u32,
+    })?;
+    Ok(i128::from_be_bytes(bytes))
+}
+
+/// Formats `value` the way Postgres prints `uuid`s and pgcrypto's `gen_random_uuid()` returns
+/// them: lowercase hex, hyphenated 8-4-4-4-12.
+pub fn encode_uuid(value: i128) -> String {
+    let hex = format!("{:032x}", value as u128);
+    [
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..],
+    ]
+    .join("-")
+}
+
+/// Parses `uuid` text in any of the forms Postgres accepts on input: either case, hyphens
+/// anywhere, optionally wrapped in braces.
+pub fn decode_uuid(encoded: &str) -> Result<i128, Error> {
+    let result = parse_uuid(encoded);
+    #[cfg(feature = "tracing")]
+    crate::trace::parsed("uuid", encoded, &result);
+    #[cfg(feature = "metrics")]
+    if result.is_err() {
+        crate::metrics_support::parse_failure("uuid");
+    }
+    result
+}
+
+fn parse_uuid(encoded: &str) -> Result<i128, Error> {
+    let inner = encoded
+        .strip_prefix('{')
+        .and_then(|rest| rest.strip_suffix('}'))
+        .map_or((0, encoded), |inner| (1, inner));
+    let mut value = 0u128;
+    let mut digits = 0;
+    for (pos, c) in inner.1.char_indices().filter(|(_, c)| *c != '-') {
+        let digit = c.to_digit(16).ok_or(Error::ParseError {
+            pos: pos + inner.0,
+            char: c,
+        })?;
+        if digits == 32 {
+            return Err(Error::Overflow);
+        }
+        value = (value << 4) | u128::from(digit);
+        digits += 1;
+    }
+    if digits != 32 {
+        return Err(Error::NonCanonical);
+    }
+    Ok(value as i128)
+}
+
+#[cfg(feature = "sqids")]
+pub(crate) fn encode_sqids(sizes: &[u8], id: i128, sqids: &Sqids) -> Result<String, Error> {
+    let numbers = crate::split(sizes, id)
+        .into_iter()
+        .map(|value| u64::try_from(value).map_err(|_| Error::ValueTooLarge { value, size: 64 }))
+        .collect::<Result<Vec<u64>, Error>>()?;
+    // The only way encoding fails is running out of attempts to avoid blocklisted words.
+    sqids.encode(&numbers).map_err(|_| Error::Exhausted)
+}
+
+#[cfg(feature = "sqids")]
+pub(crate) fn decode_sqids(sizes: &[u8], encoded: &str, sqids: &Sqids) -> Result<i128, Error> {
+    let numbers = sqids.decode(encoded);
+    // Several strings decode to the same numbers; only the canonical one is accepted.
+    if numbers.is_empty() || sqids.encode(&numbers).ok().as_deref() != Some(encoded) {
+        return Err(Error::NonCanonical);
+    }
+    let values: Vec<u128> = numbers.into_iter().map(u128::from).collect();
+    crate::join(sizes, &values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base32_roundtrip() {
+        assert_eq!("000000000000000000000000C1", encode_base32(385, 128));
+        assert_eq!(385, decode_base32("000000000000000000000000C1").unwrap());
+        assert_eq!(
+            i128::MIN,
+            decode_base32(&encode_base32(i128::MIN, 128)).unwrap()
+        );
+        assert_eq!(32 * 32 + 32, decode_base32("0I-L0").unwrap());
+    }
+
+    #[test]
+    fn base32_into_buffer() {
+        let mut buf = [0; 26];
+        assert_eq!(
+            encode_base32(385, 128),
+            encode_base32_into(385, 128, &mut buf).unwrap()
+        );
+        assert_eq!("0000C1", encode_base32_into(385, 30, &mut buf).unwrap());
+        assert!(encode_base32_into(385, 64, &mut [0; 12]).is_err());
+    }
+
+    #[test]
+    fn base32_rejects_invalid() {
+        assert!(matches!(
+            decode_base32("0-U"),
+            Err(Error::ParseError { pos: 2, char: 'U' })
+        ));
+        assert!(decode_base32("80000000000000000000000000").is_err());
+    }
+
+    #[test]
+    fn uuid_roundtrip() {
+        let value = 0x0123_4567_89ab_cdef_0011_2233_4455_6677;
+        assert_eq!("01234567-89ab-cdef-0011-223344556677", encode_uuid(value));
+        assert_eq!(
+            value,
+            decode_uuid("{0123456789ABCDEF0011223344556677}").unwrap()
+        );
+        assert_eq!(-1, decode_uuid(&encode_uuid(-1)).unwrap());
+        assert_eq!(value, from_uuid_bytes(&uuid_bytes(value)).unwrap());
+
+        assert!(matches!(
+            decode_uuid("{0123456789abcdeg}"),
+            Err(Error::ParseError { pos: 16, char: 'g' })
+        ));
+        assert!(decode_uuid("01234567").is_err());
+        assert!(decode_uuid(&"0".repeat(33)).is_err());
+        assert!(from_uuid_bytes(&[0; 8]).is_err());
+    }
+
+    #[test]
+    fn uuid_bytes_sort_in_generation_order() {
+        use crate::{RandomSegment, Spec2, TimestampSegment, TimestampUnit};
+
+        let spec = Spec2 {
+            segments: (
+                Box::new(TimestampSegment::new_with_utc_midnight(
+                    48,
+                    TimestampUnit::Microseconds,
+                    time::Date::from_calendar_date(2020, time::Month::January, 1).unwrap(),
+                )),
+                Box::new(RandomSegment::new(80)),
+            ),
+        };
+        let ids: Vec<i128> = (0..3)
+            .map(|_| {
+                std::thread::sleep(std::time::Duration::from_millis(1));
+                spec.generate().unwrap()
+            })
+            .collect();
+        assert!(ids.windows(2).all(|w| uuid_bytes(w[0]) < uuid_bytes(w[1])));
+    }
+
+    #[cfg(feature = "sqids")]
+    #[test]
+    fn sqids_per_segment() {
+        use crate::{ConstantSegment, Layout, Spec2};
+
+        let spec = Spec2 {
+            segments: (
+                Box::new(ConstantSegment::new(16, 1)),
+                Box::new(ConstantSegment::new(48, 2)),
+            ),
+        };
+        let sqids = Sqids::builder().min_length(8).build().unwrap();
+        let id = spec.generate().unwrap();
+
+        let encoded = spec.encode_sqids(id, &sqids).unwrap();
+        assert_eq!(vec![1, 2], sqids.decode(&encoded));
+        assert_eq!(id, spec.decode_sqids(&encoded, &sqids).unwrap());
+        assert!(spec.decode_sqids("not-a-sqid", &sqids).is_err());
+    }
+}
@@ -0,0 +1,271 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+
+use aes::Aes256;
+use fpe::ff1::{FlexibleNumeralString, FF1};
+
+use crate::{mask, Error};
+
+/// FF1 needs at least a million possible values, which is 20 digits for a binary alphabet.
+const MIN_BITS: u8 = 20;
+
+/// FF1 (NIST SP 800-38G) cipher over the binary digits of an ID, so the ciphertext keeps the
+/// bit width of the spec it was generated by.
+pub struct Cipher {
+    ff1: FF1<Aes256>,
+    tweak: Vec<u8>,
+}
+
+impl Cipher {
+    // FF1::new only rejects radixes below 2.
+    #[allow(clippy::expect_used)]
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self {
+            ff1: FF1::new(key, 2).expect("radix 2 is always supported"),
+            tweak: Vec::new(),
+        }
+    }
+
+    pub fn with_tweak(mut self, tweak: &[u8]) -> Self {
+        self.tweak = tweak.to_vec();
+        self
+    }
+
+    /// Replaces the key in place. IDs encrypted with the previous key no longer decrypt.
+    #[allow(clippy::expect_used)]
+    pub fn rotate(&mut self, key: &[u8; 32]) {
+        self.ff1 = FF1::new(key, 2).expect("radix 2 is always supported");
+    }
+
+    pub fn encrypt(&self, value: i128, bits: u8) -> Result<i128, Error> {
+        let digits = to_digits(value, bits)?;
+        let encrypted = self
+            .ff1
+            .encrypt(&self.tweak, &digits)
+            .map_err(|_| Error::Encryption {
+                reason: "FF1 rejected the input",
+            })?;
+        Ok(from_digits(encrypted))
+    }
+
+    pub fn decrypt(&self, value: i128, bits: u8) -> Result<i128, Error> {
+        let digits = to_digits(value, bits)?;
+        let decrypted = self
+            .ff1
+            .decrypt(&self.tweak, &digits)
+            .map_err(|_| Error::Encryption {
+                reason: "FF1 rejected the input",
+            })?;
+        Ok(from_digits(decrypted))
+    }
+}
+
+/// Set of ciphers addressed by a small key id that travels in clear in the most significant
+/// segment of every encrypted ID. New IDs use the current key; IDs from retired keys still
+/// decrypt until the key is removed.
+pub struct Keyring {
+    key_bits: u8,
+    current: Option<u32>,
+    ciphers: BTreeMap<u32, Cipher>,
+    retired: BTreeSet<u32>,
+}
+
+impl Keyring {
+    pub fn new(key_bits: u8) -> Self {
+        Self {
+            key_bits,
+            current: None,
+            ciphers: BTreeMap::new(),
+            retired: BTreeSet::new(),
+        }
+    }
+
+    pub fn key_bits(&self) -> u8 {
+        self.key_bits
+    }
+
+    /// Adds a key and makes it the one new IDs are encrypted with.
+    pub fn add(&mut self, key_id: u32, cipher: Cipher) -> Result<(), Error> {
+        if u128::from(key_id) > mask(self.key_bits) {
+            return Err(Error::ValueTooLarge {
+                value: u128::from(key_id),
+                size: self.key_bits,
+            });
+        }
+        self.ciphers.insert(key_id, cipher);
+        self.retired.remove(&key_id);
+        self.current = Some(key_id);
+        Ok(())
+    }
+
+    /// Stops encrypting with `key_id` while still decrypting IDs issued under it. If it was the
+    /// current key, the highest key id that is not retired becomes current.
+    pub fn retire(&mut self, key_id: u32) {
+        self.retired.insert(key_id);
+        if self.current == Some(key_id) {
+            self.current = self
+                .ciphers
+                .keys()
+                .rev()
+                .find(|id| !self.retired.contains(id))
+                .copied();
+        }
+    }
+
+    /// Forgets `key_id` entirely; IDs issued under it no longer decrypt.
+    pub fn remove(&mut self, key_id: u32) {
+        self.retire(key_id);
+        self.ciphers.remove(&key_id);
+        self.retired.remove(&key_id);
+    }
+
+    pub fn current(&self) -> Option<u32> {
+        self.current
+    }
+
+    pub fn encrypt(&self, value: i128, bits: u8) -> Result<i128, Error> {
+        let key_id = self.current.ok_or(Error::Encryption {
+            reason: "no current key",
+        })?;
+        let payload_bits = self.payload_bits(bits)?;
+        let payload = (value as u128 & mask(payload_bits)) as i128;
+        let cipher = self.ciphers.get(&key_id).ok_or(Error::Encryption {
+            reason: "unknown key id",
+        })?;
+        let encrypted = cipher.encrypt(payload, payload_bits)?;
+        let key_id = u128::from(key_id)
+            .checked_shl(u32::from(payload_bits))
+            .unwrap_or(0);
+        Ok((key_id | encrypted as u128) as i128)
+    }
+
+    pub fn decrypt(&self, value: i128, bits: u8) -> Result<i128, Error> {
+        let payload_bits = self.payload_bits(bits)?;
+        let key_id = (value as u128)
+            .checked_shr(u32::from(payload_bits))
+            .unwrap_or(0);
+        let cipher = u32::try_from(key_id)
+            .ok()
+            .and_then(|key_id| self.ciphers.get(&key_id))
+            .ok_or(Error::Encryption {
+                reason: "unknown key id",
+            })?;
+        let payload = (value as u128 & mask(payload_bits)) as i128;
+        let decrypted = cipher.decrypt(payload, payload_bits)?;
+        let key_id = key_id.checked_shl(u32::from(payload_bits)).unwrap_or(0);
+        Ok((key_id | decrypted as u128) as i128)
+    }
+
+    fn payload_bits(&self, bits: u8) -> Result<u8, Error> {
+        bits.checked_sub(self.key_bits).ok_or(Error::SizeTooSmall {
+            size: u32::from(bits),
+            min: u32::from(self.key_bits),
+        })
+    }
+}
+
+fn to_digits(value: i128, bits: u8) -> Result<FlexibleNumeralString, Error> {
+    if bits < MIN_BITS {
+        return Err(Error::SizeTooSmall {
+            size: u32::from(bits),
+            min: u32::from(MIN_BITS),
+        });
+    }
+    if bits > 128 {
+        return Err(Error::SizeExceeded {
+            size: u32::from(bits),
+            max: 128,
+        });
+    }
+    let value = value as u128;
+    if bits < 128 && value >> bits != 0 {
+        return Err(Error::ValueTooLarge { value, size: bits });
+    }
+    let digits: Vec<u16> = (0..bits)
+        .rev()
+        .map(|bit| ((value >> bit) & 1) as u16)
+        .collect();
+    Ok(FlexibleNumeralString::from(digits))
+}
+
+fn from_digits(digits: FlexibleNumeralString) -> i128 {
+    let digits: Vec<u16> = digits.into();
+    digits
+        .iter()
+        .fold(0u128, |acc, digit| (acc << 1) | u128::from(*digit)) as i128
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConstantSegment, Layout, RandomSegment, Spec2};
+
+    #[test]
+    fn encrypt_within_width() {
+        let spec = Spec2 {
+            segments: (
+                Box::new(ConstantSegment::new(8, 42)),
+                Box::new(RandomSegment::new(40)),
+            ),
+        };
+        let cipher = Cipher::new(&[7; 32]);
+        let generated = spec.generate().unwrap();
+
+        let encrypted = spec.encrypt(generated, &cipher).unwrap();
+        assert_eq!(0, encrypted >> 48);
+        assert_eq!(generated, spec.decrypt(encrypted, &cipher).unwrap());
+    }
+
+    #[test]
+    fn rotated_key_changes_ciphertext() {
+        let mut cipher = Cipher::new(&[1; 32]).with_tweak(b"orders");
+        let encrypted = cipher.encrypt(12345, 64).unwrap();
+
+        cipher.rotate(&[2; 32]);
+        assert_ne!(encrypted, cipher.encrypt(12345, 64).unwrap());
+    }
+
+    #[test]
+    fn retired_keys_still_decrypt() {
+        let spec = Spec2 {
+            segments: (
+                Box::new(ConstantSegment::new(4, 0)),
+                Box::new(RandomSegment::new(60)),
+            ),
+        };
+        let mut keyring = Keyring::new(4);
+        keyring.add(1, Cipher::new(&[1; 32])).unwrap();
+        let generated = spec.generate().unwrap();
+        let old = spec.encrypt_with(generated, &keyring).unwrap();
+        assert_eq!(1, old >> 60);
+
+        keyring.add(2, Cipher::new(&[2; 32])).unwrap();
+        keyring.retire(1);
+        let new = spec.encrypt_with(generated, &keyring).unwrap();
+        assert_eq!(2, new >> 60);
+
+        let payload = generated & ((1 << 60) - 1);
+        assert_eq!(
+            payload | (1 << 60),
+            spec.decrypt_with(old, &keyring).unwrap()
+        );
+        assert_eq!(
+            payload | (2 << 60),
+            spec.decrypt_with(new, &keyring).unwrap()
+        );
+
+        keyring.retire(2);
+        assert_eq!(None, keyring.current());
+        assert!(spec.encrypt_with(generated, &keyring).is_err());
+
+        keyring.remove(1);
+        assert!(spec.decrypt_with(old, &keyring).is_err());
+        assert!(keyring.add(16, Cipher::new(&[3; 32])).is_err());
+    }
+
+    #[test]
+    fn too_narrow_for_ff1() {
+        let cipher = Cipher::new(&[0; 32]);
+        assert!(cipher.encrypt(1, 16).is_err());
+        assert!(cipher.encrypt(1 << 30, 24).is_err());
+    }
+}
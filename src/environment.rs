@@ -0,0 +1,218 @@
+use core::fmt;
+use core::str::FromStr;
+
+use crate::{mask, Error, SpecSegment};
+
+/// The deployment stage an ID was minted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Environment {
+    Development = 0,
+    Test = 1,
+    Staging = 2,
+    Production = 3,
+}
+
+impl Environment {
+    pub fn from_value(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Environment::Development),
+            1 => Some(Environment::Test),
+            2 => Some(Environment::Staging),
+            3 => Some(Environment::Production),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Environment::Development => "development",
+            Environment::Test => "test",
+            Environment::Staging => "staging",
+            Environment::Production => "production",
+        }
+    }
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses the names deployments commonly use, e.g. from an environment variable: `dev`,
+/// `stage` and `prod` as well as the full names, in any case.
+impl FromStr for Environment {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Error> {
+        let names = [
+            ("dev", Environment::Development),
+            ("development", Environment::Development),
+            ("test", Environment::Test),
+            ("stage", Environment::Staging),
+            ("staging", Environment::Staging),
+            ("prod", Environment::Production),
+            ("production", Environment::Production),
+        ];
+        names
+            .into_iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(name))
+            .map(|(_, environment)| environment)
+            .ok_or_else(|| Error::UnknownName { name: name.into() })
+    }
+}
+
+/// The [`Environment`] the generating deployment runs in, at least 2 bits wide.
+///
+/// By default it decodes IDs of every environment. [`strict`](Self::strict) segments reject
+/// IDs of other environments when decoding and verifying, so a production ID used in staging
+/// fails at the boundary instead of reading the wrong data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnvironmentSegment {
+    size: u8,
+    environment: Environment,
+    strict: bool,
+}
+
+impl EnvironmentSegment {
+    /// Fails if `size` is below the 2 bits every environment needs.
+    pub fn new(size: u8, environment: Environment) -> Result<Self, Error> {
+        if size < 2 {
+            return Err(Error::SizeTooSmall {
+                size: size.into(),
+                min: 2,
+            });
+        }
+        Ok(Self {
+            size,
+            environment,
+            strict: false,
+        })
+    }
+
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    pub fn environment(&self) -> Environment {
+        self.environment
+    }
+
+    /// Fails unless `encoded` is this segment's environment.
+    fn check(&self, encoded: i128) -> Result<(), Error> {
+        if encoded != self.environment as i128 {
+            return Err(Error::EnvironmentMismatch {
+                expected: self.environment,
+                actual: encoded as u128,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl SpecSegment<i128, Environment> for EnvironmentSegment {
+    fn size(&self) -> u8 {
+        self.size
+    }
+
+    fn upper_bound(&self) -> Environment {
+        Environment::Production
+    }
+
+    fn encode(&self) -> Result<i128, Error> {
+        Ok(self.environment as i128)
+    }
+
+    fn decode(&self, encoded: i128) -> Result<Environment, Error> {
+        if self.strict {
+            self.check(encoded)?;
+        }
+        u8::try_from(encoded)
+            .ok()
+            .and_then(Environment::from_value)
+            .ok_or(Error::UnknownValue {
+                value: encoded as u128 & mask(self.size),
+            })
+    }
+
+    fn verify(&self, encoded: i128) -> Result<(), Error> {
+        if self.strict {
+            return self.check(encoded);
+        }
+        self.decode(encoded).map(|_| ())
+    }
+
+    crate::clone_box!(i128, Environment);
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::{Layout, RandomSegment, Spec2};
+
+    fn spec(segment: EnvironmentSegment) -> Spec2<i128, Environment, i128> {
+        Spec2 {
+            segments: (Box::new(segment), Box::new(RandomSegment::new(62))),
+        }
+    }
+
+    #[test]
+    fn strict_segments_reject_other_environments() {
+        let prod = EnvironmentSegment::new(2, "prod".parse().unwrap()).unwrap();
+        let staging = EnvironmentSegment::new(2, Environment::Staging).unwrap();
+        let id = spec(prod).generate().unwrap();
+        assert_eq!(3, id >> 62);
+
+        assert_eq!(
+            Environment::Production,
+            spec(staging).decompose(id).unwrap().0
+        );
+        assert!(spec(staging).verify(id).is_valid());
+
+        let strict = spec(staging.strict());
+        let error = strict.decompose(id).unwrap_err();
+        assert_eq!("environment_mismatch", error.code());
+        assert_eq!(
+            "segment 0: production ID used in staging",
+            error.to_string()
+        );
+        assert!(!strict.verify(id).is_valid());
+        let id = strict.generate().unwrap();
+        assert_eq!(Environment::Staging, strict.decompose(id).unwrap().0);
+    }
+
+    #[test]
+    fn parses_environment_names() {
+        assert_eq!(Ok(Environment::Development), "DEV".parse().map_err(drop));
+        assert_eq!(Ok(Environment::Staging), "staging".parse().map_err(drop));
+        assert!("qa".parse::<Environment>().is_err());
+        assert!(matches!(
+            EnvironmentSegment::new(1, Environment::Test),
+            Err(Error::SizeTooSmall { size: 1, min: 2 })
+        ));
+        let segment = EnvironmentSegment::new(4, Environment::Test).unwrap();
+        assert!(matches!(
+            segment.decode(9),
+            Err(Error::UnknownValue { value: 9 })
+        ));
+        assert_eq!(
+            "ID of unknown environment 9 used in test",
+            segment.strict().decode(9).unwrap_err().to_string()
+        );
+        let wide = EnvironmentSegment::new(12, Environment::Test)
+            .unwrap()
+            .strict();
+        assert!(matches!(
+            wide.decode(300),
+            Err(Error::EnvironmentMismatch { actual: 300, .. })
+        ));
+        assert_eq!(
+            "ID of unknown environment 300 used in test",
+            wide.decode(300).unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,413 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use time::Duration;
+
+use crate::{mask, Environment, SpecError};
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Arithmetic on an ID or a timestamp left the range it is computed in.
+    Overflow,
+    /// A width is larger than the operation supports, e.g. segments adding up to over 128 bits.
+    SizeExceeded {
+        size: u32,
+        max: u32,
+    },
+    /// A width is smaller than the operation needs.
+    SizeTooSmall {
+        size: u32,
+        min: u32,
+    },
+    /// A segment is not as wide as the operation expects it to be.
+    SizeMismatch {
+        expected: u32,
+        actual: u32,
+    },
+    /// One value per segment was expected.
+    SegmentCountMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    /// `value` has bits set above the `size` bits it has to fit in.
+    ValueTooLarge {
+        value: u128,
+        size: u8,
+    },
+    /// The clock reads earlier than it did for a previous ID.
+    ClockRegression {
+        by: Duration,
+    },
+    /// A timestamp segment's epoch is still `by` ahead of the clock.
+    EpochInFuture {
+        by: Duration,
+    },
+    /// A segment has no values left to hand out.
+    Exhausted,
+    /// The character at byte `pos` is not part of the encoding.
+    ParseError {
+        pos: usize,
+        char: char,
+    },
+    /// The input decodes, but is not the encoding this crate would have produced.
+    NonCanonical,
+    Encryption {
+        reason: &'static str,
+    },
+    Verification {
+        reason: &'static str,
+    },
+    /// A timestamp does not fit the range `time` can represent.
+    Time(time::error::ComponentRange),
+    /// The layout has the listed problems.
+    InvalidSpec(Vec<SpecError>),
+    /// No layout is registered for the version an ID carries.
+    UnknownVersion {
+        version: u128,
+    },
+    /// The build lacks what the operation needs, e.g. a system clock without `std`.
+    Unsupported {
+        reason: &'static str,
+    },
+    /// An argument other than a width or a value is out of its domain, e.g. an empty window.
+    InvalidArgument {
+        reason: &'static str,
+    },
+    /// The layout has no segment to derive a partition from.
+    NoPartitionSegment,
+    /// A segment's registry has no value for `name`.
+    UnknownName {
+        name: String,
+    },
+    /// A segment's registry has no name for `value`.
+    UnknownValue {
+        value: u128,
+    },
+    /// An ID minted in the `actual` environment reached a deployment of the `expected` one.
+    EnvironmentMismatch {
+        expected: Environment,
+        actual: u128,
+    },
+    /// A registry already holds an entry for `name`.
+    AlreadyRegistered {
+        name: String,
+    },
+    /// `source` was raised by the segment at `index`, counted from the most significant one,
+    /// which carries `label` if it is [labeled](crate::Labeled).
+    InSegment {
+        index: usize,
+        label: Option<String>,
+        source: Box<Error>,
+    },
+}
+
+/// Structured fields of an [`Error`], for API responses and metrics labels.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ErrorDetails {
+    pub segment: Option<usize>,
+    pub label: Option<String>,
+    /// The offending value or size.
+    pub value: Option<u128>,
+    /// The bound `value` broke: a maximum, a minimum or the expected value, depending on the code.
+    pub limit: Option<u128>,
+    pub position: Option<usize>,
+}
+
+impl Error {
+    /// Stable, machine-readable identifier of the error kind. Codes never change meaning once
+    /// released, unlike the `Display` text.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::Overflow => "overflow",
+            Error::SizeExceeded { .. } => "size_exceeded",
+            Error::SizeTooSmall { .. } => "size_too_small",
+            Error::SizeMismatch { .. } => "size_mismatch",
+            Error::SegmentCountMismatch { .. } => "segment_count_mismatch",
+            Error::ValueTooLarge { .. } => "value_too_large",
+            Error::ClockRegression { .. } => "clock_regression",
+            Error::EpochInFuture { .. } => "epoch_in_future",
+            Error::Exhausted => "exhausted",
+            Error::ParseError { .. } => "parse_error",
+            Error::NonCanonical => "non_canonical",
+            Error::Encryption { .. } => "encryption",
+            Error::Verification { .. } => "verification",
+            Error::Time(_) => "time_out_of_range",
+            Error::InvalidSpec(_) => "invalid_spec",
+            Error::UnknownVersion { .. } => "unknown_version",
+            Error::Unsupported { .. } => "unsupported",
+            Error::InvalidArgument { .. } => "invalid_argument",
+            Error::NoPartitionSegment => "no_partition_segment",
+            Error::UnknownName { .. } => "unknown_name",
+            Error::UnknownValue { .. } => "unknown_value",
+            Error::EnvironmentMismatch { .. } => "environment_mismatch",
+            Error::AlreadyRegistered { .. } => "already_registered",
+            Error::InSegment { source, .. } => source.code(),
+        }
+    }
+
+    pub fn details(&self) -> ErrorDetails {
+        let sizes = |value: u32, limit: u32| ErrorDetails {
+            value: Some(u128::from(value)),
+            limit: Some(u128::from(limit)),
+            ..ErrorDetails::default()
+        };
+        match self {
+            Error::SizeExceeded { size, max } => sizes(*size, *max),
+            Error::SizeTooSmall { size, min } => sizes(*size, *min),
+            Error::SizeMismatch { expected, actual } => sizes(*actual, *expected),
+            Error::SegmentCountMismatch { expected, actual } => ErrorDetails {
+                value: Some(*actual as u128),
+                limit: Some(*expected as u128),
+                ..ErrorDetails::default()
+            },
+            Error::ValueTooLarge { value, size } => ErrorDetails {
+                value: Some(*value),
+                limit: Some(mask(*size)),
+                ..ErrorDetails::default()
+            },
+            Error::ClockRegression { by } | Error::EpochInFuture { by } => ErrorDetails {
+                value: u128::try_from(by.whole_nanoseconds()).ok(),
+                ..ErrorDetails::default()
+            },
+            Error::UnknownVersion { version: value } | Error::UnknownValue { value } => {
+                ErrorDetails {
+                    value: Some(*value),
+                    ..ErrorDetails::default()
+                }
+            }
+            Error::EnvironmentMismatch { expected, actual } => ErrorDetails {
+                value: Some(*actual),
+                limit: Some((*expected as u8).into()),
+                ..ErrorDetails::default()
+            },
+            Error::ParseError { pos, .. } => ErrorDetails {
+                position: Some(*pos),
+                ..ErrorDetails::default()
+            },
+            Error::InSegment {
+                index,
+                label,
+                source,
+            } => ErrorDetails {
+                segment: Some(*index),
+                label: label.clone(),
+                ..source.details()
+            },
+            _ => ErrorDetails::default(),
+        }
+    }
+
+    /// Body for HTTP error responses: `code`, `message` and the non-empty [`ErrorDetails`].
+    /// Values and limits are decimal strings, as they may exceed what JSON numbers hold.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> serde_json::Value {
+        let details = self.details();
+        let mut body = serde_json::json!({ "code": self.code(), "message": self.to_string() });
+        let fields = [
+            ("segment", details.segment.map(serde_json::Value::from)),
+            ("label", details.label.map(serde_json::Value::from)),
+            ("value", details.value.map(|value| value.to_string().into())),
+            ("limit", details.limit.map(|limit| limit.to_string().into())),
+            ("position", details.position.map(serde_json::Value::from)),
+        ];
+        for (key, field) in fields {
+            if let Some(field) = field {
+                body[key] = field;
+            }
+        }
+        body
+    }
+
+    pub(crate) fn in_segment(self, index: usize) -> Self {
+        self.in_labeled_segment(index, None)
+    }
+
+    pub(crate) fn in_labeled_segment(self, index: usize, label: Option<&str>) -> Self {
+        Error::InSegment {
+            index,
+            label: label.map(String::from),
+            source: Box::new(self),
+        }
+    }
+
+    /// Names the segment an [`Error::InSegment`] was raised by with `label(index)`, unless it
+    /// is named already.
+    pub(crate) fn with_label<'a>(self, label: impl FnOnce(usize) -> Option<&'a str>) -> Self {
+        match self {
+            Error::InSegment {
+                index,
+                label: None,
+                source,
+            } => Error::InSegment {
+                index,
+                label: label(index).map(String::from),
+                source,
+            },
+            error => error,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Overflow => f.write_str("arithmetic overflow"),
+            Error::SizeExceeded { size, max } => {
+                write!(f, "size of {} bits exceeds the maximum of {}", size, max)
+            }
+            Error::SizeTooSmall { size, min } => {
+                write!(f, "size of {} bits is below the minimum of {}", size, min)
+            }
+            Error::SizeMismatch { expected, actual } => {
+                write!(f, "expected a size of {} bits, got {}", expected, actual)
+            }
+            Error::SegmentCountMismatch { expected, actual } => {
+                write!(f, "expected {} segments, got {}", expected, actual)
+            }
+            Error::ValueTooLarge { value, size } => {
+                write!(f, "value {} does not fit in {} bits", value, size)
+            }
+            Error::ClockRegression { by } => write!(f, "clock moved backwards by {}", by),
+            Error::EpochInFuture { by } => write!(f, "epoch lies {} in the future", by),
+            Error::Exhausted => f.write_str("no values left to generate"),
+            Error::ParseError { pos, char } => {
+                write!(f, "unexpected character {:?} at position {}", char, pos)
+            }
+            Error::NonCanonical => f.write_str("input is not in canonical form"),
+            Error::Encryption { reason } => write!(f, "encryption failed: {}", reason),
+            Error::Verification { reason } => write!(f, "verification failed: {}", reason),
+            Error::Time(_) => f.write_str("timestamp out of range"),
+            Error::InvalidSpec(errors) => {
+                f.write_str("invalid spec")?;
+                for error in errors {
+                    write!(f, "; {}", error)?;
+                }
+                Ok(())
+            }
+            Error::UnknownVersion { version } => write!(f, "unknown spec version {}", version),
+            Error::Unsupported { reason } => write!(f, "unsupported: {}", reason),
+            Error::InvalidArgument { reason } => write!(f, "invalid argument: {}", reason),
+            Error::NoPartitionSegment => f.write_str("no segment keys partitions"),
+            Error::UnknownName { name } => write!(f, "{:?} is not registered", name),
+            Error::UnknownValue { value } => write!(f, "no name is registered for {}", value),
+            Error::EnvironmentMismatch { expected, actual } => {
+                match u8::try_from(*actual).ok().and_then(Environment::from_value) {
+                    Some(actual) => write!(f, "{} ID used in {}", actual, expected),
+                    None => write!(
+                        f,
+                        "ID of unknown environment {} used in {}",
+                        actual, expected
+                    ),
+                }
+            }
+            Error::AlreadyRegistered { name } => write!(f, "{} is already registered", name),
+            Error::InSegment {
+                index,
+                label,
+                source,
+            } => write!(f, "{}: {}", SegmentName(*index, label.as_deref()), source),
+        }
+    }
+}
+
+/// A segment as messages name it: by its label if it has one, by its index otherwise.
+pub(crate) struct SegmentName<'a>(pub(crate) usize, pub(crate) Option<&'a str>);
+
+impl fmt::Display for SegmentName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.1 {
+            Some(label) => write!(f, "{} segment", label),
+            None => write!(f, "segment {}", self.0),
+        }
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Error::Time(source) => Some(source),
+            Error::InSegment { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<time::error::ComponentRange> for Error {
+    fn from(source: time::error::ComponentRange) -> Self {
+        Error::Time(source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::error::Error as _;
+
+    use super::*;
+
+    #[test]
+    fn display_and_source() {
+        let error = Error::ParseError { pos: 3, char: 'U' };
+        assert_eq!("unexpected character 'U' at position 3", error.to_string());
+        assert!(error.source().is_none());
+
+        let error = Error::from(time::OffsetDateTime::from_unix_timestamp(i64::MAX).unwrap_err());
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn codes_and_details() {
+        let error = Error::ValueTooLarge {
+            value: 300,
+            size: 8,
+        }
+        .in_segment(1);
+        assert_eq!("value_too_large", error.code());
+        assert_eq!(
+            ErrorDetails {
+                segment: Some(1),
+                label: None,
+                value: Some(300),
+                limit: Some(255),
+                position: None,
+            },
+            error.details()
+        );
+        assert_eq!(
+            "segment 1: value 300 does not fit in 8 bits",
+            error.to_string()
+        );
+
+        let error = Error::Exhausted.in_labeled_segment(2, Some("tenant"));
+        assert_eq!(
+            "tenant segment: no values left to generate",
+            error.to_string()
+        );
+        assert_eq!(Some("tenant".into()), error.details().label);
+        assert_eq!(Some(2), error.details().segment);
+        let error = Error::Exhausted
+            .in_segment(2)
+            .with_label(|index| (index == 2).then_some("tenant"));
+        assert_eq!(
+            "tenant segment: no values left to generate",
+            error.to_string()
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_body() {
+        let error = Error::ParseError { pos: 3, char: 'U' };
+        assert_eq!(
+            serde_json::json!({
+                "code": "parse_error",
+                "message": "unexpected character 'U' at position 3",
+                "position": 3,
+            }),
+            error.to_json()
+        );
+    }
+}
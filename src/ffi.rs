@@ -0,0 +1,302 @@
+//! C API, declared in `include/utid.h`, so C, C++ and Swift services share layouts with Rust
+//! ones. Specs are opaque handles built from the same JSON configs or environment variables as
+//! [`DynamicSpec`]; IDs travel as [`UtidId`] pairs of 64-bit halves. Every function reports its
+//! outcome as a [`UtidStatus`] and writes results through out-pointers.
+
+use core::ffi::{c_char, CStr};
+use core::ptr;
+
+use crate::encoding::{decode_base32, encode_base32};
+use crate::{DynamicSpec, Error, Layout, Utid, Value};
+
+/// An ID split into its upper and lower 64 bits, as [`Utid::to_u64_pair`] does.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UtidId {
+    pub hi: u64,
+    pub lo: u64,
+}
+
+impl From<i128> for UtidId {
+    fn from(value: i128) -> Self {
+        let (hi, lo) = Utid(value).to_u64_pair();
+        UtidId { hi, lo }
+    }
+}
+
+impl From<UtidId> for i128 {
+    fn from(id: UtidId) -> Self {
+        Utid::from_u64_pair(id.hi, id.lo).0
+    }
+}
+
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UtidStatus {
+    Ok = 0,
+    NullPointer = 1,
+    InvalidUtf8 = 2,
+    InvalidConfig = 3,
+    BufferTooSmall = 4,
+    ParseError = 5,
+    ValueTooLarge = 6,
+    ClockRegression = 7,
+    EpochInFuture = 8,
+    Exhausted = 9,
+    Overflow = 10,
+    Other = 255,
+}
+
+impl From<Error> for UtidStatus {
+    fn from(error: Error) -> Self {
+        match error.code() {
+            "parse_error" | "non_canonical" => UtidStatus::ParseError,
+            "value_too_large" | "size_mismatch" => UtidStatus::ValueTooLarge,
+            "clock_regression" => UtidStatus::ClockRegression,
+            "epoch_in_future" => UtidStatus::EpochInFuture,
+            "exhausted" => UtidStatus::Exhausted,
+            "overflow" | "time_out_of_range" => UtidStatus::Overflow,
+            _ => UtidStatus::Other,
+        }
+    }
+}
+
+/// Opaque handle to a spec; free it with [`utid_spec_free`].
+pub struct UtidSpec(DynamicSpec);
+
+/// Length of a base32-encoded ID, without the terminating NUL.
+pub const UTID_ENCODED_LEN: usize = 26;
+
+unsafe fn str_arg<'a>(value: *const c_char) -> Result<&'a str, UtidStatus> {
+    if value.is_null() {
+        return Err(UtidStatus::NullPointer);
+    }
+    CStr::from_ptr(value)
+        .to_str()
+        .map_err(|_| UtidStatus::InvalidUtf8)
+}
+
+fn status(result: Result<(), UtidStatus>) -> UtidStatus {
+    result.err().unwrap_or(UtidStatus::Ok)
+}
+
+unsafe fn new_spec(
+    out: *mut *mut UtidSpec,
+    build: impl FnOnce() -> Result<DynamicSpec, UtidStatus>,
+) -> UtidStatus {
+    status((|| {
+        if out.is_null() {
+            return Err(UtidStatus::NullPointer);
+        }
+        let spec = build()?;
+        *out = Box::into_raw(Box::new(UtidSpec(spec)));
+        Ok(())
+    })())
+}
+
+/// Builds a spec from its JSON config.
+///
+/// # Safety
+///
+/// `json` must be a NUL-terminated string and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn utid_spec_new_json(
+    json: *const c_char,
+    out: *mut *mut UtidSpec,
+) -> UtidStatus {
+    new_spec(out, || {
+        DynamicSpec::from_json(str_arg(json)?).map_err(|_| UtidStatus::InvalidConfig)
+    })
+}
+
+/// Builds a spec from the `{prefix}_LAYOUT`, `{prefix}_EPOCH` and `{prefix}_NODE_ID`
+/// environment variables, as [`DynamicSpec::from_env`] does.
+///
+/// # Safety
+///
+/// `prefix` must be a NUL-terminated string and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn utid_spec_new_env(
+    prefix: *const c_char,
+    out: *mut *mut UtidSpec,
+) -> UtidStatus {
+    new_spec(out, || {
+        DynamicSpec::from_env(str_arg(prefix)?).map_err(|_| UtidStatus::InvalidConfig)
+    })
+}
+
+/// # Safety
+///
+/// `spec` must be null or a handle from a `utid_spec_new_*` function not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn utid_spec_free(spec: *mut UtidSpec) {
+    if !spec.is_null() {
+        drop(Box::from_raw(spec));
+    }
+}
+
+/// Number of segments, i.e. how many values [`utid_decompose`] writes.
+///
+/// # Safety
+///
+/// `spec` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn utid_spec_segment_count(spec: *const UtidSpec) -> usize {
+    spec.as_ref().map_or(0, |spec| spec.0.segments().len())
+}
+
+/// # Safety
+///
+/// `spec` must be null or a live handle and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn utid_generate(spec: *const UtidSpec, out: *mut UtidId) -> UtidStatus {
+    status((|| {
+        let spec = spec.as_ref().ok_or(UtidStatus::NullPointer)?;
+        if out.is_null() {
+            return Err(UtidStatus::NullPointer);
+        }
+        *out = spec.0.generate()?.into();
+        Ok(())
+    })())
+}
+
+/// Writes one value per segment to `values`: nanoseconds since the Unix epoch for timestamps,
+/// the segment value for everything else.
+///
+/// # Safety
+///
+/// `spec` must be null or a live handle and `values` valid for `len` writes.
+#[no_mangle]
+pub unsafe extern "C" fn utid_decompose(
+    spec: *const UtidSpec,
+    id: UtidId,
+    values: *mut UtidId,
+    len: usize,
+) -> UtidStatus {
+    status((|| {
+        let spec = spec.as_ref().ok_or(UtidStatus::NullPointer)?;
+        if values.is_null() {
+            return Err(UtidStatus::NullPointer);
+        }
+        if len < spec.0.segments().len() {
+            return Err(UtidStatus::BufferTooSmall);
+        }
+        for (index, value) in spec.0.decompose(id.into())?.into_iter().enumerate() {
+            let raw = match value {
+                Value::Timestamp(timestamp) => timestamp.unix_timestamp_nanos(),
+                Value::Integer(value) => value,
+            };
+            *values.add(index) = raw.into();
+        }
+        Ok(())
+    })())
+}
+
+/// Writes the ID as NUL-terminated Crockford base32 to `buf`, which must hold at least
+/// `UTID_ENCODED_LEN + 1` bytes.
+///
+/// # Safety
+///
+/// `buf` must be valid for `len` writes.
+#[no_mangle]
+pub unsafe extern "C" fn utid_encode(id: UtidId, buf: *mut c_char, len: usize) -> UtidStatus {
+    status((|| {
+        if buf.is_null() {
+            return Err(UtidStatus::NullPointer);
+        }
+        if len <= UTID_ENCODED_LEN {
+            return Err(UtidStatus::BufferTooSmall);
+        }
+        let encoded = encode_base32(id.into(), 128);
+        ptr::copy_nonoverlapping(encoded.as_ptr().cast(), buf, UTID_ENCODED_LEN);
+        *buf.add(UTID_ENCODED_LEN) = 0;
+        Ok(())
+    })())
+}
+
+/// Parses Crockford base32, as [`utid_encode`] writes it.
+///
+/// # Safety
+///
+/// `encoded` must be a NUL-terminated string and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn utid_parse(encoded: *const c_char, out: *mut UtidId) -> UtidStatus {
+    status((|| {
+        let encoded = str_arg(encoded)?;
+        if out.is_null() {
+            return Err(UtidStatus::NullPointer);
+        }
+        *out = decode_base32(encoded)?.into();
+        Ok(())
+    })())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use super::*;
+
+    #[test]
+    fn generate_decompose_and_encode() {
+        let json = CString::new(
+            r#"{"segments":[{"kind":"constant","size":64,"value":7},{"kind":"random","size":64}]}"#,
+        )
+        .unwrap();
+        let mut spec = ptr::null_mut();
+        unsafe {
+            assert_eq!(UtidStatus::Ok, utid_spec_new_json(json.as_ptr(), &mut spec));
+            assert_eq!(2, utid_spec_segment_count(spec));
+
+            let mut id = UtidId { hi: 0, lo: 0 };
+            assert_eq!(UtidStatus::Ok, utid_generate(spec, &mut id));
+            assert_eq!(7, id.hi);
+
+            let mut values = [UtidId { hi: 0, lo: 0 }; 2];
+            assert_eq!(
+                UtidStatus::BufferTooSmall,
+                utid_decompose(spec, id, values.as_mut_ptr(), 1)
+            );
+            assert_eq!(
+                UtidStatus::Ok,
+                utid_decompose(spec, id, values.as_mut_ptr(), 2)
+            );
+            assert_eq!(
+                [UtidId { hi: 0, lo: 7 }, UtidId { hi: 0, lo: id.lo }],
+                values
+            );
+
+            let mut buf = [0 as c_char; UTID_ENCODED_LEN + 1];
+            assert_eq!(UtidStatus::Ok, utid_encode(id, buf.as_mut_ptr(), buf.len()));
+            let mut parsed = UtidId { hi: 0, lo: 0 };
+            assert_eq!(UtidStatus::Ok, utid_parse(buf.as_ptr(), &mut parsed));
+            assert_eq!(id, parsed);
+
+            utid_spec_free(spec);
+        }
+    }
+
+    #[test]
+    fn reports_errors() {
+        let mut spec = ptr::null_mut();
+        let mut id = UtidId { hi: 0, lo: 0 };
+        let invalid = CString::new("{}").unwrap();
+        let malformed = CString::new("0U").unwrap();
+        unsafe {
+            assert_eq!(
+                UtidStatus::InvalidConfig,
+                utid_spec_new_json(invalid.as_ptr(), &mut spec)
+            );
+            assert_eq!(
+                UtidStatus::NullPointer,
+                utid_spec_new_json(ptr::null(), &mut spec)
+            );
+            assert_eq!(UtidStatus::NullPointer, utid_generate(spec, &mut id));
+            assert_eq!(
+                UtidStatus::ParseError,
+                utid_parse(malformed.as_ptr(), &mut id)
+            );
+            utid_spec_free(spec);
+        }
+    }
+}
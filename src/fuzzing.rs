@@ -0,0 +1,79 @@
+use arbitrary::{Arbitrary, Result, Unstructured};
+use time::{Date, Duration, Month};
+
+use crate::{mask, SegmentConfig, SpecConfig, TimestampUnit, Utid};
+
+impl<'a> Arbitrary<'a> for Utid {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Utid(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for TimestampUnit {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[
+            TimestampUnit::Seconds,
+            TimestampUnit::Milliseconds,
+            TimestampUnit::Microseconds,
+            TimestampUnit::Nanoseconds,
+        ])?)
+    }
+}
+
+fn segment(u: &mut Unstructured<'_>, size: u8) -> Result<SegmentConfig> {
+    Ok(match u.int_in_range(0..=2)? {
+        0 => SegmentConfig::Timestamp {
+            size,
+            unit: u.arbitrary()?,
+            epoch: Date::from_calendar_date(
+                u.int_in_range(1970..=2030)?,
+                Month::try_from(u.int_in_range(1..=12)?).unwrap_or(Month::January),
+                u.int_in_range(1..=28)?,
+            )
+            .unwrap_or(Date::MIN),
+            max_skew: Duration::milliseconds(u.int_in_range(0..=3_600_000)?),
+        },
+        1 => SegmentConfig::Random {
+            size,
+            os_rng: u.arbitrary()?,
+        },
+        _ => SegmentConfig::Constant {
+            size,
+            value: (u.arbitrary::<u128>()? & mask(size)) as i128,
+        },
+    })
+}
+
+/// Always a valid layout: one to four non-empty segments within 128 bits.
+impl<'a> Arbitrary<'a> for SpecConfig {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut remaining = 128u8;
+        let mut segments = Vec::new();
+        for _ in 0..u.int_in_range(1..=4)? {
+            if remaining == 0 {
+                break;
+            }
+            let size = u.int_in_range(1..=remaining)?;
+            remaining -= size;
+            segments.push(segment(u, size)?);
+        }
+        Ok(SpecConfig { segments })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    use crate::SpecConfig;
+
+    #[test]
+    fn arbitrary_specs_are_valid() {
+        let bytes: Vec<u8> = (0..4096u32).map(|i| (i * 7919 % 251) as u8).collect();
+        let mut u = Unstructured::new(&bytes);
+        for _ in 0..50 {
+            let config = SpecConfig::arbitrary(&mut u).unwrap();
+            assert!(config.build().is_ok(), "{:?}", config);
+        }
+    }
+}
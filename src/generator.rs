@@ -0,0 +1,729 @@
+use alloc::string::String;
+use alloc::sync::Arc;
+use core::fmt;
+
+use time::Duration;
+
+use crate::replay::{Record, Recorder};
+#[cfg(all(feature = "std", target_has_atomic = "64"))]
+use crate::Sequence;
+use crate::{mask, Error, Spec, Spec2, Spec3, Spec4, Utid};
+
+/// Shifts and masks of a layout of `N` segments, computed once so packing and unpacking an ID
+/// is a fixed sequence of shifts rather than a walk over the segment sizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Packing<const N: usize> {
+    sizes: [u8; N],
+    shifts: [u32; N],
+    masks: [u128; N],
+    size: u32,
+}
+
+impl<const N: usize> Packing<N> {
+    /// `sizes` run from the most significant segment to the least.
+    pub fn new(sizes: [u8; N]) -> Result<Self, Error> {
+        let mut shifts = [0; N];
+        let mut masks = [0; N];
+        let mut size = 0u32;
+        for index in (0..N).rev() {
+            shifts[index] = size;
+            masks[index] = mask(sizes[index]);
+            size += u32::from(sizes[index]);
+        }
+        if size > 128 {
+            return Err(Error::SizeExceeded { size, max: 128 });
+        }
+        Ok(Self {
+            sizes,
+            shifts,
+            masks,
+            size,
+        })
+    }
+
+    /// Width of the packed IDs in bits.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Packs raw segment values into an ID, rejecting values wider than their segment.
+    pub fn join(&self, values: [u128; N]) -> Result<i128, Error> {
+        let mut id = 0u128;
+        for (index, value) in values.into_iter().enumerate() {
+            if value & !self.masks[index] != 0 {
+                return Err(Error::ValueTooLarge {
+                    value,
+                    size: self.sizes[index],
+                }
+                .in_segment(index));
+            }
+            id |= value.checked_shl(self.shifts[index]).unwrap_or(0);
+        }
+        Ok(id as i128)
+    }
+
+    /// Splits an ID into its raw segment values, rejecting IDs with bits set above the layout's
+    /// width. Layouts of up to 64 bits shift in 64-bit registers, which is cheaper on 32-bit
+    /// targets.
+    pub fn split(&self, id: i128) -> Result<[u128; N], Error> {
+        if self.size < 128 && (id as u128) >> self.size != 0 {
+            return Err(Error::ValueTooLarge {
+                value: id as u128,
+                size: self.size as u8,
+            });
+        }
+        let mut values = [0; N];
+        if self.size <= 64 {
+            let id = id as u64;
+            for (index, value) in values.iter_mut().enumerate() {
+                let narrow = id.checked_shr(self.shifts[index]).unwrap_or(0);
+                *value = u128::from(narrow & self.masks[index] as u64);
+            }
+        } else {
+            let id = id as u128;
+            for (index, value) in values.iter_mut().enumerate() {
+                *value = id.checked_shr(self.shifts[index]).unwrap_or(0) & self.masks[index];
+            }
+        }
+        Ok(values)
+    }
+}
+
+/// A spec of `N` segments decoding to `Out`, encoded and decoded one raw value per segment, so
+/// a [`Generator`] can pack them with a precomputed [`Packing`].
+pub trait SegmentTuple<const N: usize, Out> {
+    fn segment_sizes(&self) -> [u8; N];
+    fn encode_segments(&self) -> Result<[u128; N], Error>;
+    fn decode_segments(&self, values: [u128; N]) -> Result<Out, Error>;
+
+    /// [`encode_segments`](Self::encode_segments) for the routing `key`; see
+    /// [`SpecSegment::encode_with`](crate::SpecSegment::encode_with).
+    fn encode_segments_with(&self, _key: &[u8]) -> Result<[u128; N], Error> {
+        self.encode_segments()
+    }
+
+    /// The [label](crate::SpecSegment::label) of the segment at `index`, if it has one.
+    fn segment_label(&self, _index: usize) -> Option<&str> {
+        None
+    }
+}
+
+/// What a [`Generator`] reports to its hook, once per generated ID or failed attempt. Node IDs
+/// are passed in rather than leased by this crate, so losing a lease is the caller's to report.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum Event<'a> {
+    Generated(i128),
+    /// The clock of the segment at `segment` read `by` earlier than for a previous ID, as
+    /// [monotonic](crate::TimestampSegment::monotonic) timestamp segments report.
+    ClockRegression {
+        segment: usize,
+        by: Duration,
+    },
+    /// The segment at `segment`, e.g. a per-tick sequence, had no values left.
+    Exhausted {
+        segment: usize,
+    },
+    /// Generating failed for any other reason.
+    Failed(&'a Error),
+}
+
+impl<'a> Event<'a> {
+    fn from_error(error: &'a Error) -> Self {
+        match error {
+            Error::InSegment { index, source, .. } => match **source {
+                Error::ClockRegression { by } => Event::ClockRegression {
+                    segment: *index,
+                    by,
+                },
+                Error::Exhausted => Event::Exhausted { segment: *index },
+                _ => Event::Failed(error),
+            },
+            _ => Event::Failed(error),
+        }
+    }
+}
+
+/// What [`Generator::health`] found, for services to surface from a health-check endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Health {
+    /// Every timestamp segment could read its clock and encode the time.
+    pub clock_ok: bool,
+    /// Every random segment could draw from its random number generator.
+    pub entropy_ok: bool,
+    /// Time left until the timestamp bits run out, if the spec has a timestamp; negative once
+    /// they have.
+    pub exhausted_in: Option<Duration>,
+    /// Whether the node ID's lease is held, if the generator was given a
+    /// [lease check](Generator::with_lease).
+    pub lease_ok: Option<bool>,
+    /// Percentage of the latest tick's sequence values handed out, if the generator was given
+    /// a [sequence](Generator::with_sequence).
+    pub sequence_utilization: Option<u8>,
+}
+
+impl Health {
+    pub fn is_ok(&self) -> bool {
+        self.clock_ok
+            && self.entropy_ok
+            && self.lease_ok != Some(false)
+            && self.exhausted_in.is_none_or(Duration::is_positive)
+    }
+}
+
+#[derive(Clone)]
+struct Hook(Arc<dyn Fn(&Event) + Send + Sync>);
+
+impl fmt::Debug for Hook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Hook")
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Clone)]
+struct Probe<T>(Arc<dyn Fn() -> T + Send + Sync>);
+
+#[cfg(feature = "std")]
+impl<T> fmt::Debug for Probe<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Probe")
+    }
+}
+
+/// Wraps a spec with its [`Packing`] computed once, for services generating on a hot path.
+#[derive(Debug, Clone)]
+pub struct Generator<S, const N: usize> {
+    spec: S,
+    packing: Packing<N>,
+    hook: Option<Hook>,
+    #[cfg(feature = "std")]
+    lease: Option<Probe<bool>>,
+    #[cfg(feature = "std")]
+    sequence: Option<Probe<u8>>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<crate::metrics_support::SpecMetrics>,
+}
+
+impl<S, const N: usize> Generator<S, N> {
+    pub fn new<Out>(spec: S) -> Result<Self, Error>
+    where
+        S: SegmentTuple<N, Out>,
+    {
+        let packing = Packing::new(spec.segment_sizes())?;
+        Ok(Self {
+            spec,
+            packing,
+            hook: None,
+            #[cfg(feature = "std")]
+            lease: None,
+            #[cfg(feature = "std")]
+            sequence: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        })
+    }
+
+    /// Records generation counters and latencies through the `metrics` facade, labelled with
+    /// the spec's [fingerprint](crate::SpecConfig::fingerprint), or `custom` if it has segments
+    /// without a config. Handles are registered now, with the recorder installed at this point.
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self) -> Self
+    where
+        S: crate::Layout,
+    {
+        let fingerprint = self.spec.config().map(|config| config.fingerprint());
+        self.metrics = Some(crate::metrics_support::SpecMetrics::new(fingerprint));
+        self
+    }
+
+    /// Calls `hook` with an [`Event`] for every ID generated and every attempt that failed, for
+    /// logging, sampling or alerting. Hooks run on the generating thread and should return
+    /// quickly.
+    pub fn with_hook(mut self, hook: impl Fn(&Event) + Send + Sync + 'static) -> Self {
+        self.hook = Some(Hook(Arc::new(hook)));
+        self
+    }
+
+    /// Reports in [`health`](Self::health) whether the lease on the node ID the spec encodes
+    /// is still held, as `held` tells; the crate does not lease node IDs itself.
+    #[cfg(feature = "std")]
+    pub fn with_lease(mut self, held: impl Fn() -> bool + Send + Sync + 'static) -> Self {
+        self.lease = Some(Probe(Arc::new(held)));
+        self
+    }
+
+    /// Reports `sequence`'s [utilization](Sequence::utilization) in [`health`](Self::health),
+    /// for specs whose segments draw from it.
+    #[cfg(all(feature = "std", target_has_atomic = "64"))]
+    pub fn with_sequence(mut self, sequence: Arc<Sequence>) -> Self {
+        self.sequence = Some(Probe(Arc::new(move || sequence.utilization())));
+        self
+    }
+
+    /// Returns [`Layout::configuration`](crate::Layout::configuration), also emitting it as an
+    /// info-level `tracing` event with the `tracing` feature; call it once at startup.
+    pub fn log_configuration(&self) -> String
+    where
+        S: crate::Layout,
+    {
+        let configuration = self.spec.configuration();
+        #[cfg(feature = "tracing")]
+        crate::trace::configuration(&configuration);
+        configuration
+    }
+
+    /// Encodes each time-based and random segment once, outside of any ID, to check its clock
+    /// and random number generator, which draws one value from each; custom random segments are
+    /// not checked.
+    #[cfg(feature = "std")]
+    pub fn health(&self) -> Health
+    where
+        S: crate::Layout,
+    {
+        let segments = self.spec.segments();
+        let encodes = |kind: fn(&dyn crate::Segment) -> bool| {
+            segments
+                .iter()
+                .filter(|segment| kind(**segment))
+                .all(|segment| segment.encode().is_ok())
+        };
+        Health {
+            clock_ok: encodes(|segment| segment.exhaustion_date().is_some()),
+            entropy_ok: encodes(|segment| {
+                matches!(segment.config(), Some(crate::SegmentConfig::Random { .. }))
+            }),
+            exhausted_in: self.spec.remaining_lifetime(),
+            lease_ok: self.lease.as_ref().map(|Probe(held)| held()),
+            sequence_utilization: self
+                .sequence
+                .as_ref()
+                .map(|Probe(utilization)| utilization()),
+        }
+    }
+
+    pub fn spec(&self) -> &S {
+        &self.spec
+    }
+
+    pub fn packing(&self) -> &Packing<N> {
+        &self.packing
+    }
+
+    pub fn generate<Out>(&self) -> Result<i128, Error>
+    where
+        S: SegmentTuple<N, Out>,
+    {
+        #[cfg(feature = "tracing")]
+        let _span = crate::trace::generate_span(N).entered();
+        self.notify(self.attempt(|| {
+            self.spec
+                .encode_segments()
+                .and_then(|values| self.packing.join(values))
+                .map_err(|e| e.with_label(|index| self.spec.segment_label(index)))
+        }))
+    }
+
+    /// [`generate`](Self::generate) for the routing `key`; see
+    /// [`SpecSegment::encode_with`](crate::SpecSegment::encode_with).
+    pub fn generate_with<Out>(&self, key: &[u8]) -> Result<i128, Error>
+    where
+        S: SegmentTuple<N, Out>,
+    {
+        #[cfg(feature = "tracing")]
+        let _span = crate::trace::generate_span(N).entered();
+        self.notify(self.attempt(|| {
+            self.spec
+                .encode_segments_with(key)
+                .and_then(|values| self.packing.join(values))
+                .map_err(|e| e.with_label(|index| self.spec.segment_label(index)))
+        }))
+    }
+
+    fn attempt<T>(&self, generate: impl FnOnce() -> Result<T, Error>) -> Result<T, Error> {
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            return metrics.time(generate);
+        }
+        generate()
+    }
+
+    fn notify(&self, result: Result<i128, Error>) -> Result<i128, Error> {
+        #[cfg(feature = "tracing")]
+        crate::trace::generated(&result);
+        if let Some(Hook(hook)) = &self.hook {
+            match &result {
+                Ok(id) => hook(&Event::Generated(*id)),
+                Err(error) => hook(&Event::from_error(error)),
+            }
+        }
+        result
+    }
+
+    /// Generates one ID per element of `ids`, so batch callers can reuse one buffer.
+    pub fn generate_into<Out>(&self, ids: &mut [i128]) -> Result<(), Error>
+    where
+        S: SegmentTuple<N, Out>,
+    {
+        for id in ids {
+            *id = self.generate()?;
+        }
+        Ok(())
+    }
+
+    /// [`generate_into`](Self::generate_into) writing each ID as [`Utid::to_bytes`] does.
+    pub fn generate_bytes_into<Out>(&self, ids: &mut [[u8; 16]]) -> Result<(), Error>
+    where
+        S: SegmentTuple<N, Out>,
+    {
+        for id in ids {
+            *id = Utid(self.generate()?).to_bytes();
+        }
+        Ok(())
+    }
+
+    /// [`generate`](Self::generate), passing the ID and the value each segment encoded to
+    /// `recorder`, so [`replay`](Self::replay) can rebuild it later.
+    pub fn generate_recorded<Out>(&self, recorder: &impl Recorder<N>) -> Result<i128, Error>
+    where
+        S: SegmentTuple<N, Out>,
+    {
+        #[cfg(feature = "tracing")]
+        let _span = crate::trace::generate_span(N).entered();
+        let (values, id) = match self.attempt(|| {
+            let values = self.spec.encode_segments()?;
+            let id = self
+                .packing
+                .join(values)
+                .map_err(|e| e.with_label(|index| self.spec.segment_label(index)))?;
+            Ok((values, id))
+        }) {
+            Ok(generated) => generated,
+            Err(error) => return self.notify(Err(error)),
+        };
+        self.notify(Ok(id))?;
+        recorder.record(&Record { id, values });
+        Ok(id)
+    }
+
+    /// Rebuilds a recorded ID from its segment values, without reading the clock or the RNG,
+    /// and checks it is the ID that was recorded.
+    pub fn replay(&self, record: &Record<N>) -> Result<i128, Error> {
+        let id = self.packing.join(record.values)?;
+        if id != record.id {
+            return Err(Error::Verification {
+                reason: "replayed ID differs from the recorded one",
+            });
+        }
+        Ok(id)
+    }
+
+    pub fn decompose<Out>(&self, generated: i128) -> Result<Out, Error>
+    where
+        S: SegmentTuple<N, Out>,
+    {
+        self.spec.decode_segments(self.packing.split(generated)?)
+    }
+}
+
+/// The [`SegmentTuple`] methods for specs holding their segments in a `segments` tuple.
+macro_rules! segment_tuple_methods {
+    ($($r:ident: $index:tt),+) => {
+        fn segment_sizes(&self) -> [u8; [$($index),+].len()] {
+            [$(self.segments.$index.size()),+]
+        }
+
+        fn encode_segments(&self) -> Result<[u128; [$($index),+].len()], Error> {
+            Ok([$(
+                self.segments.$index
+                    .encode()
+                    .map_err(|e| e.in_labeled_segment($index, self.segments.$index.label()))?
+                    as u128
+            ),+])
+        }
+
+        fn encode_segments_with(&self, key: &[u8]) -> Result<[u128; [$($index),+].len()], Error> {
+            Ok([$(
+                self.segments.$index
+                    .encode_with(key)
+                    .map_err(|e| e.in_labeled_segment($index, self.segments.$index.label()))?
+                    as u128
+            ),+])
+        }
+
+        fn decode_segments(
+            &self,
+            values: [u128; [$($index),+].len()],
+        ) -> Result<($($r,)+), Error> {
+            Ok(($(
+                self.segments.$index
+                    .decode(values[$index] as i128)
+                    .map_err(|e| e.in_labeled_segment($index, self.segments.$index.label()))?,
+            )+))
+        }
+
+        fn segment_label(&self, index: usize) -> Option<&str> {
+            [$(self.segments.$index.label()),+].get(index).copied().flatten()
+        }
+    };
+}
+pub(crate) use segment_tuple_methods;
+
+macro_rules! impl_segment_tuple {
+    ($spec:ident<$($r:ident: $index:tt),+>) => {
+        impl<$($r),+> SegmentTuple<{ [$($index),+].len() }, ($($r,)+)>
+            for $spec<i128, $($r),+>
+        {
+            segment_tuple_methods!($($r: $index),+);
+        }
+    };
+}
+
+impl<R> SegmentTuple<1, R> for Spec<i128, R> {
+    fn segment_sizes(&self) -> [u8; 1] {
+        [self.segment.size()]
+    }
+
+    fn encode_segments(&self) -> Result<[u128; 1], Error> {
+        Ok([self
+            .segment
+            .encode()
+            .map_err(|e| e.in_labeled_segment(0, self.segment.label()))? as u128])
+    }
+
+    fn encode_segments_with(&self, key: &[u8]) -> Result<[u128; 1], Error> {
+        Ok([self
+            .segment
+            .encode_with(key)
+            .map_err(|e| e.in_labeled_segment(0, self.segment.label()))? as u128])
+    }
+
+    fn decode_segments(&self, values: [u128; 1]) -> Result<R, Error> {
+        self.segment
+            .decode(values[0] as i128)
+            .map_err(|e| e.in_labeled_segment(0, self.segment.label()))
+    }
+
+    fn segment_label(&self, index: usize) -> Option<&str> {
+        self.segment.label().filter(|_| index == 0)
+    }
+}
+
+impl_segment_tuple!(Spec2<R1: 0, R2: 1>);
+impl_segment_tuple!(Spec3<R1: 0, R2: 1, R3: 2>);
+impl_segment_tuple!(Spec4<R1: 0, R2: 1, R3: 2, R4: 3>);
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+
+    use time::{Date, Month, OffsetDateTime};
+
+    use super::*;
+    use crate::{join, split, ConstantSegment, RandomSegment, TimestampSegment, TimestampUnit};
+
+    #[test]
+    fn packing_matches_join_and_split() {
+        for sizes in [[40u8, 8, 16], [48, 16, 64], [0, 64, 64], [1, 126, 1]] {
+            let packing = Packing::new(sizes).unwrap();
+            let values = sizes.map(|size| mask(size) / 3);
+            let id = packing.join(values).unwrap();
+            assert_eq!(join(&sizes, &values).unwrap(), id);
+            assert_eq!(split(&sizes, id), packing.split(id).unwrap());
+            assert_eq!(values, packing.split(id).unwrap());
+        }
+    }
+
+    #[test]
+    fn packing_rejects_what_join_rejects() {
+        assert!(matches!(
+            Packing::new([64, 65]),
+            Err(Error::SizeExceeded { size: 129, .. })
+        ));
+        let packing = Packing::new([8, 8]).unwrap();
+        assert!(matches!(
+            packing.join([1, 256]),
+            Err(Error::InSegment { index: 1, .. })
+        ));
+        assert!(matches!(
+            packing.split(1 << 16),
+            Err(Error::ValueTooLarge { size: 16, .. })
+        ));
+    }
+
+    #[test]
+    fn generator_matches_spec() {
+        let since = Date::from_calendar_date(2023, Month::January, 1).unwrap();
+        let spec = Spec3::<i128, OffsetDateTime, i128, i128> {
+            segments: (
+                Box::new(TimestampSegment::new_with_utc_midnight(
+                    48,
+                    TimestampUnit::Milliseconds,
+                    since,
+                )),
+                Box::new(ConstantSegment::new(16, 7)),
+                Box::new(RandomSegment::new(64)),
+            ),
+        };
+        let generator = Generator::new(spec).unwrap();
+        assert_eq!(128, generator.packing().size());
+        assert!(generator
+            .log_configuration()
+            .starts_with("layout=timestamp:48:ms,constant:16,random:64 bits=128 epoch=2023-01-01"));
+        let id = generator.generate().unwrap();
+        let (timestamp, constant, random) = generator.decompose(id).unwrap();
+        assert!(timestamp > since.midnight().assume_utc());
+        assert_eq!(7, constant);
+        assert_eq!(
+            generator.spec().decompose(id).unwrap(),
+            (timestamp, constant, random)
+        );
+
+        let mut ids = [0; 4];
+        generator.generate_into(&mut ids).unwrap();
+        assert!(ids.iter().all(|id| id >> 64 & 0xffff == 7));
+        let mut bytes = [[0; 16]; 4];
+        generator.generate_bytes_into(&mut bytes).unwrap();
+        assert!(bytes.iter().all(|id| id[6..8] == [0, 7]));
+    }
+
+    #[test]
+    fn hooks_see_every_outcome() {
+        use alloc::sync::Arc;
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        // Generated, exhausted and failed attempts.
+        let counts: Arc<[AtomicUsize; 3]> = Arc::default();
+        let hook = {
+            let counts = Arc::clone(&counts);
+            move |event: &Event| {
+                let counter = match event {
+                    Event::Generated(id) => {
+                        assert_eq!(7, id >> 8);
+                        &counts[0]
+                    }
+                    Event::Exhausted { segment: 1 } => &counts[1],
+                    _ => &counts[2],
+                };
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+        };
+
+        let spec = |fill| Spec2::<i128, i128, i128> {
+            segments: (
+                Box::new(ConstantSegment::new(8, 7)),
+                Box::new(RandomSegment::with_rng(8, fill)),
+            ),
+        };
+        let generator = Generator::new(spec(|bytes| {
+            bytes.fill(1);
+            Ok(())
+        }))
+        .unwrap()
+        .with_hook(hook.clone());
+        let mut ids = [0; 3];
+        generator.generate_into(&mut ids).unwrap();
+        assert_eq!(3, counts[0].load(Ordering::Relaxed));
+
+        let generator = Generator::new(spec(|_| Err(Error::Exhausted)))
+            .unwrap()
+            .with_hook(hook);
+        assert!(generator.generate().is_err());
+        assert!(generator.generate_recorded(&|_: &Record<2>| ()).is_err());
+        assert_eq!(2, counts[1].load(Ordering::Relaxed));
+        assert_eq!(0, counts[2].load(Ordering::Relaxed));
+    }
+
+    #[cfg(all(feature = "std", target_has_atomic = "64"))]
+    #[test]
+    fn hooks_see_clock_regressions() {
+        use core::sync::atomic::{AtomicI64, Ordering};
+        use std::sync::Mutex;
+
+        static NOW: AtomicI64 = AtomicI64::new(100);
+        static LAST: AtomicI64 = AtomicI64::new(0);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let generator = Generator::new(Spec::<i128, OffsetDateTime> {
+            segment: Box::new(
+                TimestampSegment::new(32, TimestampUnit::Seconds, OffsetDateTime::UNIX_EPOCH)
+                    .with_clock(|| {
+                        OffsetDateTime::from_unix_timestamp(NOW.load(Ordering::Relaxed)).unwrap()
+                    })
+                    .monotonic(&LAST),
+            ),
+        })
+        .unwrap()
+        .with_hook({
+            let events = Arc::clone(&events);
+            move |event: &Event| events.lock().unwrap().push(format!("{:?}", event))
+        });
+        generator.generate().unwrap();
+        NOW.store(98, Ordering::Relaxed);
+        assert!(generator.generate().is_err());
+        assert_eq!(
+            vec![
+                "Generated(100)".to_string(),
+                format!(
+                    "{:?}",
+                    Event::ClockRegression {
+                        segment: 0,
+                        by: Duration::seconds(2)
+                    }
+                )
+            ],
+            *events.lock().unwrap()
+        );
+    }
+
+    #[cfg(all(feature = "std", target_has_atomic = "64"))]
+    #[test]
+    fn health_checks_clocks_and_rngs() {
+        let spec = |clock: fn() -> OffsetDateTime, fill| Spec2::<i128, OffsetDateTime, i128> {
+            segments: (
+                Box::new(
+                    TimestampSegment::new(16, TimestampUnit::Seconds, OffsetDateTime::UNIX_EPOCH)
+                        .with_clock(clock),
+                ),
+                Box::new(RandomSegment::with_rng(8, fill)),
+            ),
+        };
+        let health = Generator::new(spec(|| OffsetDateTime::UNIX_EPOCH, |_| Ok(())))
+            .unwrap()
+            .health();
+        assert!(health.clock_ok && health.entropy_ok);
+        assert!(health.exhausted_in.unwrap().is_negative());
+        assert!(!health.is_ok());
+
+        let before_epoch = || OffsetDateTime::UNIX_EPOCH - Duration::DAY;
+        let health = Generator::new(spec(before_epoch, |_| Err(Error::Exhausted)))
+            .unwrap()
+            .health();
+        assert_eq!((false, false), (health.clock_ok, health.entropy_ok));
+        assert_eq!((None, None), (health.lease_ok, health.sequence_utilization));
+
+        // Epochs off midnight have no config, but are still checked and count down.
+        let since = OffsetDateTime::UNIX_EPOCH + Duration::hours(6);
+        let spec = Spec::<i128, OffsetDateTime> {
+            segment: Box::new(
+                TimestampSegment::new(16, TimestampUnit::Seconds, since).with_clock(|| {
+                    OffsetDateTime::UNIX_EPOCH + Duration::hours(6) - Duration::SECOND
+                }),
+            ),
+        };
+        let sequence = Arc::new(Sequence::new(2));
+        sequence.next(5).unwrap();
+        let health = Generator::new(spec)
+            .unwrap()
+            .with_lease(|| false)
+            .with_sequence(Arc::clone(&sequence))
+            .health();
+        assert!(!health.clock_ok);
+        assert!(health.exhausted_in.is_some());
+        assert_eq!(
+            (Some(false), Some(25)),
+            (health.lease_ok, health.sequence_utilization)
+        );
+        assert!(!health.is_ok());
+    }
+}
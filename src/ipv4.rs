@@ -0,0 +1,75 @@
+use core::net::Ipv4Addr;
+
+use crate::{Error, SpecSegment};
+
+/// The 32 bits of the generating node's IPv4 address, so edge nodes without a coordination
+/// service embed where an ID came from instead of a configured node id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ipv4Segment {
+    address: Ipv4Addr,
+}
+
+impl Ipv4Segment {
+    pub const fn new(address: Ipv4Addr) -> Self {
+        Self { address }
+    }
+
+    pub fn address(&self) -> Ipv4Addr {
+        self.address
+    }
+}
+
+impl SpecSegment<i128, Ipv4Addr> for Ipv4Segment {
+    fn size(&self) -> u8 {
+        32
+    }
+
+    fn upper_bound(&self) -> Ipv4Addr {
+        Ipv4Addr::BROADCAST
+    }
+
+    fn encode(&self) -> Result<i128, Error> {
+        Ok(u32::from(self.address).into())
+    }
+
+    fn decode(&self, encoded: i128) -> Result<Ipv4Addr, Error> {
+        u32::try_from(encoded)
+            .map(Ipv4Addr::from)
+            .map_err(|_| Error::ValueTooLarge {
+                value: encoded as u128,
+                size: 32,
+            })
+    }
+
+    crate::clone_box!(i128, Ipv4Addr);
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+
+    use super::*;
+    use crate::{RandomSegment, Spec2};
+
+    #[test]
+    fn embeds_the_address() {
+        let address = Ipv4Addr::new(10, 0, 42, 7);
+        let spec = Spec2::<i128, Ipv4Addr, i128> {
+            segments: (
+                Box::new(Ipv4Segment::new(address)),
+                Box::new(RandomSegment::new(32)),
+            ),
+        };
+        let id = spec.generate().unwrap();
+        assert_eq!(0x0a00_2a07, id >> 32);
+        assert_eq!(address, spec.decompose(id).unwrap().0);
+        assert_eq!(
+            Ipv4Addr::BROADCAST,
+            spec.decompose(u64::MAX.into()).unwrap().0
+        );
+        assert!(matches!(
+            Ipv4Segment::new(address).decode(1 << 32),
+            Err(Error::ValueTooLarge { size: 32, .. })
+        ));
+    }
+}
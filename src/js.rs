@@ -0,0 +1,63 @@
+//! JavaScript bindings, so frontends mint and inspect IDs from the same JSON spec config as the
+//! backend. IDs cross the boundary as Crockford base32 strings, since JavaScript numbers can't
+//! hold them.
+
+use js_sys::{Array, Date};
+use wasm_bindgen::prelude::*;
+
+use crate::encoding::{decode_base32, encode_base32, encode_uuid};
+use crate::{DynamicSpec, Value};
+
+#[wasm_bindgen]
+pub struct UtidGenerator {
+    spec: DynamicSpec,
+}
+
+#[wasm_bindgen]
+impl UtidGenerator {
+    /// Builds a generator from a spec config in its JSON form.
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: &str) -> Result<UtidGenerator, JsError> {
+        Ok(UtidGenerator {
+            spec: DynamicSpec::from_json(config)?,
+        })
+    }
+
+    pub fn generate(&self) -> Result<String, JsError> {
+        Ok(encode_base32(self.spec.generate()?, 128))
+    }
+
+    /// One entry per segment: a `Date` for timestamps, a `BigInt` for everything else.
+    pub fn decompose(&self, id: &str) -> Result<Array, JsError> {
+        Ok(self
+            .spec
+            .decompose(decode_base32(id)?)?
+            .into_iter()
+            .map(|value| match value {
+                Value::Timestamp(timestamp) => {
+                    let millis = timestamp.unix_timestamp_nanos() as f64 / 1e6;
+                    JsValue::from(Date::new(&millis.into()))
+                }
+                Value::Integer(value) => JsValue::from(value),
+            })
+            .collect())
+    }
+}
+
+/// The ID as a decimal string, e.g. for `BigInt(toDecimal(id))`.
+#[wasm_bindgen(js_name = toDecimal)]
+pub fn to_decimal(id: &str) -> Result<String, JsError> {
+    Ok(decode_base32(id)?.to_string())
+}
+
+#[wasm_bindgen(js_name = fromDecimal)]
+pub fn from_decimal(decimal: &str) -> Result<String, JsError> {
+    let value: i128 = decimal.parse()?;
+    Ok(encode_base32(value, 128))
+}
+
+/// The ID in `uuid` text form, as Postgres prints it.
+#[wasm_bindgen(js_name = toUuid)]
+pub fn to_uuid(id: &str) -> Result<String, JsError> {
+    Ok(encode_uuid(decode_base32(id)?))
+}
@@ -0,0 +1,154 @@
+use alloc::boxed::Box;
+use core::fmt;
+
+use time::OffsetDateTime;
+
+use crate::{ClonedSegment, Error, SegmentConfig, SpecSegment};
+
+/// Wraps a segment with a human label, e.g. `tenant`, which diagrams, [`Layout::explain`],
+/// validation and generation errors and metrics use in place of the segment's index. Everything
+/// else is delegated to the wrapped segment.
+///
+/// [`Layout::explain`]: crate::Layout::explain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Labeled<S> {
+    label: &'static str,
+    segment: S,
+}
+
+impl<S> Labeled<S> {
+    pub const fn new(label: &'static str, segment: S) -> Self {
+        Self { label, segment }
+    }
+
+    pub const fn label(&self) -> &'static str {
+        self.label
+    }
+
+    pub fn segment(&self) -> &S {
+        &self.segment
+    }
+
+    pub fn into_inner(self) -> S {
+        self.segment
+    }
+}
+
+impl<T, R, S: SpecSegment<T, R>> SpecSegment<T, R> for Labeled<S> {
+    fn size(&self) -> u8 {
+        self.segment.size()
+    }
+
+    fn upper_bound(&self) -> R {
+        self.segment.upper_bound()
+    }
+
+    fn encode(&self) -> Result<T, Error> {
+        self.segment.encode()
+    }
+
+    fn decode(&self, encoded: T) -> Result<R, Error> {
+        self.segment.decode(encoded)
+    }
+
+    fn encode_with(&self, key: &[u8]) -> Result<T, Error> {
+        self.segment.encode_with(key)
+    }
+
+    fn verify(&self, encoded: T) -> Result<(), Error> {
+        self.segment.verify(encoded)
+    }
+
+    fn fixed(&self) -> Option<T> {
+        self.segment.fixed()
+    }
+
+    fn exhaustion_date(&self) -> Option<OffsetDateTime> {
+        self.segment.exhaustion_date()
+    }
+
+    fn config(&self) -> Option<SegmentConfig> {
+        self.segment.config()
+    }
+
+    fn keys_partition(&self) -> bool {
+        self.segment.keys_partition()
+    }
+
+    fn label(&self) -> Option<&str> {
+        Some(self.label)
+    }
+
+    fn clone_box<'a>(&self) -> Option<Box<dyn SpecSegment<T, R> + 'a>>
+    where
+        Self: 'a,
+        T: 'a,
+        R: 'a,
+    {
+        let segment = ClonedSegment(self.segment.clone_box()?);
+        Some(Box::new(Labeled::new(self.label, segment)))
+    }
+}
+
+impl<S: fmt::Display> fmt::Display for Labeled<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.label, self.segment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::{ConstantSegment, Generator, Layout, RandomSegment, Spec2, StaticSpec2};
+
+    fn spec() -> Spec2<i128, i128, i128> {
+        Spec2 {
+            segments: (
+                Box::new(Labeled::new("tenant", ConstantSegment::new(8, 7))),
+                Box::new(Labeled::new(
+                    "entropy",
+                    RandomSegment::with_rng(8, |_| Err(Error::Exhausted)),
+                )),
+            ),
+        }
+    }
+
+    #[test]
+    fn names_segments_in_descriptions() {
+        let spec = spec();
+        assert!(spec.to_string().contains("tenant (8)"));
+        assert!(spec
+            .describe_markdown()
+            .contains("| 0 | tenant: constant |"));
+        let explained = spec.explain(0x0800);
+        assert!(explained.contains("0  tenant: constant"));
+        assert!(explained.contains("! expected 7"));
+    }
+
+    #[test]
+    fn names_segments_in_errors() {
+        let error = spec().generate().unwrap_err();
+        assert_eq!(
+            "entropy segment: no values left to generate",
+            error.to_string()
+        );
+
+        let generator = Generator::new(spec()).unwrap();
+        let error = generator.generate().unwrap_err();
+        assert_eq!(Some("entropy".into()), error.details().label);
+
+        let spec = StaticSpec2 {
+            segments: (
+                Labeled::new("tenant", ConstantSegment::new(4, 300)),
+                RandomSegment::new(8),
+            ),
+        };
+        assert_eq!(
+            "tenant segment: value 300 does not fit in 4 bits",
+            spec.generate().unwrap_err().to_string()
+        );
+    }
+}
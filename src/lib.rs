@@ -1,28 +1,410 @@
-use std::fmt;
+#![cfg_attr(
+    all(feature = "strict", not(test)),
+    deny(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::unreachable,
+        clippy::todo,
+        clippy::unimplemented
+    )
+)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+#[cfg(feature = "std")]
+use rand::rngs::OsRng;
 use rand::Rng;
-use time::{Date, Duration, OffsetDateTime};
+use time::{Date, Duration, OffsetDateTime, Time, UtcOffset};
+
+#[cfg(feature = "actix")]
+pub mod actix;
+#[cfg(feature = "axum")]
+pub mod axum;
+#[cfg(feature = "bson")]
+mod bson_support;
+#[cfg(feature = "std")]
+mod capacity;
+#[cfg(feature = "chrono")]
+pub mod chrono;
+#[cfg(feature = "std")]
+pub mod clock;
+mod column;
+mod compat;
+mod config;
+mod datetime;
+mod derivation;
+mod describe;
+#[cfg(feature = "diesel")]
+mod diesel_support;
+pub mod encoding;
+#[cfg(feature = "fpe")]
+pub mod encryption;
+mod environment;
+mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "arbitrary")]
+mod fuzzing;
+mod generator;
+mod ipv4;
+#[cfg(all(feature = "js", target_arch = "wasm32", target_os = "unknown"))]
+pub mod js;
+mod labeled;
+#[cfg(feature = "std")]
+mod loader;
+mod lot;
+mod mac;
+#[cfg(feature = "metrics")]
+mod metrics_support;
+mod node;
+mod partition;
+pub mod presets;
+#[cfg(feature = "python")]
+mod python;
+mod redaction;
+mod region;
+mod registry;
+pub mod replay;
+#[cfg(feature = "rusqlite")]
+mod rusqlite_support;
+#[cfg(target_has_atomic = "64")]
+mod sequence;
+#[cfg(feature = "serde")]
+pub mod serde;
+mod shard;
+#[cfg(feature = "sqlx")]
+mod sqlx_support;
+mod static_spec;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+#[cfg(feature = "std")]
+pub mod system_time;
+mod tenant;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+#[cfg(feature = "tracing")]
+mod trace;
+mod type_tag;
+mod validation;
+mod verification;
+mod version;
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub mod wasm;
+
+#[cfg(feature = "std")]
+pub use capacity::{CapacityReport, CollisionEstimate, Limit, SAFE_COLLISION_PROBABILITY};
+pub use column::{UtidBigInt, UtidBytes};
+pub use compat::{Change, CompatReport, Compatibility};
+pub use config::{DynamicSpec, SegmentConfig, SpecConfig, Value};
+pub use datetime::{DateTimeType, TimestampSegmentAs};
+pub use derivation::NameHash;
+pub use environment::{Environment, EnvironmentSegment};
+pub use error::{Error, ErrorDetails};
+pub use generator::{Event, Generator, Health, Packing, SegmentTuple};
+pub use ipv4::Ipv4Segment;
+pub use labeled::Labeled;
+#[cfg(feature = "std")]
+pub use loader::ConfigError;
+pub use lot::{LotCode, LotSegment};
+pub use mac::MacSegment;
+pub use node::NodeSegment;
+pub use redaction::Redaction;
+pub use region::{Region, RegionSegment};
+pub use registry::SpecRegistry;
+#[cfg(target_has_atomic = "64")]
+pub use sequence::Sequence;
+pub use shard::ShardSegment;
+pub use static_spec::{StaticSpec2, StaticSpec3, StaticSpec4};
+pub use tenant::{TenantRegistry, TenantSegment};
+pub use type_tag::{TypeEntry, TypeTagSegment, TypeTags};
+pub use validation::SpecError;
+pub use verification::{Check, Verification};
+pub use version::{VersionDispatch, VersionSegment};
+
+#[cfg(feature = "fpe")]
+use encryption::{Cipher, Keyring};
 
 pub trait SpecSegment<T, R> {
     fn size(&self) -> u8;
+    /// The largest value the segment decodes to; for values without an order, such as names,
+    /// the one it encodes.
     fn upper_bound(&self) -> R;
     fn encode(&self) -> Result<T, Error>;
-    fn decode(&self, encoded: T) -> R;
+    fn decode(&self, encoded: T) -> Result<R, Error>;
+
+    /// Encodes for an ID generated for a caller's routing `key`, e.g. a user or account id.
+    /// Segments that don't depend on it encode as usual.
+    fn encode_with(&self, _key: &[u8]) -> Result<T, Error> {
+        self.encode()
+    }
+
+    /// Checks that an encoded value could have been produced by this segment.
+    fn verify(&self, _encoded: T) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// The value this segment always encodes to, if it never varies between IDs. Segments that
+    /// decode values other than their own, such as the tenant or node of any ID, are not fixed
+    /// even though every ID they generate carries the same value.
+    fn fixed(&self) -> Option<T> {
+        None
+    }
+
+    /// The last instant a time-based segment can encode, if it is one.
+    fn exhaustion_date(&self) -> Option<OffsetDateTime> {
+        None
+    }
+
+    /// Description to persist and rebuild the segment from, for the built-in segments.
+    fn config(&self) -> Option<SegmentConfig> {
+        None
+    }
+
+    /// Whether [`Layout::partition_key`] hashes this segment. By default only segments that
+    /// vary between IDs and are not time-based do, so shard and random bits spread the load.
+    fn keys_partition(&self) -> bool {
+        self.fixed().is_none() && self.exhaustion_date().is_none()
+    }
+
+    /// Human name of the segment, e.g. `tenant`, used instead of its index in diagrams, errors
+    /// and metrics. Attach one with [`Labeled`].
+    fn label(&self) -> Option<&str> {
+        None
+    }
+
+    /// A boxed copy of the segment, which specs holding segments as trait objects clone through;
+    /// see [`Spec::try_clone`]. `None` unless the segment overrides it, as the built-in ones do.
+    fn clone_box<'a>(&self) -> Option<Box<dyn SpecSegment<T, R> + 'a>>
+    where
+        Self: 'a,
+        T: 'a,
+        R: 'a,
+    {
+        None
+    }
+}
+
+/// Overrides [`SpecSegment::clone_box`] for a segment that is [`Clone`].
+macro_rules! clone_box {
+    ($t:ty, $r:ty) => {
+        fn clone_box<'a>(&self) -> Option<alloc::boxed::Box<dyn $crate::SpecSegment<$t, $r> + 'a>>
+        where
+            Self: 'a,
+        {
+            Some(alloc::boxed::Box::new(self.clone()))
+        }
+    };
+}
+
+pub(crate) use clone_box;
+
+/// The copy [`SpecSegment::clone_box`] returns, for wrappers such as [`Labeled`] to hold.
+pub(crate) struct ClonedSegment<'a, T, R>(pub(crate) Box<dyn SpecSegment<T, R> + 'a>);
+
+impl<T, R> SpecSegment<T, R> for ClonedSegment<'_, T, R> {
+    fn size(&self) -> u8 {
+        self.0.size()
+    }
+
+    fn upper_bound(&self) -> R {
+        self.0.upper_bound()
+    }
+
+    fn encode(&self) -> Result<T, Error> {
+        self.0.encode()
+    }
+
+    fn decode(&self, encoded: T) -> Result<R, Error> {
+        self.0.decode(encoded)
+    }
+
+    fn encode_with(&self, key: &[u8]) -> Result<T, Error> {
+        self.0.encode_with(key)
+    }
+
+    fn verify(&self, encoded: T) -> Result<(), Error> {
+        self.0.verify(encoded)
+    }
+
+    fn fixed(&self) -> Option<T> {
+        self.0.fixed()
+    }
+
+    fn exhaustion_date(&self) -> Option<OffsetDateTime> {
+        self.0.exhaustion_date()
+    }
+
+    fn config(&self) -> Option<SegmentConfig> {
+        self.0.config()
+    }
+
+    fn keys_partition(&self) -> bool {
+        self.0.keys_partition()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.0.label()
+    }
+
+    fn clone_box<'a>(&self) -> Option<Box<dyn SpecSegment<T, R> + 'a>>
+    where
+        Self: 'a,
+    {
+        self.0.clone_box()
+    }
+}
+
+/// Object-safe view of a segment, independent of what it decodes to, so a spec can walk all of
+/// its segments uniformly.
+pub trait Segment {
+    fn size(&self) -> u8;
+    fn encode(&self) -> Result<i128, Error>;
+    fn verify(&self, encoded: i128) -> Result<(), Error>;
+    fn fixed(&self) -> Option<i128>;
+    fn exhaustion_date(&self) -> Option<OffsetDateTime>;
+    fn config(&self) -> Option<SegmentConfig>;
+    fn keys_partition(&self) -> bool;
+    fn label(&self) -> Option<&str>;
+}
+
+impl<R> Segment for Box<dyn SpecSegment<i128, R>> {
+    fn size(&self) -> u8 {
+        self.as_ref().size()
+    }
+
+    fn encode(&self) -> Result<i128, Error> {
+        self.as_ref().encode()
+    }
+
+    fn verify(&self, encoded: i128) -> Result<(), Error> {
+        self.as_ref().verify(encoded)
+    }
+
+    fn fixed(&self) -> Option<i128> {
+        self.as_ref().fixed()
+    }
+
+    fn exhaustion_date(&self) -> Option<OffsetDateTime> {
+        self.as_ref().exhaustion_date()
+    }
+
+    fn config(&self) -> Option<SegmentConfig> {
+        self.as_ref().config()
+    }
+
+    fn keys_partition(&self) -> bool {
+        self.as_ref().keys_partition()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.as_ref().label()
+    }
 }
 
+#[derive(Debug, Clone)]
 pub struct TimestampSegment {
     size: u8,
     unit: TimestampUnit,
     since: OffsetDateTime,
+    // `since` split up once, so encoding and decoding skip the calendar arithmetic.
+    since_seconds: i64,
+    since_subsec: i32,
+    since_nanos: i128,
+    until: OffsetDateTime,
+    max_skew: Duration,
+    clock: Option<fn() -> OffsetDateTime>,
+    #[cfg(target_has_atomic = "64")]
+    last: Option<&'static core::sync::atomic::AtomicI64>,
 }
 
 impl TimestampSegment {
-    pub fn new_with_utc_midnight(size: u8, unit: TimestampUnit, since: Date) -> Self {
+    /// Counts from `since`, which need not be a midnight; only midnight epochs have a
+    /// [`SegmentConfig`], though.
+    pub const fn new(size: u8, unit: TimestampUnit, since: OffsetDateTime) -> Self {
         Self {
             size,
             unit,
-            since: since.midnight().assume_utc(),
+            since,
+            since_seconds: since.unix_timestamp(),
+            since_subsec: since.nanosecond() as i32,
+            since_nanos: since.unix_timestamp_nanos(),
+            until: until(size, unit, since.unix_timestamp_nanos()),
+            max_skew: Duration::MINUTE,
+            clock: None,
+            #[cfg(target_has_atomic = "64")]
+            last: None,
+        }
+    }
+
+    pub const fn new_with_utc_midnight(size: u8, unit: TimestampUnit, since: Date) -> Self {
+        Self::new(size, unit, since.midnight().assume_utc())
+    }
+
+    /// Reads the time from `clock` instead of the system clock, e.g. from an RTC on targets
+    /// without `std`, where a segment without a clock fails to encode.
+    pub const fn with_clock(mut self, clock: fn() -> OffsetDateTime) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    fn now(&self) -> Result<OffsetDateTime, Error> {
+        match self.clock {
+            Some(clock) => Ok(clock()),
+            None => now(),
+        }
+    }
+
+    /// How far in the future a timestamp may lie and still verify, to tolerate clock skew
+    /// between the generating and the verifying host. Defaults to one minute.
+    pub const fn with_max_skew(mut self, max_skew: Duration) -> Self {
+        self.max_skew = max_skew;
+        self
+    }
+
+    /// Fails with [`Error::ClockRegression`] rather than encode a timestamp earlier than the
+    /// latest one in `last`, which every encoding raises, so IDs stay ordered when the clock
+    /// steps back. Start it at 0 and share it between the segments whose IDs must stay ordered
+    /// together. Timestamps wider than 63 bits are not tracked.
+    #[cfg(target_has_atomic = "64")]
+    pub const fn monotonic(mut self, last: &'static core::sync::atomic::AtomicI64) -> Self {
+        self.last = Some(last);
+        self
+    }
+
+    /// The current timestamp, without updating [`monotonic`](Self::monotonic) state.
+    fn read(&self) -> Result<i128, Error> {
+        let now = self.now()?;
+        let duration = Duration::new(
+            now.unix_timestamp() - self.since_seconds,
+            now.nanosecond() as i32 - self.since_subsec,
+        );
+        if duration.is_negative() {
+            return Err(Error::EpochInFuture { by: -duration });
         }
+        Ok(self.unit.from_duration(duration))
+    }
+}
+
+/// The last instant a timestamp segment can encode, computed once when it is built.
+const fn until(size: u8, unit: TimestampUnit, since_nanos: i128) -> OffsetDateTime {
+    const LATEST: OffsetDateTime = Date::MAX.with_time(Time::MAX).assume_utc();
+    let Some(offset) = unit.to_nano(upper_bound(size)) else {
+        return LATEST;
+    };
+    let Some(nanos) = since_nanos.checked_add(offset) else {
+        return LATEST;
+    };
+    match OffsetDateTime::from_unix_timestamp_nanos(nanos) {
+        Ok(until) => until,
+        Err(_) => LATEST,
     }
 }
 
@@ -31,32 +413,93 @@ impl SpecSegment<i128, OffsetDateTime> for TimestampSegment {
         self.size
     }
 
+    /// Saturates at the latest representable date when the segment outlasts it.
     fn upper_bound(&self) -> OffsetDateTime {
-        let offset = if self.size == 128u8 {
-            i128::MAX
-        } else {
-            (1 << self.size) - 1
-        };
-        let duration = Duration::new(
-            i64::try_from(self.unit.to_nano(offset) / 1_000_000_000).unwrap(),
-            i32::try_from(self.unit.to_nano(offset) % 1_000_000_000).unwrap(),
-            // TODO cover overflow
-        );
-        self.since + duration
+        self.until
     }
 
     fn encode(&self) -> Result<i128, Error> {
-        let now = OffsetDateTime::now_utc();
-        let duration = now - self.since;
-        Ok(self.unit.from_nano(duration.whole_nanoseconds()))
+        let encoded = self.read()?;
+        #[cfg(target_has_atomic = "64")]
+        if let (Some(last), Ok(current)) = (&self.last, i64::try_from(encoded)) {
+            let last = last.fetch_max(current, core::sync::atomic::Ordering::Relaxed);
+            if last > current {
+                let nanos = self
+                    .unit
+                    .to_nano(i128::from(last) - i128::from(current))
+                    .unwrap_or(i128::MAX);
+                let by = Duration::new(
+                    (nanos / 1_000_000_000).try_into().unwrap_or(i64::MAX),
+                    (nanos % 1_000_000_000) as i32,
+                );
+                return Err(Error::ClockRegression { by });
+            }
+        }
+        Ok(encoded)
+    }
+
+    fn decode(&self, encoded: i128) -> Result<OffsetDateTime, Error> {
+        let nanos = self
+            .unit
+            .to_nano(encoded)
+            .and_then(|offset| self.since_nanos.checked_add(offset))
+            .ok_or(Error::Overflow)?;
+        Ok(OffsetDateTime::from_unix_timestamp_nanos(nanos)?)
+    }
+
+    fn verify(&self, encoded: i128) -> Result<(), Error> {
+        let latest = self
+            .read()?
+            .checked_add(self.unit.from_duration(self.max_skew))
+            .ok_or(Error::Overflow)?;
+        if encoded > latest {
+            return Err(Error::Verification {
+                reason: "timestamp lies in the future",
+            });
+        }
+        Ok(())
+    }
+
+    fn exhaustion_date(&self) -> Option<OffsetDateTime> {
+        Some(self.upper_bound())
     }
 
-    fn decode(&self, encoded: i128) -> OffsetDateTime {
-        let origin = self.unit.from_nano(self.since.unix_timestamp_nanos());
-        OffsetDateTime::from_unix_timestamp_nanos(origin + encoded).unwrap() // TODO Cover overflow
+    fn config(&self) -> Option<SegmentConfig> {
+        let since = self.since.to_offset(UtcOffset::UTC);
+        (since.time() == Time::MIDNIGHT).then_some(SegmentConfig::Timestamp {
+            size: self.size,
+            unit: self.unit,
+            epoch: since.date(),
+            max_skew: self.max_skew,
+        })
+    }
+
+    clone_box!(i128, OffsetDateTime);
+}
+
+/// Function pointers can't be compared reliably, so any two custom clocks compare equal.
+impl PartialEq for TimestampSegment {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size
+            && self.unit == other.unit
+            && self.since == other.since
+            && self.max_skew == other.max_skew
+            && self.clock.is_some() == other.clock.is_some()
+            && self.is_monotonic() == other.is_monotonic()
+    }
+}
+
+impl TimestampSegment {
+    fn is_monotonic(&self) -> bool {
+        #[cfg(target_has_atomic = "64")]
+        return self.last.is_some();
+        #[cfg(not(target_has_atomic = "64"))]
+        false
     }
 }
 
+impl Eq for TimestampSegment {}
+
 impl fmt::Display for TimestampSegment {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -68,14 +511,75 @@ impl fmt::Display for TimestampSegment {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RandomSegment {
     size: u8,
+    source: RandomSource,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RandomSource {
+    Thread,
+    Os,
+    Custom(fn(&mut [u8]) -> Result<(), Error>),
 }
 
 impl RandomSegment {
     // TODO Consider this public modifier is needed
-    pub fn new(size: u8) -> Self {
-        Self { size }
+    pub const fn new(size: u8) -> Self {
+        Self {
+            size,
+            source: RandomSource::Thread,
+        }
+    }
+
+    /// Draws every value straight from the operating system's CSPRNG instead of a thread-local
+    /// generator seeded from it.
+    pub const fn with_os_rng(size: u8) -> Self {
+        Self {
+            size,
+            source: RandomSource::Os,
+        }
+    }
+
+    /// Fills values from `fill`, e.g. a hardware RNG on targets without `std`, where the
+    /// thread-local and OS generators are unavailable. `fill` must be cryptographically secure
+    /// for the IDs to be unguessable.
+    pub const fn with_rng(size: u8, fill: fn(&mut [u8]) -> Result<(), Error>) -> Self {
+        Self {
+            size,
+            source: RandomSource::Custom(fill),
+        }
+    }
+
+    /// Fills `bytes` from the segment's source, for drawing many values at once.
+    pub(crate) fn fill(&self, bytes: &mut [u8]) -> Result<(), Error> {
+        match self.source {
+            #[cfg(feature = "std")]
+            RandomSource::Thread => rand::thread_rng().fill(bytes),
+            #[cfg(feature = "std")]
+            RandomSource::Os => OsRng.fill(bytes),
+            #[cfg(not(feature = "std"))]
+            RandomSource::Thread | RandomSource::Os => {
+                return Err(Error::Unsupported {
+                    reason: "no random number generator without std",
+                })
+            }
+            RandomSource::Custom(fill) => fill(bytes)?,
+        }
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    fn draw(&self, rng: &mut impl Rng) -> i128 {
+        if self.size == 128u8 {
+            // upper_bound stops at i128::MAX, which would leave the sign bit always clear
+            rng.gen()
+        } else if self.size <= 64 {
+            i128::from(rng.gen_range(0..=mask64(self.size)))
+        } else {
+            rng.gen_range(0..=self.upper_bound())
+        }
     }
 }
 
@@ -85,32 +589,95 @@ impl SpecSegment<i128, i128> for RandomSegment {
     }
 
     fn upper_bound(&self) -> i128 {
-        if self.size == 128u8 {
-            i128::MAX
-        } else {
-            (1 << self.size) - 1
-        }
+        upper_bound(self.size)
     }
 
     fn encode(&self) -> Result<i128, Error> {
-        let mut rng = rand::thread_rng();
-        Ok(rng.gen_range(0..=self.upper_bound()))
+        match self.source {
+            #[cfg(feature = "std")]
+            RandomSource::Thread => Ok(self.draw(&mut rand::thread_rng())),
+            #[cfg(feature = "std")]
+            RandomSource::Os => Ok(self.draw(&mut OsRng)),
+            #[cfg(not(feature = "std"))]
+            RandomSource::Thread | RandomSource::Os => Err(Error::Unsupported {
+                reason: "no random number generator without std",
+            }),
+            RandomSource::Custom(fill) => {
+                let mut bytes = [0; 16];
+                fill(&mut bytes)?;
+                Ok((u128::from_be_bytes(bytes) & mask(self.size)) as i128)
+            }
+        }
+    }
+
+    fn decode(&self, encoded: i128) -> Result<i128, Error> {
+        Ok(encoded)
+    }
+
+    fn config(&self) -> Option<SegmentConfig> {
+        Some(SegmentConfig::Random {
+            size: self.size,
+            os_rng: matches!(self.source, RandomSource::Os),
+        })
+    }
+
+    clone_box!(i128, i128);
+}
+
+/// Function pointers can't be compared reliably, so any two custom RNGs compare equal.
+impl PartialEq for RandomSource {
+    fn eq(&self, other: &Self) -> bool {
+        core::mem::discriminant(self) == core::mem::discriminant(other)
     }
+}
+
+impl Eq for RandomSource {}
 
-    fn decode(&self, encoded: i128) -> i128 {
-        encoded
+impl fmt::Display for RandomSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rng = match self.source {
+            RandomSource::Thread => "thread",
+            RandomSource::Os => "os",
+            RandomSource::Custom(_) => "custom",
+        };
+        write!(f, "RandomSegment(bits:{}, rng:{})", self.size, rng)
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ConstantSegment<T> {
     size: u8,
     value: T,
 }
 
 impl ConstantSegment<i128> {
-    pub fn new(size: u8, value: i128) -> Self {
+    /// Generating fails with [`Error::ValueTooLarge`] if `value` doesn't fit in `size` bits;
+    /// use [`ConstantSegment::try_new`] to catch that when the spec is built.
+    pub const fn new(size: u8, value: i128) -> Self {
         Self { size, value }
     }
+
+    pub fn try_new(size: u8, value: i128) -> Result<Self, Error> {
+        let segment = Self { size, value };
+        segment.check()?;
+        Ok(segment)
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        if self.size > 128 {
+            return Err(Error::SizeExceeded {
+                size: u32::from(self.size),
+                max: 128,
+            });
+        }
+        if self.value as u128 & !mask(self.size) != 0 {
+            return Err(Error::ValueTooLarge {
+                value: self.value as u128,
+                size: self.size,
+            });
+        }
+        Ok(())
+    }
 }
 
 impl SpecSegment<i128, i128> for ConstantSegment<i128> {
@@ -119,28 +686,141 @@ impl SpecSegment<i128, i128> for ConstantSegment<i128> {
     }
 
     fn upper_bound(&self) -> i128 {
-        if self.size == 128u8 {
-            i128::MAX
-        } else {
-            (1 << self.size) - 1
-        }
+        upper_bound(self.size)
     }
 
     fn encode(&self) -> Result<i128, Error> {
+        self.check()?;
         Ok(self.value)
     }
 
-    fn decode(&self, encoded: i128) -> i128 {
-        encoded
+    fn decode(&self, encoded: i128) -> Result<i128, Error> {
+        Ok(encoded)
+    }
+
+    fn verify(&self, encoded: i128) -> Result<(), Error> {
+        if encoded != self.value {
+            return Err(Error::Verification {
+                reason: "constant does not match",
+            });
+        }
+        Ok(())
+    }
+
+    fn fixed(&self) -> Option<i128> {
+        Some(self.value)
+    }
+
+    fn config(&self) -> Option<SegmentConfig> {
+        Some(SegmentConfig::Constant {
+            size: self.size,
+            value: self.value,
+        })
     }
+
+    clone_box!(i128, i128);
 }
 
-#[derive(Debug)]
-pub enum Error {
-    OverflowError,
+impl fmt::Display for ConstantSegment<i128> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ConstantSegment(bits:{}, value:{})",
+            self.size, self.value
+        )
+    }
 }
 
-#[derive(Debug)]
+/// A generated ID. Displays, parses and (with the `serde` feature) serializes as Crockford
+/// base32, since many consumers, JSON ones in particular, can't hold 128-bit integers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow),
+    diesel(sql_type = diesel::sql_types::Uuid)
+)]
+pub struct Utid(pub i128);
+
+/// Conversions for protobuf and other formats without 128-bit integers. Both forms are
+/// big-endian, so they round-trip losslessly and sort like the unsigned ID: `bytes` fields
+/// compare bytewise, `(hi, lo)` pairs compare as tuples.
+impl Utid {
+    /// The 16 bytes for a `bytes` field, most significant first.
+    pub fn to_bytes(self) -> [u8; 16] {
+        encoding::uuid_bytes(self.0)
+    }
+
+    /// Inverse of [`Utid::to_bytes`]; fails unless given exactly 16 bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        encoding::from_uuid_bytes(bytes).map(Utid)
+    }
+
+    /// The upper and lower 64 bits, for a pair of `fixed64` fields.
+    pub fn to_u64_pair(self) -> (u64, u64) {
+        let value = self.0 as u128;
+        ((value >> 64) as u64, value as u64)
+    }
+
+    /// Inverse of [`Utid::to_u64_pair`].
+    pub fn from_u64_pair(hi: u64, lo: u64) -> Self {
+        Utid(((u128::from(hi) << 64) | u128::from(lo)) as i128)
+    }
+}
+
+impl From<i128> for Utid {
+    fn from(id: i128) -> Self {
+        Utid(id)
+    }
+}
+
+impl From<Utid> for i128 {
+    fn from(id: Utid) -> Self {
+        id.0
+    }
+}
+
+/// For `BIGINT` columns and other 64-bit sinks; fails for IDs outside the `i64` range.
+impl TryFrom<Utid> for i64 {
+    type Error = Error;
+
+    fn try_from(id: Utid) -> Result<Self, Self::Error> {
+        i64::try_from(id.0).map_err(|_| Error::ValueTooLarge {
+            value: id.0 as u128,
+            size: 63,
+        })
+    }
+}
+
+impl fmt::Display for Utid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&encoding::encode_base32(self.0, 128))
+    }
+}
+
+impl core::str::FromStr for Utid {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        encoding::decode_base32(s).map(Utid)
+    }
+}
+
+/// Compares two secret-bearing IDs (e.g. `&id.to_be_bytes()` or their encoded strings) in time
+/// that depends only on their length, so token checks don't leak how many leading bytes matched.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    core::hint::black_box(diff) == 0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(::serde::Serialize, ::serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum TimestampUnit {
     Seconds,
     Milliseconds,
@@ -149,7 +829,8 @@ pub enum TimestampUnit {
 }
 
 impl TimestampUnit {
-    fn from_nano(&self, nanos: i128) -> i128 {
+    #[allow(clippy::wrong_self_convention)]
+    fn from_nano(self, nanos: i128) -> i128 {
         match self {
             TimestampUnit::Seconds => nanos / 1_000_000_000,
             TimestampUnit::Milliseconds => nanos / 1_000_000,
@@ -158,12 +839,29 @@ impl TimestampUnit {
         }
     }
 
-    fn to_nano(&self, value: i128) -> i128 {
+    /// Same as `from_nano(duration.whole_nanoseconds())`, but in 64-bit arithmetic unless the
+    /// duration is out of its range, as 128-bit division is emulated on 32-bit targets.
+    #[allow(clippy::wrong_self_convention)]
+    fn from_duration(self, duration: Duration) -> i128 {
+        let (scale, divisor) = match self {
+            TimestampUnit::Seconds => (1, 1_000_000_000),
+            TimestampUnit::Milliseconds => (1_000, 1_000_000),
+            TimestampUnit::Microseconds => (1_000_000, 1_000),
+            TimestampUnit::Nanoseconds => (1_000_000_000, 1),
+        };
+        duration
+            .whole_seconds()
+            .checked_mul(scale)
+            .and_then(|value| value.checked_add(i64::from(duration.subsec_nanoseconds()) / divisor))
+            .map_or_else(|| self.from_nano(duration.whole_nanoseconds()), i128::from)
+    }
+
+    const fn to_nano(self, value: i128) -> Option<i128> {
         match self {
-            TimestampUnit::Seconds => value * 1_000_000_000,
-            TimestampUnit::Milliseconds => value * 1_000_000,
-            TimestampUnit::Microseconds => value * 1_000,
-            TimestampUnit::Nanoseconds => value,
+            TimestampUnit::Seconds => value.checked_mul(1_000_000_000),
+            TimestampUnit::Milliseconds => value.checked_mul(1_000_000),
+            TimestampUnit::Microseconds => value.checked_mul(1_000),
+            TimestampUnit::Nanoseconds => Some(value),
         }
     }
 }
@@ -172,9 +870,11 @@ pub struct Spec<T, R> {
     // TODO Check if removing pub modifier is possible
     pub segment: Box<dyn SpecSegment<T, R>>,
 }
+#[allow(clippy::type_complexity)]
 pub struct Spec2<T, R1, R2> {
     pub segments: (Box<dyn SpecSegment<T, R1>>, Box<dyn SpecSegment<T, R2>>),
 }
+#[allow(clippy::type_complexity)]
 pub struct Spec3<T, R1, R2, R3> {
     pub segments: (
         Box<dyn SpecSegment<T, R1>>,
@@ -182,6 +882,7 @@ pub struct Spec3<T, R1, R2, R3> {
         Box<dyn SpecSegment<T, R3>>,
     ),
 }
+#[allow(clippy::type_complexity)]
 pub struct Spec4<T, R1, R2, R3, R4> {
     pub segments: (
         Box<dyn SpecSegment<T, R1>>,
@@ -191,89 +892,559 @@ pub struct Spec4<T, R1, R2, R3, R4> {
     ),
 }
 
+/// Bit layout shared by every spec, from the most significant segment to the least.
+pub trait Layout {
+    fn segments(&self) -> Vec<&dyn Segment>;
+
+    fn sizes(&self) -> Vec<u8> {
+        self.segments()
+            .iter()
+            .map(|segment| segment.size())
+            .collect()
+    }
+
+    fn size(&self) -> u8 {
+        self.sizes()
+            .iter()
+            .fold(0u8, |acc, size| acc.saturating_add(*size))
+    }
+
+    /// Encrypts a generated ID within the spec's bit width, hiding its segments.
+    #[cfg(feature = "fpe")]
+    fn encrypt(&self, generated: i128, cipher: &Cipher) -> Result<i128, Error> {
+        cipher.encrypt(generated, self.size())
+    }
+
+    #[cfg(feature = "fpe")]
+    fn decrypt(&self, encrypted: i128, cipher: &Cipher) -> Result<i128, Error> {
+        cipher.decrypt(encrypted, self.size())
+    }
+
+    /// Encrypts with the keyring's current key, writing its key id into the first segment,
+    /// which must be exactly as wide as the keyring's key ids.
+    #[cfg(feature = "fpe")]
+    fn encrypt_with(&self, generated: i128, keyring: &Keyring) -> Result<i128, Error> {
+        key_segment(&self.sizes(), keyring)?;
+        keyring.encrypt(generated, self.size())
+    }
+
+    #[cfg(feature = "fpe")]
+    fn decrypt_with(&self, encrypted: i128, keyring: &Keyring) -> Result<i128, Error> {
+        key_segment(&self.sizes(), keyring)?;
+        keyring.decrypt(encrypted, self.size())
+    }
+
+    /// Encodes each segment as one Sqids number, so existing Sqids consumers in other languages
+    /// decode the segment values directly. Segments must be at most 64 bits wide.
+    #[cfg(feature = "sqids")]
+    fn encode_sqids(&self, id: i128, sqids: &encoding::Sqids) -> Result<String, Error> {
+        encoding::encode_sqids(&self.sizes(), id, sqids)
+    }
+
+    #[cfg(feature = "sqids")]
+    fn decode_sqids(&self, encoded: &str, sqids: &encoding::Sqids) -> Result<i128, Error> {
+        encoding::decode_sqids(&self.sizes(), encoded, sqids)
+    }
+
+    /// The last instant the spec's timestamp bits can encode, taking the earliest one if there
+    /// are several timestamp segments. Timestamp segments compute theirs when built.
+    fn timestamp_exhaustion_date(&self) -> Option<OffsetDateTime> {
+        self.segments()
+            .iter()
+            .filter_map(|segment| segment.exhaustion_date())
+            .min()
+    }
+
+    /// Time left until [`Layout::timestamp_exhaustion_date`]; negative once it has passed.
+    #[cfg(feature = "std")]
+    fn remaining_lifetime(&self) -> Option<Duration> {
+        self.timestamp_exhaustion_date()
+            .map(|date| date - OffsetDateTime::now_utc())
+    }
+
+    /// Description of every segment, if all of them are built-in ones.
+    fn config(&self) -> Option<SpecConfig> {
+        let segments = self
+            .segments()
+            .iter()
+            .map(|segment| segment.config())
+            .collect::<Option<Vec<SegmentConfig>>>()?;
+        Some(SpecConfig { segments })
+    }
+
+    /// Birthday-bound collision estimate for `ids_per_tick` IDs generated in each tick of the
+    /// timestamp, with the highest safe rate, for sizing the random segments. Only built-in
+    /// random segments count as random bits.
+    #[cfg(feature = "std")]
+    fn collision_probability(&self, ids_per_tick: f64) -> CollisionEstimate {
+        capacity::collision_probability(&self.segments(), ids_per_tick)
+    }
+
+    /// Timestamp lifetime and collision risk at `rate` IDs per second, with whichever limits
+    /// the layout first, for dashboards and capacity planning.
+    #[cfg(feature = "std")]
+    fn capacity_report(&self, rate: f64) -> CapacityReport {
+        capacity::capacity_report(&self.segments(), rate)
+    }
+
+    /// Compares this layout with the one replacing it; see [`SpecConfig::compat`]. `None` if
+    /// either has segments without a [`SegmentConfig`].
+    fn compat(&self, other: &dyn Layout) -> Option<Result<CompatReport, Vec<SpecError>>> {
+        Some(self.config()?.compat(&other.config()?))
+    }
+
+    /// Markdown table of the layout with one freshly encoded example value per segment, to paste
+    /// into design docs and runbooks.
+    fn describe_markdown(&self) -> String {
+        describe::markdown(&self.segments())
+    }
+
+    /// Multi-line breakdown of `id` for log messages and support tooling: its bits grouped by
+    /// segment, each segment decoded, and flags such as timestamps in the future or constants
+    /// that don't match.
+    fn explain(&self, id: i128) -> String {
+        describe::explain(&self.segments(), id, now().ok())
+    }
+
+    /// The effective layout, epochs, node ids, RNG kinds and fingerprint as one logfmt line, for
+    /// startup logs incident responders can check a deployment's layout against.
+    fn configuration(&self) -> String {
+        describe::configuration(&self.segments())
+    }
+
+    fn describe_html(&self) -> String {
+        describe::html(&self.segments())
+    }
+
+    /// The bit diagram and the table of [`Layout::describe_markdown`] as a standalone SVG image.
+    fn describe_svg(&self) -> String {
+        describe::svg(&self.segments())
+    }
+
+    /// Checks the layout itself, reporting every violation rather than the first.
+    fn validate(&self) -> Result<(), Vec<SpecError>> {
+        validation::validate(&self.segments())
+    }
+
+    /// Runs every structural and per-segment check against `id` and reports each outcome.
+    fn verify(&self, id: i128) -> Verification {
+        verification::verify(&self.segments(), id)
+    }
+
+    /// [`verify`](Self::verify) for an ID in its base32 form, reporting a malformed input as a
+    /// failed [`Check::Encoding`] rather than an error.
+    fn verify_str(&self, encoded: &str) -> Verification {
+        verification::verify_str(&self.segments(), encoded)
+    }
+
+    /// Derives an ID from a namespace and a name, UUIDv5-style: the same pair always yields the
+    /// same ID. Constant segments keep their value and time segments hold their epoch; every
+    /// other bit comes from the hash.
+    fn derive(&self, hash: NameHash, namespace: i128, name: &[u8]) -> Result<i128, Error> {
+        derivation::derive(&self.segments(), hash, namespace, name)
+    }
+
+    /// Idempotency key for a request: retries carrying the same `payload` within the same
+    /// `window`-wide time bucket map to the same ID. Buckets are aligned to the Unix epoch.
+    fn idempotency_key(&self, payload: &[u8], window: Duration) -> Result<i128, Error> {
+        derivation::idempotency_key(&self.segments(), payload, window, now()?)
+    }
+
+    /// Produces a pseudonymous copy of `id` with one [`Redaction`] applied per segment.
+    fn redact(&self, id: i128, redactions: &[Redaction], salt: &[u8]) -> Result<i128, Error> {
+        redaction::redact(&self.sizes(), id, redactions, salt)
+    }
+
+    /// Stable partition in `0..partitions` for `id`, e.g. for a Kafka record key. Only segments
+    /// that [key partitions](SpecSegment::keys_partition) are hashed, so IDs generated in the same
+    /// instant still spread over all partitions.
+    fn partition_key(&self, id: i128, partitions: u32) -> Result<u32, Error> {
+        partition::partition_key(&self.segments(), id, partitions)
+    }
+}
+
+#[cfg(feature = "std")]
+fn now() -> Result<OffsetDateTime, Error> {
+    Ok(OffsetDateTime::now_utc())
+}
+
+#[cfg(not(feature = "std"))]
+fn now() -> Result<OffsetDateTime, Error> {
+    Err(Error::Unsupported {
+        reason: "no system clock without std",
+    })
+}
+
+#[cfg(feature = "fpe")]
+fn key_segment(sizes: &[u8], keyring: &Keyring) -> Result<(), Error> {
+    let actual = sizes.first().copied().unwrap_or(0);
+    if actual != keyring.key_bits() {
+        return Err(Error::SizeMismatch {
+            expected: u32::from(keyring.key_bits()),
+            actual: u32::from(actual),
+        });
+    }
+    Ok(())
+}
+
+pub(crate) const fn mask(size: u8) -> u128 {
+    if size >= 128 {
+        u128::MAX
+    } else {
+        (1u128 << size) - 1
+    }
+}
+
+/// Largest non-negative value a segment of `size` bits holds; 128-bit segments stop at
+/// `i128::MAX` even though their encoded values may use the sign bit.
+pub(crate) const fn upper_bound(size: u8) -> i128 {
+    if size >= 127 {
+        i128::MAX
+    } else {
+        mask(size) as i128
+    }
+}
+
+const fn mask64(size: u8) -> u64 {
+    if size >= 64 {
+        u64::MAX
+    } else {
+        (1u64 << size) - 1
+    }
+}
+
+fn total_size(sizes: &[u8]) -> u32 {
+    sizes.iter().map(|size| u32::from(*size)).sum()
+}
+
+/// Splits an ID into its raw segment values, most significant segment first.
+pub(crate) fn split(sizes: &[u8], id: i128) -> Vec<u128> {
+    let mut values = vec![0; sizes.len()];
+    split_into(sizes, id, &mut values);
+    values
+}
+
+/// [`split`] writing to `values`, which must hold one value per segment, without allocating.
+pub(crate) fn split_into(sizes: &[u8], id: i128, values: &mut [u128]) {
+    if total_size(sizes) <= 64 {
+        return split64(sizes, id as u64, values);
+    }
+    let id = id as u128;
+    let mut shift = 0u32;
+    for (size, value) in sizes.iter().zip(values).rev() {
+        *value = id.checked_shr(shift).unwrap_or(0) & mask(*size);
+        shift += u32::from(*size);
+    }
+}
+
+/// [`split_into`] for layouts of up to 64 bits, where 64-bit shifts and masks suffice. On
+/// 32-bit targets these are a couple of instructions rather than emulated 128-bit operations.
+fn split64(sizes: &[u8], id: u64, values: &mut [u128]) {
+    let mut shift = 0u32;
+    for (size, value) in sizes.iter().zip(values).rev() {
+        *value = u128::from(id.checked_shr(shift).unwrap_or(0) & mask64(*size));
+        shift += u32::from(*size);
+    }
+}
+
+/// Like [`split`], but rejects IDs with bits set above the segments' total width.
+pub(crate) fn unpack(sizes: &[u8], id: i128) -> Result<Vec<u128>, Error> {
+    let mut values = vec![0; sizes.len()];
+    unpack_into(sizes, id, &mut values)?;
+    Ok(values)
+}
+
+pub(crate) fn unpack_into(sizes: &[u8], id: i128, values: &mut [u128]) -> Result<(), Error> {
+    let total = total_size(sizes);
+    if total < 128 && (id as u128) >> total != 0 {
+        return Err(Error::ValueTooLarge {
+            value: id as u128,
+            size: total as u8,
+        });
+    }
+    split_into(sizes, id, values);
+    Ok(())
+}
+
+/// Packs raw segment values back into an ID, rejecting values wider than their segment.
+pub(crate) fn join(sizes: &[u8], values: &[u128]) -> Result<i128, Error> {
+    if sizes.len() != values.len() {
+        return Err(Error::SegmentCountMismatch {
+            expected: sizes.len(),
+            actual: values.len(),
+        });
+    }
+    let total = total_size(sizes);
+    if total > 128 {
+        return Err(Error::SizeExceeded {
+            size: total,
+            max: 128,
+        });
+    }
+    if total <= 64 {
+        return join64(sizes, values);
+    }
+    let mut id = 0u128;
+    let mut shift = 0u32;
+    for (index, (size, value)) in sizes.iter().zip(values).enumerate().rev() {
+        if *value & !mask(*size) != 0 {
+            return Err(Error::ValueTooLarge {
+                value: *value,
+                size: *size,
+            }
+            .in_segment(index));
+        }
+        id |= value.checked_shl(shift).unwrap_or(0);
+        shift += u32::from(*size);
+    }
+    Ok(id as i128)
+}
+
+/// [`join`] for layouts of up to 64 bits; see [`split64`].
+fn join64(sizes: &[u8], values: &[u128]) -> Result<i128, Error> {
+    let mut id = 0u64;
+    let mut shift = 0u32;
+    for (index, (size, value)) in sizes.iter().zip(values).enumerate().rev() {
+        let narrow = u64::try_from(*value)
+            .ok()
+            .filter(|narrow| narrow & !mask64(*size) == 0)
+            .ok_or(Error::ValueTooLarge {
+                value: *value,
+                size: *size,
+            })
+            .map_err(|e| e.in_segment(index))?;
+        id |= narrow.checked_shl(shift).unwrap_or(0);
+        shift += u32::from(*size);
+    }
+    Ok(i128::from(id))
+}
+
+impl<R> Layout for Spec<i128, R> {
+    fn segments(&self) -> Vec<&dyn Segment> {
+        vec![&self.segment]
+    }
+}
+
+impl<R1, R2> Layout for Spec2<i128, R1, R2> {
+    fn segments(&self) -> Vec<&dyn Segment> {
+        vec![&self.segments.0, &self.segments.1]
+    }
+}
+
+impl<R1, R2, R3> Layout for Spec3<i128, R1, R2, R3> {
+    fn segments(&self) -> Vec<&dyn Segment> {
+        vec![&self.segments.0, &self.segments.1, &self.segments.2]
+    }
+}
+
+impl<R1, R2, R3, R4> Layout for Spec4<i128, R1, R2, R3, R4> {
+    fn segments(&self) -> Vec<&dyn Segment> {
+        vec![
+            &self.segments.0,
+            &self.segments.1,
+            &self.segments.2,
+            &self.segments.3,
+        ]
+    }
+}
+
+/// Whether two layouts have the same segments, as far as segments tell: their sizes, configs,
+/// fixed values, exhaustion dates and labels.
+fn same_segments(a: &[&dyn Segment], b: &[&dyn Segment]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(a, b)| {
+            a.size() == b.size()
+                && a.config() == b.config()
+                && a.fixed() == b.fixed()
+                && a.exhaustion_date() == b.exhaustion_date()
+                && a.label() == b.label()
+        })
+}
+
+impl<T: 'static, R: 'static> Spec<T, R> {
+    /// A copy of the spec, if its segment supports [`SpecSegment::clone_box`].
+    pub fn try_clone(&self) -> Option<Self> {
+        Some(Self {
+            segment: self.segment.clone_box()?,
+        })
+    }
+}
+
+macro_rules! impl_try_clone {
+    ($spec:ident<$($r:ident: $index:tt),+>) => {
+        impl<T: 'static, $($r: 'static),+> $spec<T, $($r),+> {
+            /// A copy of the spec, if all its segments support [`SpecSegment::clone_box`].
+            pub fn try_clone(&self) -> Option<Self> {
+                Some(Self {
+                    segments: ($(self.segments.$index.clone_box()?,)+),
+                })
+            }
+        }
+    };
+}
+
+impl_try_clone!(Spec2<R1: 0, R2: 1>);
+impl_try_clone!(Spec3<R1: 0, R2: 1, R3: 2>);
+impl_try_clone!(Spec4<R1: 0, R2: 1, R3: 2, R4: 3>);
+
+macro_rules! impl_diagram {
+    ($spec:ident<$($r:ident),+>) => {
+        impl<$($r),+> fmt::Display for $spec<i128, $($r),+> {
+            /// Summarizes the layout in one line, then renders it as an RFC-style bit diagram.
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let segments = self.segments();
+                writeln!(f, "{}", describe::summary(&segments))?;
+                f.write_str(&describe::diagram(&segments))
+            }
+        }
+
+        impl<$($r),+> fmt::Debug for $spec<i128, $($r),+> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($spec))
+                    .field("sizes", &self.sizes())
+                    .finish()
+            }
+        }
+
+        /// Segments are trait objects, so specs compare by layout rather than by segment values.
+        impl<$($r),+> PartialEq for $spec<i128, $($r),+> {
+            fn eq(&self, other: &Self) -> bool {
+                same_segments(&self.segments(), &other.segments())
+            }
+        }
+    };
+}
+
+impl_diagram!(Spec<R>);
+impl_diagram!(Spec2<R1, R2>);
+impl_diagram!(Spec3<R1, R2, R3>);
+impl_diagram!(Spec4<R1, R2, R3, R4>);
+
 impl<R> Spec<i128, R> {
+    /// Builds the spec only if its layout is valid; see [`Layout::validate`].
+    pub fn try_new(segment: Box<dyn SpecSegment<i128, R>>) -> Result<Self, Vec<SpecError>> {
+        let spec = Self { segment };
+        spec.validate()?;
+        Ok(spec)
+    }
+
     pub fn generate(&self) -> Result<i128, Error> {
-        self.segment.encode()
+        Ok(self.encode_segments()?[0] as i128)
+    }
+
+    /// [`generate`](Self::generate) for the routing `key`; see [`SpecSegment::encode_with`].
+    pub fn generate_with(&self, key: &[u8]) -> Result<i128, Error> {
+        Ok(self.encode_segments_with(key)?[0] as i128)
     }
 
     pub fn decompose(&self, generated: i128) -> Result<R, Error> {
-        Ok(self.segment.decode(generated))
+        unpack(&[self.segment.size()], generated)?;
+        self.decode_segments([generated as u128])
     }
 }
 
 impl<R1, R2> Spec2<i128, R1, R2> {
+    pub fn try_new(
+        first: Box<dyn SpecSegment<i128, R1>>,
+        second: Box<dyn SpecSegment<i128, R2>>,
+    ) -> Result<Self, Vec<SpecError>> {
+        let spec = Self {
+            segments: (first, second),
+        };
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Lays out the segments anew on every call; wrap the spec in a [`Generator`] to compute
+    /// its [`Packing`] once for hot paths.
     pub fn generate(&self) -> Result<i128, Error> {
-        let mut result = self.segments.1.encode()?;
-        result |= self.segments.0.encode()? << self.segments.1.size();
-        Ok(result)
+        Packing::new(self.segment_sizes())?
+            .join(self.encode_segments()?)
+            .map_err(|e| e.with_label(|index| self.segment_label(index)))
     }
 
-    pub fn decompose(&self, generated: i128) -> Result<(R1, R2), Error> {
-        let second = ((1i128 << self.segments.1.size()) - 1) & generated;
-        let second = self.segments.1.decode(second);
+    /// [`generate`](Self::generate) for the routing `key`; see [`SpecSegment::encode_with`].
+    pub fn generate_with(&self, key: &[u8]) -> Result<i128, Error> {
+        Packing::new(self.segment_sizes())?
+            .join(self.encode_segments_with(key)?)
+            .map_err(|e| e.with_label(|index| self.segment_label(index)))
+    }
 
-        let first = ((generated as u128) >> self.segments.1.size()) as i128;
-        let first = self.segments.0.decode(first);
-        Ok((first, second))
+    /// Like [`generate`](Self::generate), lays out the segments on every call.
+    pub fn decompose(&self, generated: i128) -> Result<(R1, R2), Error> {
+        let values = Packing::new(self.segment_sizes())?.split(generated)?;
+        self.decode_segments(values)
     }
 }
 
 impl<R1, R2, R3> Spec3<i128, R1, R2, R3> {
+    pub fn try_new(
+        first: Box<dyn SpecSegment<i128, R1>>,
+        second: Box<dyn SpecSegment<i128, R2>>,
+        third: Box<dyn SpecSegment<i128, R3>>,
+    ) -> Result<Self, Vec<SpecError>> {
+        let spec = Self {
+            segments: (first, second, third),
+        };
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Lays out the segments anew on every call; wrap the spec in a [`Generator`] to compute
+    /// its [`Packing`] once for hot paths.
     pub fn generate(&self) -> Result<i128, Error> {
-        let mut result = self.segments.2.encode()?;
-        let mut shift = self.segments.2.size();
-        result |= self.segments.1.encode()? << shift;
-        shift += self.segments.1.size();
+        Packing::new(self.segment_sizes())?
+            .join(self.encode_segments()?)
+            .map_err(|e| e.with_label(|index| self.segment_label(index)))
+    }
 
-        result |= self.segments.0.encode()? << shift;
-        Ok(result)
+    /// [`generate`](Self::generate) for the routing `key`; see [`SpecSegment::encode_with`].
+    pub fn generate_with(&self, key: &[u8]) -> Result<i128, Error> {
+        Packing::new(self.segment_sizes())?
+            .join(self.encode_segments_with(key)?)
+            .map_err(|e| e.with_label(|index| self.segment_label(index)))
     }
 
+    /// Like [`generate`](Self::generate), lays out the segments on every call.
     pub fn decompose(&self, generated: i128) -> Result<(R1, R2, R3), Error> {
-        let third = ((1i128 << self.segments.2.size()) - 1) & generated;
-        let third = self.segments.2.decode(third);
-        let mut shift = self.segments.2.size();
-        
-        let second = (((1i128 << (self.segments.1.size() + shift)) - 1) & generated) >> shift;
-        let second = self.segments.1.decode(second);
-        shift += self.segments.1.size();
-
-        let first = generated >> shift;
-        let first = self.segments.0.decode(first);
-        Ok((first, second, third))
+        let values = Packing::new(self.segment_sizes())?.split(generated)?;
+        self.decode_segments(values)
     }
 }
 
 impl<R1, R2, R3, R4> Spec4<i128, R1, R2, R3, R4> {
-    pub fn generate(&self) -> Result<i128, Error> {
-        let mut result = self.segments.3.encode()?;
-        let mut shift = self.segments.3.size();
-        result |= self.segments.2.encode()? << shift;
-        shift += self.segments.2.size();
+    pub fn try_new(
+        first: Box<dyn SpecSegment<i128, R1>>,
+        second: Box<dyn SpecSegment<i128, R2>>,
+        third: Box<dyn SpecSegment<i128, R3>>,
+        fourth: Box<dyn SpecSegment<i128, R4>>,
+    ) -> Result<Self, Vec<SpecError>> {
+        let spec = Self {
+            segments: (first, second, third, fourth),
+        };
+        spec.validate()?;
+        Ok(spec)
+    }
 
-        result |= self.segments.1.encode()? << shift;
-        shift += self.segments.1.size();
+    /// Lays out the segments anew on every call; wrap the spec in a [`Generator`] to compute
+    /// its [`Packing`] once for hot paths.
+    pub fn generate(&self) -> Result<i128, Error> {
+        Packing::new(self.segment_sizes())?
+            .join(self.encode_segments()?)
+            .map_err(|e| e.with_label(|index| self.segment_label(index)))
+    }
 
-        result |= self.segments.0.encode()? << shift;
-        Ok(result)
+    /// [`generate`](Self::generate) for the routing `key`; see [`SpecSegment::encode_with`].
+    pub fn generate_with(&self, key: &[u8]) -> Result<i128, Error> {
+        Packing::new(self.segment_sizes())?
+            .join(self.encode_segments_with(key)?)
+            .map_err(|e| e.with_label(|index| self.segment_label(index)))
     }
 
+    /// Like [`generate`](Self::generate), lays out the segments on every call.
     pub fn decompose(&self, generated: i128) -> Result<(R1, R2, R3, R4), Error> {
-        let fourth = ((1i128 << self.segments.3.size()) - 1) & generated;
-        let fourth = self.segments.3.decode(fourth);
-        let mut shift = self.segments.3.size();
-
-        let third = (((1i128 << (self.segments.2.size() + shift)) - 1) & generated) >> shift;
-        let third = self.segments.2.decode(third);
-        shift += self.segments.2.size();
-
-        let second = (((1i128 << (self.segments.1.size() + shift)) - 1) & generated) >> shift;
-        let second = self.segments.1.decode(second);
-        shift += self.segments.1.size();
-
-        let first = generated >> shift;
-        let first = self.segments.0.decode(first);
-        Ok((first, second, third, fourth))
+        let values = Packing::new(self.segment_sizes())?.split(generated)?;
+        self.decode_segments(values)
     }
 }
 
@@ -324,6 +1495,149 @@ mod tests {
         println!("Full bits of timestamp: {} ({})", generated, timestamp);
     }
 
+    #[test]
+    fn epoch_in_future() {
+        let tomorrow = OffsetDateTime::now_utc().date().next_day().unwrap();
+        let spec = Spec {
+            segment: Box::new(TimestampSegment::new_with_utc_midnight(
+                48,
+                TimestampUnit::Milliseconds,
+                tomorrow,
+            )),
+        };
+        assert!(matches!(
+            spec.generate(),
+            Err(Error::InSegment { index: 0, source, .. })
+                if matches!(*source, Error::EpochInFuture { by } if by.is_positive())
+        ));
+    }
+
+    #[test]
+    fn epochs_off_the_second() {
+        fn at(hour: u8, second: u8, milli: u16, offset: i8) -> OffsetDateTime {
+            Date::from_calendar_date(2023, time::Month::May, 17)
+                .unwrap()
+                .with_hms_milli(hour, 0, second, milli)
+                .unwrap()
+                .assume_offset(UtcOffset::from_hms(offset, 0, 0).unwrap())
+        }
+        fn clock() -> OffsetDateTime {
+            at(12, 1, 100, 2)
+        }
+        let segment = TimestampSegment::new(48, TimestampUnit::Milliseconds, at(10, 0, 900, 0))
+            .with_clock(clock);
+        assert_eq!(200, segment.encode().unwrap());
+        assert_eq!(clock(), segment.decode(200).unwrap());
+
+        let segment = TimestampSegment::new(48, TimestampUnit::Milliseconds, at(10, 1, 300, 0))
+            .with_clock(clock);
+        assert!(matches!(
+            segment.encode(),
+            Err(Error::EpochInFuture { by }) if by == Duration::milliseconds(200)
+        ));
+    }
+
+    #[test]
+    fn upper_bound_is_the_last_decodable_instant() {
+        let since = Date::from_calendar_date(2023, time::Month::January, 1).unwrap();
+        let units = [
+            TimestampUnit::Seconds,
+            TimestampUnit::Milliseconds,
+            TimestampUnit::Microseconds,
+            TimestampUnit::Nanoseconds,
+        ];
+        for unit in units {
+            for size in [1, 32, 41, 48, 64] {
+                let segment = TimestampSegment::new_with_utc_midnight(size, unit, since);
+                if let Ok(last) = segment.decode(upper_bound(size)) {
+                    assert_eq!(last, segment.upper_bound());
+                }
+            }
+        }
+    }
+
+    #[cfg(target_has_atomic = "64")]
+    #[test]
+    fn monotonic_timestamps_reject_regressions() {
+        use core::sync::atomic::{AtomicI64, Ordering};
+
+        static NOW: AtomicI64 = AtomicI64::new(100);
+        static LAST: AtomicI64 = AtomicI64::new(0);
+        let clock = || OffsetDateTime::from_unix_timestamp(NOW.load(Ordering::Relaxed)).unwrap();
+        let segment = TimestampSegment::new(32, TimestampUnit::Seconds, OffsetDateTime::UNIX_EPOCH)
+            .with_clock(clock)
+            .monotonic(&LAST);
+        assert_eq!(100, segment.encode().unwrap());
+        NOW.store(97, Ordering::Relaxed);
+        assert!(matches!(
+            segment.clone().encode(),
+            Err(Error::ClockRegression { by }) if by == Duration::seconds(3)
+        ));
+        assert!(segment.verify(97).is_ok());
+        NOW.store(101, Ordering::Relaxed);
+        assert_eq!(101, segment.encode().unwrap());
+        assert_ne!(
+            segment,
+            TimestampSegment::new(32, TimestampUnit::Seconds, OffsetDateTime::UNIX_EPOCH)
+                .with_clock(clock)
+        );
+    }
+
+    #[test]
+    fn upper_bound_saturates() {
+        let since = Date::from_calendar_date(2023, time::Month::January, 1).unwrap();
+        let latest = Date::MAX.with_time(Time::MAX).assume_utc();
+
+        let segment = TimestampSegment::new_with_utc_midnight(32, TimestampUnit::Seconds, since);
+        assert_eq!(
+            since.midnight().assume_utc() + Duration::seconds(u32::MAX.into()),
+            segment.upper_bound()
+        );
+        let segment =
+            TimestampSegment::new_with_utc_midnight(64, TimestampUnit::Nanoseconds, since);
+        assert!(segment.upper_bound() < latest);
+        for size in [80, 100, 127, 128] {
+            for unit in [
+                TimestampUnit::Seconds,
+                TimestampUnit::Milliseconds,
+                TimestampUnit::Microseconds,
+                TimestampUnit::Nanoseconds,
+            ] {
+                let segment = TimestampSegment::new_with_utc_midnight(size, unit, since);
+                assert_eq!(latest, segment.upper_bound());
+            }
+        }
+    }
+
+    #[test]
+    fn timestamp_exhaustion() {
+        let since = Date::from_calendar_date(2023, time::Month::January, 1).unwrap();
+        let spec = Spec3 {
+            segments: (
+                Box::new(TimestampSegment::new_with_utc_midnight(
+                    32,
+                    TimestampUnit::Seconds,
+                    since,
+                )),
+                Box::new(TimestampSegment::new_with_utc_midnight(
+                    41,
+                    TimestampUnit::Milliseconds,
+                    since,
+                )),
+                Box::new(RandomSegment::new(16)),
+            ),
+        };
+        let exhaustion = spec.timestamp_exhaustion_date().unwrap();
+        assert_eq!(2092, exhaustion.year());
+        assert!(spec.remaining_lifetime().unwrap() > Duration::days(365 * 60));
+
+        let spec = Spec {
+            segment: Box::new(RandomSegment::new(64)),
+        };
+        assert_eq!(None, spec.timestamp_exhaustion_date());
+        assert_eq!(None, spec.remaining_lifetime());
+    }
+
     #[test]
     fn segment_display() {
         let segment = TimestampSegment::new_with_utc_midnight(
@@ -334,6 +1648,31 @@ mod tests {
         println!("timestamp segment: {}", segment);
     }
 
+    #[test]
+    fn random_and_constant_display() {
+        assert_eq!(
+            "RandomSegment(bits:16, rng:thread)",
+            RandomSegment::new(16).to_string()
+        );
+        assert_eq!(
+            "RandomSegment(bits:64, rng:os)",
+            RandomSegment::with_os_rng(64).to_string()
+        );
+        assert_eq!(
+            "ConstantSegment(bits:8, value:3)",
+            ConstantSegment::new(8, 3).to_string()
+        );
+        let spec = Spec2 {
+            segments: (
+                Box::new(ConstantSegment::new(8, 3)),
+                Box::new(Labeled::new("entropy", RandomSegment::new(56))),
+            ),
+        };
+        assert!(spec
+            .to_string()
+            .starts_with("64 bits: constant 8 = 3, entropy: random 56\n"));
+    }
+
     #[test]
     fn constant_and_random() {
         let spec = Spec2 {
@@ -346,4 +1685,292 @@ mod tests {
         let (constant, random) = spec.decompose(generated).unwrap();
         println!("Constant: {}, random: {}", constant, random);
     }
+
+    #[test]
+    fn constant_must_fit_its_width() {
+        assert!(matches!(
+            ConstantSegment::try_new(8, 44444),
+            Err(Error::ValueTooLarge {
+                value: 44444,
+                size: 8
+            })
+        ));
+        assert!(ConstantSegment::try_new(8, -1).is_err());
+        assert!(ConstantSegment::try_new(128, -1).is_ok());
+
+        let spec = Spec2 {
+            segments: (
+                Box::new(ConstantSegment::new(8, 44444)),
+                Box::new(RandomSegment::new(56)),
+            ),
+        };
+        assert!(spec.generate().is_err());
+    }
+
+    #[test]
+    fn upper_bounds_at_width_boundaries() {
+        assert_eq!(u64::MAX as i128, RandomSegment::new(64).upper_bound());
+        assert_eq!(i128::MAX, RandomSegment::new(127).upper_bound());
+        assert_eq!(i128::MAX, RandomSegment::new(128).upper_bound());
+        assert_eq!(0, ConstantSegment::new(0, 0).upper_bound());
+        assert_eq!(1, ConstantSegment::new(1, 0).upper_bound());
+    }
+
+    #[test]
+    fn pack_at_width_boundaries() {
+        for (first, second) in [(1u8, 127u8), (64, 64), (127, 1), (0, 128), (128, 0)] {
+            let first_max = upper_bound(first);
+            let second_max = mask(second) as i128;
+            let spec = Spec2 {
+                segments: (
+                    Box::new(ConstantSegment::new(first, first_max)),
+                    Box::new(ConstantSegment::new(second, second_max)),
+                ),
+            };
+            let generated = spec.generate().unwrap();
+            assert_eq!((first_max, second_max), spec.decompose(generated).unwrap());
+        }
+
+        let spec = Spec3 {
+            segments: (
+                Box::new(ConstantSegment::new(1, 1)),
+                Box::new(RandomSegment::new(127)),
+                Box::new(ConstantSegment::new(0, 0)),
+            ),
+        };
+        let generated = spec.generate().unwrap();
+        assert!(generated < 0);
+        assert_eq!(1, spec.decompose(generated).unwrap().0);
+
+        let spec = Spec2 {
+            segments: (
+                Box::new(RandomSegment::new(64)),
+                Box::new(RandomSegment::new(65)),
+            ),
+        };
+        assert!(matches!(
+            spec.generate(),
+            Err(Error::SizeExceeded { size: 129, .. })
+        ));
+    }
+
+    #[test]
+    fn decompose_never_panics() {
+        let since = Date::from_calendar_date(2023, time::Month::January, 1).unwrap();
+        let timestamp =
+            |size, unit| Box::new(TimestampSegment::new_with_utc_midnight(size, unit, since));
+        let specs = [
+            Spec2 {
+                segments: (
+                    timestamp(48, TimestampUnit::Milliseconds),
+                    Box::new(RandomSegment::new(80)),
+                ),
+            },
+            Spec2 {
+                segments: (
+                    timestamp(100, TimestampUnit::Seconds),
+                    Box::new(RandomSegment::new(28)),
+                ),
+            },
+            Spec2 {
+                segments: (
+                    timestamp(127, TimestampUnit::Nanoseconds),
+                    Box::new(RandomSegment::new(1)),
+                ),
+            },
+            Spec2 {
+                segments: (
+                    timestamp(32, TimestampUnit::Seconds),
+                    Box::new(RandomSegment::new(32)),
+                ),
+            },
+        ];
+        let mut rng = rand::thread_rng();
+        let inputs = [0, 1, -1, i128::MIN, i128::MAX, 1 << 64, u64::MAX as i128]
+            .into_iter()
+            .chain((0..10_000).map(|_| rng.gen::<i128>()));
+        for input in inputs {
+            for spec in &specs {
+                let _ = spec.decompose(input);
+            }
+        }
+
+        let spec = &specs[3];
+        assert!(matches!(
+            spec.decompose(1 << 64),
+            Err(Error::ValueTooLarge { size: 64, .. })
+        ));
+        let error = specs[1].decompose(-1).err().unwrap();
+        assert_eq!("overflow", error.code());
+        assert_eq!(Some(0), error.details().segment);
+    }
+
+    #[test]
+    fn constant_time_comparison() {
+        let token = 0x0123_4567_89ab_cdef_i128;
+        assert!(constant_time_eq(&token.to_be_bytes(), &token.to_be_bytes()));
+        assert!(!constant_time_eq(
+            &token.to_be_bytes(),
+            &(token ^ 1).to_be_bytes()
+        ));
+        assert!(!constant_time_eq(b"abc", b"abcd"));
+    }
+
+    #[test]
+    fn narrow_layouts_match_wide_arithmetic() {
+        let mut rng = rand::thread_rng();
+        for sizes in [
+            &[41u8, 10, 12][..],
+            &[32, 32],
+            &[1, 63],
+            &[64],
+            &[8, 8, 8, 8],
+        ] {
+            for _ in 0..1000 {
+                let id = rng.gen::<i128>();
+                let wide: Vec<u128> = {
+                    let mut shift = 0u32;
+                    let mut values: Vec<u128> = sizes
+                        .iter()
+                        .rev()
+                        .map(|size| {
+                            let value = (id as u128 >> shift) & mask(*size);
+                            shift += u32::from(*size);
+                            value
+                        })
+                        .collect();
+                    values.reverse();
+                    values
+                };
+                assert_eq!(wide, split(sizes, id));
+                let total = sizes.iter().map(|size| *size as u32).sum::<u32>() as u8;
+                assert_eq!(
+                    (id as u128 & mask(total)) as i128,
+                    join(sizes, &wide).unwrap()
+                );
+            }
+        }
+        assert!(matches!(
+            join(&[32, 32], &[1 << 32, 0]),
+            Err(Error::InSegment { index: 0, .. })
+        ));
+        assert!(join(&[64], &[1 << 64]).is_err());
+    }
+
+    #[test]
+    fn duration_conversion_matches_nanoseconds() {
+        let units = [
+            TimestampUnit::Seconds,
+            TimestampUnit::Milliseconds,
+            TimestampUnit::Microseconds,
+            TimestampUnit::Nanoseconds,
+        ];
+        let durations = [
+            Duration::ZERO,
+            Duration::new(1_700_000_000, 123_456_789),
+            Duration::new(-5, -999_999_999),
+            Duration::MAX,
+            Duration::MIN,
+        ];
+        for unit in units {
+            for duration in durations {
+                assert_eq!(
+                    unit.from_nano(duration.whole_nanoseconds()),
+                    unit.from_duration(duration)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn injected_clock_and_rng() {
+        fn clock() -> OffsetDateTime {
+            Date::from_calendar_date(2023, time::Month::January, 2)
+                .unwrap()
+                .midnight()
+                .assume_utc()
+        }
+        fn fill(bytes: &mut [u8]) -> Result<(), Error> {
+            bytes.fill(0xab);
+            Ok(())
+        }
+
+        let since = Date::from_calendar_date(2023, time::Month::January, 1).unwrap();
+        let spec = Spec2 {
+            segments: (
+                Box::new(
+                    TimestampSegment::new_with_utc_midnight(32, TimestampUnit::Seconds, since)
+                        .with_clock(clock),
+                ),
+                Box::new(RandomSegment::with_rng(12, fill)),
+            ),
+        };
+        assert_eq!((86_400 << 12) | 0xbab, spec.generate().unwrap());
+    }
+
+    #[test]
+    fn clone_and_compare_specs() {
+        let since = Date::from_calendar_date(2023, time::Month::January, 1).unwrap();
+        let spec: Spec3<i128, OffsetDateTime, i128, i128> = Spec3 {
+            segments: (
+                Box::new(TimestampSegment::new_with_utc_midnight(
+                    40,
+                    TimestampUnit::Seconds,
+                    since,
+                )),
+                Box::new(Labeled::new("tenant", ConstantSegment::new(8, 3))),
+                Box::new(RandomSegment::new(16)),
+            ),
+        };
+        let cloned = spec.try_clone().unwrap();
+        assert_eq!(spec, cloned);
+        assert_eq!(spec.segments.1.encode().unwrap(), 3);
+        assert_eq!("Spec3 { sizes: [40, 8, 16] }", format!("{:?}", cloned));
+
+        let mut other = spec.try_clone().unwrap();
+        other.segments.1 = Box::new(ConstantSegment::new(8, 3));
+        assert_ne!(spec, other);
+        other.segments.1 = Box::new(Labeled::new("tenant", ConstantSegment::new(8, 4)));
+        assert_ne!(spec, other);
+
+        struct Opaque;
+        impl SpecSegment<i128, i128> for Opaque {
+            fn size(&self) -> u8 {
+                8
+            }
+            fn upper_bound(&self) -> i128 {
+                255
+            }
+            fn encode(&self) -> Result<i128, Error> {
+                Ok(0)
+            }
+            fn decode(&self, encoded: i128) -> Result<i128, Error> {
+                Ok(encoded)
+            }
+        }
+        other.segments.1 = Box::new(Opaque);
+        assert!(other.try_clone().is_none());
+
+        assert_eq!(RandomSegment::new(16), RandomSegment::new(16));
+        assert_ne!(RandomSegment::new(16), RandomSegment::with_os_rng(16));
+        assert_ne!(
+            TimestampSegment::new_with_utc_midnight(40, TimestampUnit::Seconds, since),
+            TimestampSegment::new_with_utc_midnight(40, TimestampUnit::Seconds, since)
+                .with_clock(OffsetDateTime::now_utc),
+        );
+    }
+
+    #[test]
+    fn utid_bytes_and_u64_pairs() {
+        let ids = [0, 1, -1, i128::MIN, i128::MAX, 1 << 64, u64::MAX as i128].map(Utid);
+        for id in ids {
+            assert_eq!(id, Utid::from_bytes(&id.to_bytes()).unwrap());
+            let (hi, lo) = id.to_u64_pair();
+            assert_eq!(id, Utid::from_u64_pair(hi, lo));
+        }
+        assert_eq!((1, 0), Utid(1 << 64).to_u64_pair());
+        assert_eq!((u64::MAX, u64::MAX), Utid(-1).to_u64_pair());
+        assert!(Utid(-1).to_u64_pair() > Utid(i128::MAX).to_u64_pair());
+        assert!(Utid::from_bytes(&[0; 15]).is_err());
+    }
 }
@@ -0,0 +1,290 @@
+use std::fmt;
+#[cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+use std::path::Path;
+
+use time::{Date, Duration, Month};
+
+use crate::{DynamicSpec, SegmentConfig, SpecConfig, SpecError, TimestampUnit};
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ConfigError {
+    Io(std::io::Error),
+    /// The file extension names no format enabled in this build.
+    UnknownFormat(String),
+    /// The parser's message, which points at the offending line where the format allows.
+    Parse(String),
+    /// The environment variable the config needs, e.g. `APP_ID_NODE_ID`, is not set.
+    MissingVar(String),
+    /// Every layout violation, keyed by the config path it was found at, e.g. `segments[1].size`.
+    Invalid(Vec<(String, SpecError)>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(error) => write!(f, "cannot read spec config: {}", error),
+            ConfigError::UnknownFormat(extension) => {
+                write!(f, "unsupported spec config format {:?}", extension)
+            }
+            ConfigError::Parse(message) => write!(f, "malformed spec config: {}", message),
+            ConfigError::MissingVar(name) => write!(f, "spec config variable {} is not set", name),
+            ConfigError::Invalid(errors) => {
+                f.write_str("invalid spec config")?;
+                for (key, error) in errors {
+                    write!(f, "\n  {}: {}", key, error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+fn key(error: &SpecError) -> String {
+    match error {
+        SpecError::TooWide { .. } => "segments".to_string(),
+        SpecError::ZeroWidth { segment, .. } | SpecError::SegmentTooWide { segment, .. } => {
+            format!("segments[{}].size", segment)
+        }
+        SpecError::ConstantTooLarge { segment, .. } => format!("segments[{}].value", segment),
+    }
+}
+
+fn build(config: SpecConfig) -> Result<DynamicSpec, ConfigError> {
+    config.build().map_err(|errors| {
+        ConfigError::Invalid(
+            errors
+                .into_iter()
+                .map(|error| (key(&error), error))
+                .collect(),
+        )
+    })
+}
+
+impl DynamicSpec {
+    #[cfg(feature = "json")]
+    pub fn from_json(config: &str) -> Result<Self, ConfigError> {
+        build(serde_json::from_str(config).map_err(|e| ConfigError::Parse(e.to_string()))?)
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn from_toml(config: &str) -> Result<Self, ConfigError> {
+        build(toml::from_str(config).map_err(|e| ConfigError::Parse(e.to_string()))?)
+    }
+
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(config: &str) -> Result<Self, ConfigError> {
+        build(serde_yaml::from_str(config).map_err(|e| ConfigError::Parse(e.to_string()))?)
+    }
+
+    /// Reads a spec config file, picking the format from its extension.
+    #[cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let config = std::fs::read_to_string(path).map_err(ConfigError::Io)?;
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default();
+        match extension {
+            #[cfg(feature = "json")]
+            "json" => Self::from_json(&config),
+            #[cfg(feature = "toml")]
+            "toml" => Self::from_toml(&config),
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => Self::from_yaml(&config),
+            _ => Err(ConfigError::UnknownFormat(extension.to_string())),
+        }
+    }
+
+    /// Builds a spec from environment variables, so deployments can adjust it per environment:
+    ///
+    /// - `{prefix}_LAYOUT`: comma-separated segments, most significant first, each one of
+    ///   `timestamp:<size>:<s|ms|us|ns>`, `node:<size>`, `random:<size>` or
+    ///   `constant:<size>:<value>`, e.g. `timestamp:41:ms,node:10,random:12`.
+    /// - `{prefix}_EPOCH`: `YYYY-MM-DD` date timestamps count from, required with a timestamp.
+    /// - `{prefix}_NODE_ID`: value of the `node` segment, required with one.
+    pub fn from_env(prefix: &str) -> Result<Self, ConfigError> {
+        from_vars(prefix, |name| std::env::var(name).ok())
+    }
+}
+
+fn from_vars(
+    prefix: &str,
+    var: impl Fn(&str) -> Option<String>,
+) -> Result<DynamicSpec, ConfigError> {
+    let name = |suffix: &str| format!("{}_{}", prefix, suffix);
+    let required =
+        |suffix: &str| var(&name(suffix)).ok_or_else(|| ConfigError::MissingVar(name(suffix)));
+    let invalid = |suffix: &str, message: String| {
+        ConfigError::Parse(format!("{}: {}", name(suffix), message))
+    };
+
+    let layout = required("LAYOUT")?;
+    let mut segments = Vec::new();
+    for item in layout.split(',').map(str::trim) {
+        let parts: Vec<&str> = item.split(':').collect();
+        let size = parts
+            .get(1)
+            .and_then(|size| size.parse::<u8>().ok())
+            .ok_or_else(|| invalid("LAYOUT", format!("missing or bad size in {:?}", item)))?;
+        let segment = match (parts[0], parts.len()) {
+            ("timestamp", 3) => SegmentConfig::Timestamp {
+                size,
+                unit: parse_unit(parts[2])
+                    .ok_or_else(|| invalid("LAYOUT", format!("unknown unit in {:?}", item)))?,
+                epoch: {
+                    let epoch = required("EPOCH")?;
+                    parse_date(&epoch)
+                        .ok_or_else(|| invalid("EPOCH", format!("{:?} is not YYYY-MM-DD", epoch)))?
+                },
+                max_skew: Duration::MINUTE,
+            },
+            ("node", 2) => {
+                let node = required("NODE_ID")?;
+                SegmentConfig::Constant {
+                    size,
+                    value: node
+                        .parse()
+                        .map_err(|_| invalid("NODE_ID", format!("{:?} is not a number", node)))?,
+                }
+            }
+            ("random", 2) => SegmentConfig::Random {
+                size,
+                os_rng: false,
+            },
+            ("constant", 3) => SegmentConfig::Constant {
+                size,
+                value: parts[2]
+                    .parse()
+                    .map_err(|_| invalid("LAYOUT", format!("bad value in {:?}", item)))?,
+            },
+            _ => return Err(invalid("LAYOUT", format!("unknown segment {:?}", item))),
+        };
+        segments.push(segment);
+    }
+    build(SpecConfig { segments })
+}
+
+fn parse_unit(unit: &str) -> Option<TimestampUnit> {
+    match unit {
+        "s" => Some(TimestampUnit::Seconds),
+        "ms" => Some(TimestampUnit::Milliseconds),
+        "us" => Some(TimestampUnit::Microseconds),
+        "ns" => Some(TimestampUnit::Nanoseconds),
+        _ => None,
+    }
+}
+
+fn parse_date(date: &str) -> Option<Date> {
+    let mut parts = date.splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = Month::try_from(parts.next()?.parse::<u8>().ok()?).ok()?;
+    let day = parts.next()?.parse().ok()?;
+    Date::from_calendar_date(year, month, day).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Layout;
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn load_toml() {
+        let spec = DynamicSpec::from_toml(
+            r#"
+            [[segments]]
+            kind = "timestamp"
+            size = 48
+            unit = "milliseconds"
+            epoch = "2023-01-01"
+
+            [[segments]]
+            kind = "random"
+            size = 80
+            "#,
+        )
+        .unwrap();
+        assert_eq!(2, spec.decompose(spec.generate().unwrap()).unwrap().len());
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn load_yaml() {
+        let spec = DynamicSpec::from_yaml(
+            "segments:\n  - kind: constant\n    size: 8\n    value: 3\n  - kind: random\n    size: 56\n",
+        )
+        .unwrap();
+        assert_eq!(3, spec.generate().unwrap() >> 56);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn errors_reference_config_keys() {
+        let error = DynamicSpec::from_json(
+            r#"{"segments":[{"kind":"constant","size":8,"value":300},{"kind":"random","size":121}]}"#,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            "invalid spec config\n  segments: segments add up to 129 bits, more than 128\n  \
+             segments[0].value: constant 300 of segment 0 does not fit in 8 bits",
+            error.to_string()
+        );
+        assert!(matches!(
+            DynamicSpec::from_json(r#"{"segments":[{"kind":"uuid"}]}"#),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn from_environment() {
+        let vars = |name: &str| match name {
+            "APP_ID_LAYOUT" => Some("timestamp:41:ms, node:10, random:12".to_string()),
+            "APP_ID_EPOCH" => Some("2020-01-01".to_string()),
+            "APP_ID_NODE_ID" => Some("513".to_string()),
+            _ => None,
+        };
+        let spec = from_vars("APP_ID", vars).unwrap();
+        assert_eq!(vec![41, 10, 12], spec.sizes());
+        assert_eq!(513, (spec.generate().unwrap() >> 12) & 0x3ff);
+
+        let error = from_vars("APP_ID", |name| {
+            (name == "APP_ID_LAYOUT").then(|| "node:10".to_string())
+        })
+        .err()
+        .unwrap();
+        assert!(matches!(&error, ConfigError::MissingVar(name) if name == "APP_ID_NODE_ID"));
+        assert_eq!(
+            "spec config variable APP_ID_NODE_ID is not set",
+            error.to_string()
+        );
+        assert!(matches!(
+            DynamicSpec::from_env("UTID_UNSET_PREFIX"),
+            Err(ConfigError::MissingVar(_))
+        ));
+    }
+
+    #[cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+    #[test]
+    fn unknown_extension() {
+        let path = std::env::temp_dir().join(format!(
+            "utid-loader-{}-unknown-extension.ini",
+            std::process::id()
+        ));
+        std::fs::write(&path, "").unwrap();
+        let result = DynamicSpec::from_path(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(ConfigError::UnknownFormat(_))));
+    }
+}
@@ -0,0 +1,175 @@
+use core::fmt;
+
+use time::Date;
+
+use crate::{mask, Error, SpecSegment};
+
+/// Where and when a production lot was made: the production line, the shift and the day of the
+/// year, `1` being January 1st.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LotCode {
+    pub line: u16,
+    pub shift: u8,
+    pub day: u16,
+}
+
+impl LotCode {
+    /// The calendar date of the lot's day in `year`.
+    pub fn date(&self, year: i32) -> Result<Date, Error> {
+        Ok(Date::from_ordinal_date(year, self.day)?)
+    }
+}
+
+/// `line-shift-day`, the day zero-padded to three digits, as printed on physical labels.
+impl fmt::Display for LotCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}-{:03}", self.line, self.shift, self.day)
+    }
+}
+
+/// Bits of the day of the year, enough for 366.
+const DAY_BITS: u8 = 9;
+
+/// A [`LotCode`] packed as line, shift and day of the year, most significant first, so physical
+/// labels and database IDs share one encoding. The day takes 9 bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LotSegment {
+    line_bits: u8,
+    shift_bits: u8,
+    lot: LotCode,
+}
+
+impl LotSegment {
+    /// Fails if the line or shift doesn't fit in its bits, either is wider than its type, or
+    /// the day is not a day of the year.
+    pub fn new(line_bits: u8, shift_bits: u8, lot: LotCode) -> Result<Self, Error> {
+        let fields = [
+            (line_bits, u128::from(lot.line), 16),
+            (shift_bits, u128::from(lot.shift), 8),
+        ];
+        for (size, value, max) in fields {
+            if size > max {
+                return Err(Error::SizeExceeded {
+                    size: size.into(),
+                    max: max.into(),
+                });
+            }
+            if value & !mask(size) != 0 {
+                return Err(Error::ValueTooLarge { value, size });
+            }
+        }
+        day(lot.day)?;
+        Ok(Self {
+            line_bits,
+            shift_bits,
+            lot,
+        })
+    }
+
+    pub fn lot(&self) -> LotCode {
+        self.lot
+    }
+}
+
+/// Checks `day` against a leap year, which has every day of the year.
+fn day(day: u16) -> Result<u16, Error> {
+    Date::from_ordinal_date(2024, day)?;
+    Ok(day)
+}
+
+impl SpecSegment<i128, LotCode> for LotSegment {
+    fn size(&self) -> u8 {
+        self.line_bits + self.shift_bits + DAY_BITS
+    }
+
+    fn upper_bound(&self) -> LotCode {
+        LotCode {
+            line: mask(self.line_bits) as u16,
+            shift: mask(self.shift_bits) as u8,
+            day: 366,
+        }
+    }
+
+    fn encode(&self) -> Result<i128, Error> {
+        let LotCode { line, shift, day } = self.lot;
+        Ok((i128::from(line) << self.shift_bits | i128::from(shift)) << DAY_BITS | i128::from(day))
+    }
+
+    fn decode(&self, encoded: i128) -> Result<LotCode, Error> {
+        let encoded = encoded as u128;
+        if encoded & !mask(self.size()) != 0 {
+            return Err(Error::ValueTooLarge {
+                value: encoded,
+                size: self.size(),
+            });
+        }
+        Ok(LotCode {
+            line: (encoded >> (self.shift_bits + DAY_BITS)) as u16,
+            shift: (encoded >> DAY_BITS & mask(self.shift_bits)) as u8,
+            day: day((encoded & mask(DAY_BITS)) as u16)?,
+        })
+    }
+
+    fn verify(&self, encoded: i128) -> Result<(), Error> {
+        self.decode(encoded).map(|_| ())
+    }
+
+    crate::clone_box!(i128, LotCode);
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+    use alloc::string::ToString;
+
+    use time::Month;
+
+    use super::*;
+    use crate::{RandomSegment, Spec2};
+
+    #[test]
+    fn packs_line_shift_and_day() {
+        let lot = LotCode {
+            line: 12,
+            shift: 2,
+            day: 45,
+        };
+        let segment = LotSegment::new(6, 2, lot).unwrap();
+        assert_eq!(17, segment.size());
+        assert_eq!((12 << 2 | 2) << 9 | 45, segment.encode().unwrap());
+        assert_eq!("12-2-045", lot.to_string());
+        assert_eq!(
+            Date::from_calendar_date(2025, Month::February, 14).unwrap(),
+            lot.date(2025).unwrap()
+        );
+
+        let spec = Spec2::<i128, LotCode, i128> {
+            segments: (Box::new(segment), Box::new(RandomSegment::new(47))),
+        };
+        let id = spec.generate().unwrap();
+        assert_eq!(lot, spec.decompose(id).unwrap().0);
+        assert!(matches!(segment.decode(0), Err(Error::Time(_))));
+    }
+
+    #[test]
+    fn checks_each_field() {
+        let lot = |line, shift, day| LotCode { line, shift, day };
+        assert!(matches!(
+            LotSegment::new(4, 2, lot(16, 0, 1)),
+            Err(Error::ValueTooLarge { value: 16, size: 4 })
+        ));
+        assert!(matches!(
+            LotSegment::new(4, 2, lot(1, 4, 1)),
+            Err(Error::ValueTooLarge { value: 4, size: 2 })
+        ));
+        assert!(matches!(
+            LotSegment::new(4, 2, lot(1, 1, 367)),
+            Err(Error::Time(_))
+        ));
+        assert!(matches!(
+            LotSegment::new(17, 2, lot(1, 1, 1)),
+            Err(Error::SizeExceeded { size: 17, max: 16 })
+        ));
+        assert!(LotSegment::new(4, 2, lot(15, 3, 366)).is_ok());
+    }
+}
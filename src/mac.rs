@@ -0,0 +1,96 @@
+use crate::{Error, SpecSegment};
+
+/// A full EUI-48 MAC address, most significant byte first, for UUIDv1-like layouts and asset
+/// tracking schemes that embed the hardware an ID was minted on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MacSegment {
+    address: [u8; 6],
+}
+
+impl MacSegment {
+    pub const fn new(address: [u8; 6]) -> Self {
+        Self { address }
+    }
+
+    /// Uses the address of the host's first network interface that has one.
+    #[cfg(feature = "mac")]
+    pub fn detect() -> Result<Self, Error> {
+        match mac_address::get_mac_address() {
+            Ok(Some(address)) => Ok(Self::new(address.bytes())),
+            _ => Err(Error::Unsupported {
+                reason: "no MAC address found",
+            }),
+        }
+    }
+
+    pub fn address(&self) -> [u8; 6] {
+        self.address
+    }
+}
+
+impl SpecSegment<i128, [u8; 6]> for MacSegment {
+    fn size(&self) -> u8 {
+        48
+    }
+
+    fn upper_bound(&self) -> [u8; 6] {
+        [0xff; 6]
+    }
+
+    fn encode(&self) -> Result<i128, Error> {
+        Ok(self
+            .address
+            .iter()
+            .fold(0, |value, byte| value << 8 | i128::from(*byte)))
+    }
+
+    fn decode(&self, encoded: i128) -> Result<[u8; 6], Error> {
+        if encoded as u128 >> 48 != 0 {
+            return Err(Error::ValueTooLarge {
+                value: encoded as u128,
+                size: 48,
+            });
+        }
+        let bytes = encoded.to_be_bytes();
+        let mut address = [0; 6];
+        address.copy_from_slice(&bytes[10..]);
+        Ok(address)
+    }
+
+    crate::clone_box!(i128, [u8; 6]);
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+
+    use super::*;
+    use crate::{RandomSegment, Spec2};
+
+    #[test]
+    fn embeds_the_address() {
+        let address = [0x00, 0x1b, 0x63, 0x84, 0x45, 0xe6];
+        let spec = Spec2::<i128, [u8; 6], i128> {
+            segments: (
+                Box::new(MacSegment::new(address)),
+                Box::new(RandomSegment::new(80)),
+            ),
+        };
+        let id = spec.generate().unwrap();
+        assert_eq!(0x001b_6384_45e6, id >> 80);
+        assert_eq!(address, spec.decompose(id).unwrap().0);
+        assert!(matches!(
+            MacSegment::new(address).decode(1 << 48),
+            Err(Error::ValueTooLarge { size: 48, .. })
+        ));
+    }
+
+    #[cfg(feature = "mac")]
+    #[test]
+    fn detects_the_host_address() {
+        if let Ok(segment) = MacSegment::detect() {
+            let encoded = segment.encode().unwrap();
+            assert_eq!(segment.address(), segment.decode(encoded).unwrap());
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! `metrics` integration, so dashboards can track generator health fleet-wide:
+//!
+//! - `utid_ids_generated` and the `utid_generation_seconds` histogram, labelled `spec` with the
+//!   spec's [fingerprint](crate::SpecConfig::fingerprint) in hex;
+//! - `utid_generation_failures`, labelled `spec`, `code` and `segment` with the failing segment's
+//!   [label](crate::SpecSegment::label), or its index if it has none;
+//! - `utid_clock_regressions` and `utid_counter_overflows`, counted by [`Sequence`]s, which
+//!   don't know their spec and so are unlabelled;
+//! - `utid_parse_failures`, labelled `encoding`.
+//!
+//! [`Sequence`]: crate::Sequence
+
+use std::format;
+use std::panic::AssertUnwindSafe;
+use std::string::{String, ToString};
+use std::time::Instant;
+
+use metrics::{counter, histogram, Counter, Histogram};
+
+use crate::Error;
+
+/// Handles for one spec, registered once so generating doesn't look them up. They only touch
+/// atomics in the recorder, so holding them doesn't make specs any less unwind safe.
+#[derive(Debug)]
+pub(crate) struct SpecMetrics {
+    spec: String,
+    generated: AssertUnwindSafe<Counter>,
+    latency: AssertUnwindSafe<Histogram>,
+}
+
+impl Clone for SpecMetrics {
+    fn clone(&self) -> Self {
+        Self {
+            spec: self.spec.clone(),
+            generated: AssertUnwindSafe(self.generated.0.clone()),
+            latency: AssertUnwindSafe(self.latency.0.clone()),
+        }
+    }
+}
+
+impl SpecMetrics {
+    /// `None` labels the metrics `custom`, for specs with segments lacking a config.
+    pub(crate) fn new(fingerprint: Option<u64>) -> Self {
+        let spec = fingerprint.map_or_else(|| "custom".into(), |f| format!("{:016x}", f));
+        Self {
+            generated: AssertUnwindSafe(counter!("utid_ids_generated", "spec" => spec.clone())),
+            latency: AssertUnwindSafe(
+                histogram!("utid_generation_seconds", "spec" => spec.clone()),
+            ),
+            spec,
+        }
+    }
+
+    /// Runs one generation, timing it whether or not it succeeds.
+    pub(crate) fn time<T>(&self, generate: impl FnOnce() -> Result<T, Error>) -> Result<T, Error> {
+        let started = Instant::now();
+        let result = generate();
+        self.latency.record(started.elapsed());
+        match &result {
+            Ok(_) => self.generated.increment(1),
+            Err(error) => self.failed(error),
+        }
+        result
+    }
+
+    /// Failures are rare, so their counter is looked up when one happens.
+    fn failed(&self, error: &Error) {
+        let segment = match error {
+            Error::InSegment {
+                label: Some(label), ..
+            } => label.clone(),
+            Error::InSegment { index, .. } => index.to_string(),
+            _ => "none".into(),
+        };
+        counter!(
+            "utid_generation_failures",
+            "spec" => self.spec.clone(),
+            "code" => error.code(),
+            "segment" => segment
+        )
+        .increment(1);
+    }
+}
+
+pub(crate) fn clock_regression() {
+    counter!("utid_clock_regressions").increment(1);
+}
+
+pub(crate) fn counter_overflow() {
+    counter!("utid_counter_overflows").increment(1);
+}
+
+pub(crate) fn parse_failure(encoding: &'static str) {
+    counter!("utid_parse_failures", "encoding" => encoding).increment(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::string::{String, ToString};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::vec::Vec;
+
+    use metrics::{Gauge, HistogramFn, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+
+    use super::*;
+    use crate::encoding::decode_base32;
+    use crate::{ConstantSegment, Generator, Labeled, RandomSegment, Sequence, Spec2, SpecConfig};
+
+    /// Keeps counter values and histogram sample counts by key.
+    #[derive(Default)]
+    struct Memory {
+        counters: Mutex<BTreeMap<String, Arc<AtomicU64>>>,
+        histograms: Mutex<BTreeMap<String, Arc<Samples>>>,
+    }
+
+    #[derive(Default)]
+    struct Samples(AtomicU64);
+
+    impl HistogramFn for Samples {
+        fn record(&self, _: f64) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// `name{label=value,...}`, as the assertions spell keys.
+    fn flatten(key: &Key) -> String {
+        let labels: Vec<_> = key
+            .labels()
+            .map(|label| format!("{}={}", label.key(), label.value()))
+            .collect();
+        match labels.is_empty() {
+            true => key.name().to_string(),
+            false => format!("{}{{{}}}", key.name(), labels.join(",")),
+        }
+    }
+
+    impl Memory {
+        fn counter(&self, key: &str) -> u64 {
+            self.counters.lock().unwrap()[key].load(Ordering::Relaxed)
+        }
+
+        fn samples(&self, key: &str) -> u64 {
+            self.histograms.lock().unwrap()[key]
+                .0
+                .load(Ordering::Relaxed)
+        }
+    }
+
+    impl Recorder for Memory {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            let mut counters = self.counters.lock().unwrap();
+            Counter::from_arc(counters.entry(flatten(key)).or_default().clone())
+        }
+
+        fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::noop()
+        }
+
+        fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+            let mut histograms = self.histograms.lock().unwrap();
+            Histogram::from_arc(histograms.entry(flatten(key)).or_default().clone())
+        }
+    }
+
+    #[test]
+    fn counts_generation_sequences_and_parsing() {
+        let memory = Memory::default();
+        let config = SpecConfig {
+            segments: vec![
+                crate::SegmentConfig::Constant { size: 8, value: 7 },
+                crate::SegmentConfig::Random {
+                    size: 8,
+                    os_rng: false,
+                },
+            ],
+        };
+        let spec = format!("{:016x}", config.fingerprint());
+        metrics::with_local_recorder(&memory, || {
+            let dynamic = config.build().unwrap().with_metrics();
+            dynamic.generate().unwrap();
+            dynamic.generate().unwrap();
+            // Not opted in.
+            config.build().unwrap().generate().unwrap();
+
+            let generator = Generator::new(Spec2::<i128, i128, i128> {
+                segments: (
+                    Box::new(ConstantSegment::new(8, 7)),
+                    Box::new(Labeled::new(
+                        "entropy",
+                        RandomSegment::with_rng(8, |_| Err(Error::Exhausted)),
+                    )),
+                ),
+            })
+            .unwrap()
+            .with_metrics();
+            generator.generate().unwrap_err();
+
+            let sequence = Sequence::new(1);
+            for tick in [5, 5, 5, 3] {
+                sequence.next(tick).unwrap();
+            }
+
+            decode_base32("0U").unwrap_err();
+        });
+        let labelled = |name: &str| format!("{}{{spec={}}}", name, spec);
+        assert_eq!(2, memory.counter(&labelled("utid_ids_generated")));
+        assert_eq!(3, memory.samples(&labelled("utid_generation_seconds")));
+        assert_eq!(
+            1,
+            memory.counter(&format!(
+                "utid_generation_failures{{spec={},code=exhausted,segment=entropy}}",
+                spec
+            ))
+        );
+        assert_eq!(1, memory.counter("utid_clock_regressions"));
+        assert_eq!(1, memory.counter("utid_counter_overflows"));
+        assert_eq!(1, memory.counter("utid_parse_failures{encoding=base32}"));
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{mask, Error, SpecSegment};
+
+/// Snowflake-style node id: a datacenter number above a worker number, packed as one segment
+/// and decoded as the `(datacenter, worker)` pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeSegment {
+    datacenter_bits: u8,
+    worker_bits: u8,
+    datacenter: u32,
+    worker: u32,
+}
+
+impl NodeSegment {
+    /// Fails if either field is wider than 32 bits or its number doesn't fit in it.
+    pub fn new(
+        datacenter_bits: u8,
+        worker_bits: u8,
+        datacenter: u32,
+        worker: u32,
+    ) -> Result<Self, Error> {
+        for (size, value) in [(datacenter_bits, datacenter), (worker_bits, worker)] {
+            if size > 32 {
+                return Err(Error::SizeExceeded {
+                    size: u32::from(size),
+                    max: 32,
+                });
+            }
+            if u128::from(value) & !mask(size) != 0 {
+                return Err(Error::ValueTooLarge {
+                    value: value.into(),
+                    size,
+                });
+            }
+        }
+        Ok(Self {
+            datacenter_bits,
+            worker_bits,
+            datacenter,
+            worker,
+        })
+    }
+
+    /// The 5-bit datacenter and 5-bit worker of Twitter's Snowflake.
+    pub fn snowflake(datacenter: u32, worker: u32) -> Result<Self, Error> {
+        Self::new(5, 5, datacenter, worker)
+    }
+
+    pub fn datacenter(&self) -> u32 {
+        self.datacenter
+    }
+
+    pub fn worker(&self) -> u32 {
+        self.worker
+    }
+}
+
+impl SpecSegment<i128, (u32, u32)> for NodeSegment {
+    fn size(&self) -> u8 {
+        self.datacenter_bits + self.worker_bits
+    }
+
+    fn upper_bound(&self) -> (u32, u32) {
+        (
+            mask(self.datacenter_bits) as u32,
+            mask(self.worker_bits) as u32,
+        )
+    }
+
+    fn encode(&self) -> Result<i128, Error> {
+        Ok(i128::from(self.datacenter) << self.worker_bits | i128::from(self.worker))
+    }
+
+    fn decode(&self, encoded: i128) -> Result<(u32, u32), Error> {
+        let encoded = encoded as u128;
+        if encoded & !mask(self.size()) != 0 {
+            return Err(Error::ValueTooLarge {
+                value: encoded,
+                size: self.size(),
+            });
+        }
+        Ok((
+            (encoded >> self.worker_bits) as u32,
+            (encoded & mask(self.worker_bits)) as u32,
+        ))
+    }
+
+    crate::clone_box!(i128, (u32, u32));
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+
+    use super::*;
+    use crate::{RandomSegment, Spec2};
+
+    #[test]
+    fn packs_datacenter_above_worker() {
+        let segment = NodeSegment::snowflake(3, 17).unwrap();
+        assert_eq!(10, segment.size());
+        assert_eq!(3 << 5 | 17, segment.encode().unwrap());
+        assert_eq!((31, 31), segment.upper_bound());
+
+        let spec = Spec2::<i128, (u32, u32), i128> {
+            segments: (Box::new(segment), Box::new(RandomSegment::new(54))),
+        };
+        let id = spec.generate().unwrap();
+        assert_eq!((3, 17), spec.decompose(id).unwrap().0);
+        assert_eq!((31, 0), spec.decompose(0x3e0 << 54).unwrap().0);
+    }
+
+    #[test]
+    fn checks_each_field() {
+        assert!(matches!(
+            NodeSegment::snowflake(32, 0),
+            Err(Error::ValueTooLarge { value: 32, size: 5 })
+        ));
+        assert!(matches!(
+            NodeSegment::new(4, 6, 15, 64),
+            Err(Error::ValueTooLarge { value: 64, size: 6 })
+        ));
+        assert!(matches!(
+            NodeSegment::new(33, 1, 0, 0),
+            Err(Error::SizeExceeded { size: 33, max: 32 })
+        ));
+        assert!(NodeSegment::new(0, 10, 0, 1023).is_ok());
+    }
+}
@@ -0,0 +1,122 @@
+use alloc::vec::Vec;
+
+use crate::{split, Error, Segment};
+
+pub(crate) fn partition_key(
+    segments: &[&dyn Segment],
+    id: i128,
+    partitions: u32,
+) -> Result<u32, Error> {
+    if partitions == 0 {
+        return Err(Error::InvalidArgument {
+            reason: "there are no partitions to choose from",
+        });
+    }
+    let sizes: Vec<u8> = segments.iter().map(|segment| segment.size()).collect();
+    let mut keyed = segments
+        .iter()
+        .zip(split(&sizes, id))
+        .filter(|(segment, _)| segment.keys_partition())
+        .peekable();
+    if keyed.peek().is_none() {
+        return Err(Error::NoPartitionSegment);
+    }
+    let key = keyed.fold(0u64, |key, (_, value)| {
+        mix(key ^ mix(value as u64) ^ mix((value >> 64) as u64))
+    });
+    Ok(jump_hash(key, partitions))
+}
+
+/// The 64-bit finalizer of MurmurHash3, so sequential shard numbers land far apart.
+pub(crate) fn mix(mut x: u64) -> u64 {
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    x ^ (x >> 33)
+}
+
+/// Jump consistent hash (Lamping and Veach): growing from `n` to `n + 1` partitions moves only
+/// about `1 / (n + 1)` of the keys.
+fn jump_hash(mut key: u64, partitions: u32) -> u32 {
+    let (mut bucket, mut next) = (-1i64, 0i64);
+    while next < i64::from(partitions) {
+        bucket = next;
+        key = key.wrapping_mul(2_862_933_555_777_941_757).wrapping_add(1);
+        next = ((bucket + 1) as f64 * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as i64;
+    }
+    bucket as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Date;
+
+    use crate::{
+        ConstantSegment, Error, Layout, RandomSegment, Spec2, Spec3, TimestampSegment,
+        TimestampUnit,
+    };
+
+    fn spec() -> Spec3<i128, time::OffsetDateTime, i128, i128> {
+        Spec3 {
+            segments: (
+                Box::new(TimestampSegment::new_with_utc_midnight(
+                    48,
+                    TimestampUnit::Milliseconds,
+                    Date::from_calendar_date(2023, time::Month::January, 1).unwrap(),
+                )),
+                Box::new(ConstantSegment::new(8, 3)),
+                Box::new(RandomSegment::new(16)),
+            ),
+        }
+    }
+
+    #[test]
+    fn ignores_timestamp_and_constant_bits() {
+        let spec = spec();
+        let id = spec.generate().unwrap();
+        let partition = spec.partition_key(id, 12).unwrap();
+        assert!(partition < 12);
+        let later = id + (12345 << 24) + (1 << 16);
+        assert_eq!(partition, spec.partition_key(later, 12).unwrap());
+    }
+
+    #[test]
+    fn spreads_ids_of_one_instant() {
+        let spec = spec();
+        let mut counts = [0; 8];
+        for random in 0..8000 {
+            counts[spec.partition_key(random, 8).unwrap() as usize] += 1;
+        }
+        assert!(counts.iter().all(|count| (800..1200).contains(count)));
+    }
+
+    #[test]
+    fn moves_few_keys_when_growing() {
+        let spec = spec();
+        let moved = (0..10_000)
+            .filter(|id| {
+                spec.partition_key(*id, 10).unwrap() != spec.partition_key(*id, 11).unwrap()
+            })
+            .count();
+        assert!(moved < 1_500);
+    }
+
+    #[test]
+    fn rejects_layouts_without_key_segments() {
+        let constants = Spec2 {
+            segments: (
+                Box::new(ConstantSegment::new(8, 1)),
+                Box::new(ConstantSegment::new(8, 2)),
+            ),
+        };
+        assert!(matches!(
+            constants.partition_key(0x0102, 4),
+            Err(Error::NoPartitionSegment)
+        ));
+        assert!(matches!(
+            spec().partition_key(0, 0),
+            Err(Error::InvalidArgument { .. })
+        ));
+    }
+}
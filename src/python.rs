@@ -0,0 +1,142 @@
+//! Python bindings, so notebooks decode production IDs with the layout the services use instead
+//! of re-implementing it. IDs are plain Python `int`s; timestamps come back as UTC `datetime`s,
+//! truncated to microseconds.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{timezone_utc, PyDateTime};
+
+use crate::encoding::{decode_base32, decode_uuid, encode_base32, encode_uuid};
+use crate::{DynamicSpec, Error, Value};
+
+impl From<Error> for PyErr {
+    fn from(error: Error) -> Self {
+        PyValueError::new_err(error.to_string())
+    }
+}
+
+#[pyclass(name = "Spec", module = "utid", frozen)]
+pub struct PySpec {
+    spec: DynamicSpec,
+}
+
+#[pymethods]
+impl PySpec {
+    /// Builds a spec from its JSON config.
+    #[staticmethod]
+    fn from_json(config: &str) -> PyResult<Self> {
+        DynamicSpec::from_json(config)
+            .map(|spec| PySpec { spec })
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[staticmethod]
+    fn from_env(prefix: &str) -> PyResult<Self> {
+        DynamicSpec::from_env(prefix)
+            .map(|spec| PySpec { spec })
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn generate(&self) -> PyResult<i128> {
+        Ok(self.spec.generate()?)
+    }
+
+    /// One entry per segment, most significant first.
+    fn decompose<'py>(&self, py: Python<'py>, id: i128) -> PyResult<Vec<Bound<'py, PyAny>>> {
+        self.spec
+            .decompose(id)?
+            .into_iter()
+            .map(|value| match value {
+                Value::Timestamp(timestamp) => PyDateTime::new(
+                    py,
+                    timestamp.year(),
+                    timestamp.month().into(),
+                    timestamp.day(),
+                    timestamp.hour(),
+                    timestamp.minute(),
+                    timestamp.second(),
+                    timestamp.microsecond(),
+                    Some(&timezone_utc(py)),
+                )
+                .map(Bound::into_any),
+                Value::Integer(value) => Ok(value.into_pyobject(py)?.into_any()),
+            })
+            .collect()
+    }
+
+    fn __str__(&self) -> String {
+        self.spec.to_string()
+    }
+}
+
+#[pyfunction]
+fn to_base32(id: i128) -> String {
+    encode_base32(id, 128)
+}
+
+#[pyfunction]
+fn from_base32(encoded: &str) -> PyResult<i128> {
+    Ok(decode_base32(encoded)?)
+}
+
+#[pyfunction]
+fn to_uuid(id: i128) -> String {
+    encode_uuid(id)
+}
+
+#[pyfunction]
+fn from_uuid(encoded: &str) -> PyResult<i128> {
+    Ok(decode_uuid(encoded)?)
+}
+
+#[pymodule]
+fn utid(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PySpec>()?;
+    module.add_function(wrap_pyfunction!(to_base32, module)?)?;
+    module.add_function(wrap_pyfunction!(from_base32, module)?)?;
+    module.add_function(wrap_pyfunction!(to_uuid, module)?)?;
+    module.add_function(wrap_pyfunction!(from_uuid, module)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::types::PyDict;
+
+    use super::*;
+
+    #[test]
+    fn decodes_from_python() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "utid").unwrap();
+            utid(&module).unwrap();
+            let locals = PyDict::new(py);
+            locals.set_item("utid", module).unwrap();
+            py.run(
+                pyo3::ffi::c_str!(
+                    r#"
+import datetime
+spec = utid.Spec.from_json('{"segments":[' +
+    '{"kind":"timestamp","size":48,"unit":"milliseconds","epoch":"2023-01-01"},' +
+    '{"kind":"constant","size":16,"value":7},{"kind":"random","size":64}]}')
+id = spec.generate()
+timestamp, constant, _ = spec.decompose(id)
+assert constant == 7
+assert abs(datetime.datetime.now(datetime.timezone.utc) - timestamp).total_seconds() < 2
+assert utid.from_base32(utid.to_base32(id)) == id
+assert utid.from_uuid(utid.to_uuid(id)) == id
+try:
+    utid.from_base32("0U")
+    assert False
+except ValueError as e:
+    assert "position 1" in str(e)
+"#
+                ),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+        });
+    }
+}
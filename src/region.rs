@@ -0,0 +1,126 @@
+use crate::{mask, Error, SpecSegment};
+
+/// Deployment regions as the numbers IDs carry for them, typically implemented by a fieldless
+/// enum. Numbers must never be reassigned, or IDs minted before point to the wrong region.
+pub trait Region: Sized {
+    fn value(&self) -> u128;
+    fn from_value(value: u128) -> Option<Self>;
+}
+
+/// Encodes the region the generating deployment runs in, so IDs minted concurrently in
+/// different regions never collide and any ID tells which region minted it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegionSegment<G> {
+    size: u8,
+    region: G,
+}
+
+impl<G: Region> RegionSegment<G> {
+    /// Fails if `region`'s value doesn't fit in `size` bits.
+    pub fn new(size: u8, region: G) -> Result<Self, Error> {
+        let value = region.value();
+        if value & !mask(size) != 0 {
+            return Err(Error::ValueTooLarge { value, size });
+        }
+        Ok(Self { size, region })
+    }
+
+    pub fn region(&self) -> &G {
+        &self.region
+    }
+}
+
+impl<G: Region + Clone> SpecSegment<i128, G> for RegionSegment<G> {
+    fn size(&self) -> u8 {
+        self.size
+    }
+
+    fn upper_bound(&self) -> G {
+        self.region.clone()
+    }
+
+    fn encode(&self) -> Result<i128, Error> {
+        Ok(self.region.value() as i128)
+    }
+
+    fn decode(&self, encoded: i128) -> Result<G, Error> {
+        let value = encoded as u128;
+        G::from_value(value).ok_or(Error::UnknownValue { value })
+    }
+
+    fn verify(&self, encoded: i128) -> Result<(), Error> {
+        if G::from_value(encoded as u128).is_none() {
+            return Err(Error::Verification {
+                reason: "region is not defined",
+            });
+        }
+        Ok(())
+    }
+
+    crate::clone_box!(i128, G);
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+
+    use super::*;
+    use crate::{Layout, RandomSegment, Spec2};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Deployment {
+        UsEast,
+        EuWest,
+        ApSouth,
+    }
+
+    impl Region for Deployment {
+        fn value(&self) -> u128 {
+            match self {
+                Deployment::UsEast => 1,
+                Deployment::EuWest => 2,
+                Deployment::ApSouth => 3,
+            }
+        }
+
+        fn from_value(value: u128) -> Option<Self> {
+            match value {
+                1 => Some(Deployment::UsEast),
+                2 => Some(Deployment::EuWest),
+                3 => Some(Deployment::ApSouth),
+                _ => None,
+            }
+        }
+    }
+
+    fn spec(region: Deployment) -> Spec2<i128, Deployment, i128> {
+        Spec2 {
+            segments: (
+                Box::new(RegionSegment::new(4, region).unwrap()),
+                Box::new(RandomSegment::new(60)),
+            ),
+        }
+    }
+
+    #[test]
+    fn recovers_the_minting_region() {
+        let us = spec(Deployment::UsEast);
+        let eu = spec(Deployment::EuWest);
+        let id = eu.generate().unwrap();
+        assert_eq!(2, id >> 60);
+        assert_eq!(Deployment::EuWest, us.decompose(id).unwrap().0);
+        assert!(us.verify(id).is_valid());
+        assert_ne!(id >> 60, us.generate().unwrap() >> 60);
+
+        assert!(!us.verify(0).is_valid());
+        assert!(matches!(
+            us.decompose(0),
+            Err(Error::InSegment { index: 0, source, .. })
+                if matches!(*source, Error::UnknownValue { value: 0 })
+        ));
+        assert!(matches!(
+            RegionSegment::new(1, Deployment::ApSouth),
+            Err(Error::ValueTooLarge { value: 3, size: 1 })
+        ));
+    }
+}
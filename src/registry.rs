@@ -0,0 +1,175 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{DynamicSpec, Error, Layout, SegmentConfig, SpecConfig, Value, VersionDispatch};
+
+/// Spec versions of one ID family, told apart by a version segment in the most significant
+/// bits. Every version must be equally wide, so the version bits sit at the same place in all
+/// of them. Lookups go through a [`VersionDispatch`] set up by the first registration.
+pub struct SpecRegistry {
+    version_bits: u8,
+    dispatch: Option<VersionDispatch<DynamicSpec>>,
+}
+
+impl SpecRegistry {
+    pub fn new(version_bits: u8) -> Self {
+        Self {
+            version_bits,
+            dispatch: None,
+        }
+    }
+
+    /// Registers the layout of `version`; the version segment is prepended to `payload`.
+    pub fn register(&mut self, version: u32, payload: SpecConfig) -> Result<(), Error> {
+        let mut segments = vec![SegmentConfig::Constant {
+            size: self.version_bits,
+            value: version.into(),
+        }];
+        segments.extend(payload.segments);
+        let spec = SpecConfig { segments }
+            .build()
+            .map_err(Error::InvalidSpec)?;
+        let dispatch = match &mut self.dispatch {
+            Some(dispatch) => {
+                if spec.size() != dispatch.width() {
+                    return Err(Error::SizeMismatch {
+                        expected: dispatch.width().into(),
+                        actual: spec.size().into(),
+                    });
+                }
+                dispatch
+            }
+            None => self
+                .dispatch
+                .insert(VersionDispatch::new(self.version_bits, spec.size())?),
+        };
+        dispatch.register(version, spec)
+    }
+
+    pub fn latest(&self) -> Option<u32> {
+        self.dispatch.as_ref()?.latest()
+    }
+
+    pub fn spec(&self, version: u32) -> Option<&DynamicSpec> {
+        self.dispatch.as_ref()?.get(version)
+    }
+
+    /// The registry as a [`VersionDispatch`], once a version is registered.
+    pub fn dispatch(&self) -> Option<&VersionDispatch<DynamicSpec>> {
+        self.dispatch.as_ref()
+    }
+
+    pub fn generate(&self, version: u32) -> Result<i128, Error> {
+        self.spec(version)
+            .ok_or(Error::UnknownVersion {
+                version: version.into(),
+            })?
+            .generate()
+    }
+
+    /// The registered version in `id`'s leading bits.
+    pub fn version_of(&self, id: i128) -> Result<u32, Error> {
+        self.select(id).map(|(version, _)| version)
+    }
+
+    /// Decomposes `id` with the layout its version bits name, returning the version and the
+    /// values of the segments after it.
+    pub fn decompose(&self, id: i128) -> Result<(u32, Vec<Value>), Error> {
+        let (version, spec) = self.select(id)?;
+        let mut values = spec.decompose(id)?;
+        values.remove(0);
+        Ok((version, values))
+    }
+
+    fn select(&self, id: i128) -> Result<(u32, &DynamicSpec), Error> {
+        self.dispatch
+            .as_ref()
+            .ok_or(Error::UnknownVersion { version: 0 })?
+            .select(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Date, Duration};
+
+    use super::*;
+    use crate::TimestampUnit;
+
+    #[test]
+    fn dispatch_on_version() {
+        let epoch = Date::from_calendar_date(2023, time::Month::January, 1).unwrap();
+        let mut registry = SpecRegistry::new(4);
+        registry
+            .register(
+                1,
+                SpecConfig {
+                    segments: vec![
+                        SegmentConfig::Timestamp {
+                            size: 40,
+                            unit: TimestampUnit::Seconds,
+                            epoch,
+                            max_skew: Duration::MINUTE,
+                        },
+                        SegmentConfig::Random {
+                            size: 20,
+                            os_rng: false,
+                        },
+                    ],
+                },
+            )
+            .unwrap();
+        registry
+            .register(
+                2,
+                SpecConfig {
+                    segments: vec![
+                        SegmentConfig::Constant { size: 12, value: 9 },
+                        SegmentConfig::Random {
+                            size: 48,
+                            os_rng: false,
+                        },
+                    ],
+                },
+            )
+            .unwrap();
+        assert_eq!(Some(2), registry.latest());
+
+        let old = registry.generate(1).unwrap();
+        let (version, values) = registry.decompose(old).unwrap();
+        assert_eq!(1, version);
+        assert!(matches!(values[0], Value::Timestamp(_)));
+
+        let new = registry.generate(2).unwrap();
+        let (version, values) = registry.decompose(new).unwrap();
+        assert_eq!(2, version);
+        assert_eq!(Value::Integer(9), values[0]);
+
+        assert!(matches!(
+            registry.decompose(3 << 60),
+            Err(Error::UnknownVersion { version: 3 })
+        ));
+        let dispatch = registry.dispatch().unwrap();
+        assert_eq!(64, dispatch.width());
+        assert_eq!(2, dispatch.select(new).unwrap().0);
+        assert!(matches!(
+            registry.version_of(1 << 64),
+            Err(Error::ValueTooLarge { size: 64, .. })
+        ));
+        assert!(registry
+            .register(
+                3,
+                SpecConfig {
+                    segments: vec![SegmentConfig::Random {
+                        size: 8,
+                        os_rng: false
+                    }]
+                }
+            )
+            .is_err());
+        assert!(matches!(
+            registry.register(16, SpecConfig { segments: vec![] }),
+            Err(Error::InvalidSpec(_))
+        ));
+    }
+}
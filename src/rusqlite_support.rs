@@ -0,0 +1,75 @@
+//! SQLite support for [`Utid`] through rusqlite. IDs in `0..=i64::MAX`, i.e. every ID of a spec
+//! up to 63 bits wide, bind as `INTEGER`; all others bind as a 16-byte big-endian `BLOB`. SQLite
+//! sorts integers before blobs, so column order follows the numeric order of the unsigned ID
+//! either way.
+
+use ::rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+
+use crate::Utid;
+
+impl ToSql for Utid {
+    fn to_sql(&self) -> ::rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(match i64::try_from(*self) {
+            Ok(id) if id >= 0 => ToSqlOutput::from(id),
+            _ => ToSqlOutput::from(self.0.to_be_bytes().to_vec()),
+        })
+    }
+}
+
+impl FromSql for Utid {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value {
+            ValueRef::Integer(id) => Ok(Utid(id.into())),
+            ValueRef::Blob(bytes) => bytes
+                .try_into()
+                .map(|bytes| Utid(i128::from_be_bytes(bytes)))
+                .map_err(|_| FromSqlError::InvalidBlobSize {
+                    expected_size: 16,
+                    blob_size: bytes.len(),
+                }),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::rusqlite::Connection;
+
+    use crate::Utid;
+
+    #[test]
+    fn round_trips_and_keeps_order() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("CREATE TABLE ids (id)", ()).unwrap();
+        let ids = [
+            Utid(-1),
+            Utid(i64::MAX.into()),
+            Utid(1 << 64),
+            Utid(0),
+            Utid(42),
+        ];
+        for id in ids {
+            conn.execute("INSERT INTO ids VALUES (?1)", [id]).unwrap();
+        }
+
+        let kind: String = conn
+            .query_row(
+                "SELECT typeof(id) FROM ids WHERE id = ?1",
+                [Utid(42)],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!("integer", kind);
+
+        let mut stmt = conn.prepare("SELECT id FROM ids ORDER BY id").unwrap();
+        let sorted: Vec<Utid> = stmt
+            .query_map((), |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let mut expected = ids;
+        expected.sort_by_key(|id| id.0 as u128);
+        assert_eq!(&expected[..], &sorted[..]);
+    }
+}
@@ -0,0 +1,221 @@
+#[cfg(not(utid_loom))]
+use core::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(utid_loom)]
+use loom::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{mask, Error};
+
+/// State before the first pair is handed out, so `(0, 0)` can be issued. The pair it would
+/// otherwise encode, the last counter value of the last tick, is never issued.
+const UNUSED: u64 = u64::MAX;
+
+/// Per-tick counter for Snowflake-style IDs. The last tick and the counter share one atomic
+/// word updated by compare-and-swap, so concurrent generators never block each other and never
+/// hand out the same pair twice.
+///
+/// A tick earlier than the last one handed out, e.g. after the clock stepped back, and a tick
+/// whose counter ran out both continue from the last tick, borrowing the next one when needed.
+/// Ticks therefore never go backwards, at the cost of running ahead of the clock for a while.
+///
+/// `tests/loom_test.rs` checks these guarantees under every interleaving; run it with
+/// `RUSTFLAGS="--cfg utid_loom" cargo test --release --test loom_test`.
+#[derive(Debug)]
+pub struct Sequence {
+    state: AtomicU64,
+    bits: u8,
+}
+
+impl Sequence {
+    /// A counter of `bits` bits, which leaves `64 - bits` bits for the tick. [`next`](Self::next)
+    /// fails unless `bits` is between 1 and 63.
+    #[cfg(not(utid_loom))]
+    pub const fn new(bits: u8) -> Self {
+        Self {
+            state: AtomicU64::new(UNUSED),
+            bits,
+        }
+    }
+
+    /// Loom's atomics cannot be built in constant contexts.
+    #[cfg(utid_loom)]
+    pub fn new(bits: u8) -> Self {
+        Self {
+            state: AtomicU64::new(UNUSED),
+            bits,
+        }
+    }
+
+    pub fn bits(&self) -> u8 {
+        self.bits
+    }
+
+    /// The percentage of the latest tick's counter values handed out so far, rounded up, or 0
+    /// when `bits` is out of range.
+    pub fn utilization(&self) -> u8 {
+        if self.bits == 0 || self.bits >= 64 {
+            return 0;
+        }
+        let capacity = 1u128 << self.bits;
+        let state = self.state.load(Ordering::Relaxed);
+        if state == UNUSED {
+            return 0;
+        }
+        let issued = u128::from(state & mask(self.bits) as u64) + 1;
+        (issued * 100).div_ceil(capacity) as u8
+    }
+
+    /// The tick and counter value to use for an ID generated at `tick`.
+    pub fn next(&self, tick: u64) -> Result<(u64, u64), Error> {
+        if self.bits == 0 {
+            return Err(Error::SizeTooSmall { size: 0, min: 1 });
+        }
+        if self.bits >= 64 {
+            return Err(Error::SizeExceeded {
+                size: u32::from(self.bits),
+                max: 63,
+            });
+        }
+        let max = mask(self.bits) as u64;
+        if tick >> (64 - self.bits) != 0 {
+            return Err(Error::Overflow);
+        }
+        let mut current = self.state.load(Ordering::Relaxed);
+        loop {
+            // Nothing handed out yet counts as a tick before any other.
+            let last = (current != UNUSED).then_some(current >> self.bits);
+            let overflowed = last.is_some_and(|last| tick <= last) && current & max == max;
+            let next = match last {
+                Some(last) if tick <= last && !overflowed => current + 1,
+                Some(last) if tick <= last => (last + 1) << self.bits,
+                _ => tick << self.bits,
+            };
+            if next == UNUSED {
+                return Err(Error::Exhausted);
+            }
+            match self.state.compare_exchange_weak(
+                current,
+                next,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    // Recorded once the swap succeeds, so retries don't count an overflow twice.
+                    #[cfg(any(feature = "tracing", feature = "metrics"))]
+                    let last = last.unwrap_or(0);
+                    #[cfg(feature = "tracing")]
+                    if overflowed {
+                        crate::trace::counter_overflow(last);
+                    }
+                    #[cfg(feature = "metrics")]
+                    if overflowed {
+                        crate::metrics_support::counter_overflow();
+                    }
+                    #[cfg(feature = "tracing")]
+                    if tick < last {
+                        crate::trace::clock_behind(tick, last - tick);
+                    }
+                    #[cfg(feature = "metrics")]
+                    if tick < last {
+                        crate::metrics_support::clock_regression();
+                    }
+                    return Ok((next >> self.bits, next & max));
+                }
+                Err(actual) => current = actual,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn counts_within_a_tick() {
+        let sequence = Sequence::new(2);
+        assert_eq!((5, 0), sequence.next(5).unwrap());
+        assert_eq!((5, 1), sequence.next(5).unwrap());
+        assert_eq!((5, 2), sequence.next(5).unwrap());
+        assert_eq!((5, 3), sequence.next(5).unwrap());
+        // The counter ran out: borrow the next tick.
+        assert_eq!((6, 0), sequence.next(5).unwrap());
+        assert_eq!((6, 1), sequence.next(6).unwrap());
+        assert_eq!((9, 0), sequence.next(9).unwrap());
+    }
+
+    #[test]
+    fn utilization_counts_the_latest_tick() {
+        let sequence = Sequence::new(3);
+        sequence.next(5).unwrap();
+        sequence.next(5).unwrap();
+        sequence.next(5).unwrap();
+        assert_eq!(38, sequence.utilization());
+        sequence.next(6).unwrap();
+        assert_eq!(13, sequence.utilization());
+        assert_eq!(0, Sequence::new(0).utilization());
+    }
+
+    #[test]
+    fn issues_the_first_pair() {
+        let sequence = Sequence::new(4);
+        assert_eq!(0, sequence.utilization());
+        assert_eq!((0, 0), sequence.next(0).unwrap());
+        assert_eq!((0, 1), sequence.next(0).unwrap());
+    }
+
+    #[test]
+    fn never_goes_backwards() {
+        let sequence = Sequence::new(12);
+        assert_eq!((100, 0), sequence.next(100).unwrap());
+        assert_eq!((100, 1), sequence.next(90).unwrap());
+    }
+
+    #[test]
+    fn rejects_ticks_too_wide() {
+        let sequence = Sequence::new(12);
+        assert!(matches!(sequence.next(1 << 52), Err(Error::Overflow)));
+        assert!(matches!(
+            Sequence::new(64).next(0),
+            Err(Error::SizeExceeded { size: 64, .. })
+        ));
+        assert!(matches!(
+            Sequence::new(0).next(0),
+            Err(Error::SizeTooSmall { size: 0, min: 1 })
+        ));
+        // The last tick's last counter value is the unused state, so it is never issued.
+        let sequence = Sequence::new(1);
+        assert_eq!(((1 << 63) - 1, 0), sequence.next((1 << 63) - 1).unwrap());
+        assert!(matches!(
+            sequence.next((1 << 63) - 1),
+            Err(Error::Exhausted)
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn concurrent_pairs_are_unique() {
+        let sequence = Sequence::new(4);
+        let mut pairs: Vec<(u64, u64)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| {
+                    scope.spawn(|| {
+                        (0..1_000)
+                            .map(|_| sequence.next(1).unwrap())
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        let len = pairs.len();
+        pairs.sort_unstable();
+        pairs.dedup();
+        assert_eq!(len, pairs.len());
+    }
+}
@@ -0,0 +1,210 @@
+//! Serde support. [`Utid`] serializes as Crockford base32; raw `i128` IDs pick their
+//! representation per field, e.g. `#[serde(with = "utid::serde::hex")]`.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use ::serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::encoding::{decode_base32, encode_base32};
+use crate::{Error, Utid};
+
+impl Serialize for Utid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        base32::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Utid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        base32::deserialize(deserializer).map(Utid)
+    }
+}
+
+/// As a plain integer, for formats and consumers that handle 128 bits.
+pub mod number {
+    use ::serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(id: &i128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i128(*id)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i128, D::Error> {
+        i128::deserialize(deserializer)
+    }
+}
+
+/// As a decimal string, e.g. `"-42"`.
+pub mod decimal {
+    use ::serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(id: &i128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(id)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i128, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// As 32 lowercase hex digits of the ID's two's complement bits.
+pub mod hex {
+    use ::serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(id: &i128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&format_args!("{:032x}", *id as u128))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i128, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        u128::from_str_radix(&encoded, 16)
+            .map(|id| id as i128)
+            .map_err(de::Error::custom)
+    }
+}
+
+/// As 26 Crockford base32 digits, like [`Utid`].
+pub mod base32 {
+    use ::serde::{de, Deserialize, Deserializer, Serializer};
+
+    use crate::encoding::{decode_base32, encode_base32};
+
+    pub fn serialize<S: Serializer>(id: &i128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&encode_base32(*id, 128))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i128, D::Error> {
+        decode_base32(&String::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+/// Type-level prefix for [`Prefixed`] IDs.
+pub trait Prefix {
+    const PREFIX: &'static str;
+}
+
+/// An ID rendered as its prefix followed by Crockford base32, e.g. `ord_01H…`, so IDs of
+/// different entities can't be mixed up in logs or URLs.
+pub struct Prefixed<P> {
+    pub id: i128,
+    prefix: PhantomData<P>,
+}
+
+impl<P> Prefixed<P> {
+    pub fn new(id: i128) -> Self {
+        Self {
+            id,
+            prefix: PhantomData,
+        }
+    }
+}
+
+impl<P> Clone for Prefixed<P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P> Copy for Prefixed<P> {}
+
+impl<P> PartialEq for Prefixed<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<P> Eq for Prefixed<P> {}
+
+impl<P: Prefix> fmt::Debug for Prefixed<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Prefixed({})", self)
+    }
+}
+
+impl<P: Prefix> fmt::Display for Prefixed<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", P::PREFIX, encode_base32(self.id, 128))
+    }
+}
+
+impl<P: Prefix> FromStr for Prefixed<P> {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let encoded = s.strip_prefix(P::PREFIX).ok_or(Error::NonCanonical)?;
+        decode_base32(encoded).map(Self::new)
+    }
+}
+
+impl<P: Prefix> Serialize for Prefixed<P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de, P: Prefix> Deserialize<'de> for Prefixed<P> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::{Prefix, Prefixed};
+    use crate::Utid;
+
+    struct Order;
+
+    impl Prefix for Order {
+        const PREFIX: &'static str = "ord_";
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Record {
+        id: Utid,
+        #[serde(with = "crate::serde::number")]
+        number: i128,
+        #[serde(with = "crate::serde::decimal")]
+        decimal: i128,
+        #[serde(with = "crate::serde::hex")]
+        hex: i128,
+        #[serde(with = "crate::serde::base32")]
+        base32: i128,
+        order: Prefixed<Order>,
+    }
+
+    #[test]
+    fn representations_roundtrip() {
+        let record = Record {
+            id: Utid(385),
+            number: 42,
+            decimal: -1,
+            hex: -1,
+            base32: 385,
+            order: Prefixed::new(385),
+        };
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(
+            concat!(
+                r#"{"id":"000000000000000000000000C1","number":42,"decimal":"-1","#,
+                r#""hex":"ffffffffffffffffffffffffffffffff","base32":"000000000000000000000000C1","#,
+                r#""order":"ord_000000000000000000000000C1"}"#
+            ),
+            json
+        );
+        assert_eq!(record, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn rejects_wrong_prefix() {
+        assert!(serde_json::from_str::<Prefixed<Order>>(r#""usr_C1""#).is_err());
+        assert!(serde_json::from_str::<Utid>(r#""U""#).is_err());
+    }
+}
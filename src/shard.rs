@@ -0,0 +1,99 @@
+use sha2::{Digest, Sha256};
+
+use crate::{mask, upper_bound, Error, SpecSegment};
+
+/// The shard a caller's routing key, e.g. a user or account id, hashes to, so IDs carry their
+/// shard placement and routers read it off the ID instead of looking it up.
+///
+/// The shard is the last `size` bits of the key's SHA-256 digest read as a big-endian number, so
+/// services in other languages can place keys the same way. It needs the key, so specs with a
+/// shard segment generate with `generate_with`; plain `generate` fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShardSegment {
+    size: u8,
+}
+
+impl ShardSegment {
+    pub const fn new(size: u8) -> Self {
+        Self { size }
+    }
+
+    /// The shard `key` is placed on.
+    pub fn shard(&self, key: &[u8]) -> u128 {
+        let digest = Sha256::digest(key);
+        let mut low = [0; 16];
+        low.copy_from_slice(&digest[16..]);
+        u128::from_be_bytes(low) & mask(self.size)
+    }
+}
+
+impl SpecSegment<i128, i128> for ShardSegment {
+    fn size(&self) -> u8 {
+        self.size
+    }
+
+    fn upper_bound(&self) -> i128 {
+        upper_bound(self.size)
+    }
+
+    fn encode(&self) -> Result<i128, Error> {
+        Err(Error::Unsupported {
+            reason: "shard segments need a routing key; generate with one",
+        })
+    }
+
+    fn decode(&self, encoded: i128) -> Result<i128, Error> {
+        Ok(encoded)
+    }
+
+    fn encode_with(&self, key: &[u8]) -> Result<i128, Error> {
+        Ok(self.shard(key) as i128)
+    }
+
+    crate::clone_box!(i128, i128);
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+
+    use time::{Date, Month, OffsetDateTime};
+
+    use super::*;
+    use crate::{Generator, Labeled, RandomSegment, Spec3, TimestampSegment, TimestampUnit};
+
+    #[test]
+    fn places_ids_on_the_key_shard() {
+        let since = Date::from_calendar_date(2023, Month::January, 1).unwrap();
+        let shard = ShardSegment::new(10);
+        let spec = Spec3::<i128, OffsetDateTime, i128, i128> {
+            segments: (
+                Box::new(TimestampSegment::new_with_utc_midnight(
+                    44,
+                    TimestampUnit::Milliseconds,
+                    since,
+                )),
+                Box::new(Labeled::new("shard", shard)),
+                Box::new(RandomSegment::new(10)),
+            ),
+        };
+        let generator = Generator::new(spec).unwrap();
+        for key in [&b"user-1"[..], b"user-2", b"account-42"] {
+            let id = generator.generate_with(key).unwrap();
+            let (_, placed, _) = generator.decompose(id).unwrap();
+            assert_eq!(shard.shard(key) as i128, placed);
+            assert_eq!(
+                placed,
+                generator.spec().generate_with(key).unwrap() >> 10 & 0x3ff
+            );
+        }
+        assert_ne!(shard.shard(b"user-1"), shard.shard(b"user-2"));
+        // The low bits of SHA-256("abc").
+        assert_eq!(0x15ad, ShardSegment::new(16).shard(b"abc"));
+
+        assert!(matches!(
+            generator.generate(),
+            Err(Error::InSegment { index: 1, label: Some(label), .. }) if label == "shard"
+        ));
+    }
+}
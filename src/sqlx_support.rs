@@ -0,0 +1,125 @@
+//! Postgres support for [`Utid`] through sqlx. The Rust type picks the column type: [`Utid`]
+//! binds as `uuid`, [`UtidBytes`] as 16-byte `bytea` and [`UtidBigInt`], for specs up to 63
+//! bits wide, as `int8`. All three are big-endian or numeric, so index order follows the numeric
+//! order of the unsigned ID.
+
+use ::sqlx::encode::IsNull;
+use ::sqlx::error::BoxDynError;
+use ::sqlx::postgres::types::Oid;
+use ::sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, PgValueFormat, PgValueRef, Postgres};
+use ::sqlx::{Decode, Encode, Type, TypeInfo};
+
+use crate::encoding::{decode_uuid, from_uuid_bytes, uuid_bytes};
+use crate::{Utid, UtidBigInt, UtidBytes};
+
+impl Type<Postgres> for Utid {
+    fn type_info() -> PgTypeInfo {
+        // The built-in `uuid` type, so no name lookup round trip is needed.
+        PgTypeInfo::with_oid(Oid(2950))
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        ty.name() == "UUID"
+    }
+}
+
+impl Encode<'_, Postgres> for Utid {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        buf.extend_from_slice(&uuid_bytes(self.0));
+        Ok(IsNull::No)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for Utid {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        match value.format() {
+            PgValueFormat::Binary => Ok(Utid(from_uuid_bytes(value.as_bytes()?)?)),
+            PgValueFormat::Text => Ok(Utid(decode_uuid(value.as_str()?)?)),
+        }
+    }
+}
+
+impl Type<Postgres> for UtidBytes {
+    fn type_info() -> PgTypeInfo {
+        <[u8] as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <[u8] as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl Encode<'_, Postgres> for UtidBytes {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        buf.extend_from_slice(&uuid_bytes(self.0 .0));
+        Ok(IsNull::No)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for UtidBytes {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        let bytes = <&[u8] as Decode<Postgres>>::decode(value)?;
+        Ok(UtidBytes(Utid(from_uuid_bytes(bytes)?)))
+    }
+}
+
+impl Type<Postgres> for UtidBigInt {
+    fn type_info() -> PgTypeInfo {
+        <i64 as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <i64 as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl Encode<'_, Postgres> for UtidBigInt {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        self.get().encode_by_ref(buf)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for UtidBigInt {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(UtidBigInt::try_from(<i64 as Decode<Postgres>>::decode(
+            value,
+        )?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binds_as_big_endian_uuid() {
+        let mut buf = PgArgumentBuffer::default();
+        let id = Utid(0x0123_4567_89ab_cdef_0011_2233_4455_6677);
+        assert!(matches!(id.encode_by_ref(&mut buf).unwrap(), IsNull::No));
+        assert_eq!(&id.0.to_be_bytes()[..], &buf[..]);
+
+        let mut bytes = PgArgumentBuffer::default();
+        assert!(matches!(
+            UtidBytes(id).encode_by_ref(&mut bytes).unwrap(),
+            IsNull::No
+        ));
+        assert_eq!(&buf[..], &bytes[..]);
+        assert!(<UtidBytes as Type<Postgres>>::compatible(
+            &PgTypeInfo::with_name("BYTEA")
+        ));
+    }
+
+    #[test]
+    fn binds_narrow_ids_as_int8() {
+        let mut buf = PgArgumentBuffer::default();
+        let id = UtidBigInt::try_from(Utid(42)).unwrap();
+        assert!(matches!(id.encode_by_ref(&mut buf).unwrap(), IsNull::No));
+        assert_eq!(&42i64.to_be_bytes()[..], &buf[..]);
+        assert!(<UtidBigInt as Type<Postgres>>::compatible(
+            &PgTypeInfo::with_name("INT8")
+        ));
+        assert!(!<Utid as Type<Postgres>>::compatible(
+            &PgTypeInfo::with_name("INT8")
+        ));
+    }
+}
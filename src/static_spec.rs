@@ -0,0 +1,122 @@
+use crate::generator::segment_tuple_methods;
+use crate::{Error, Packing, SegmentTuple, SpecSegment};
+
+macro_rules! static_spec {
+    ($(#[$doc:meta])* $spec:ident { $($s:ident / $r:ident: $index:tt),+ }) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct $spec<$($s),+> {
+            pub segments: ($($s,)+),
+        }
+
+        impl<$($s),+> $spec<$($s),+> {
+            pub fn generate<$($r),+>(&self) -> Result<i128, Error>
+            where
+                $($s: SpecSegment<i128, $r>),+
+            {
+                Packing::new(self.segment_sizes())?
+                    .join(self.encode_segments()?)
+                    .map_err(|e| e.with_label(|index| self.segment_label(index)))
+            }
+
+            /// [`generate`](Self::generate) for the routing `key`; see
+            /// [`SpecSegment::encode_with`].
+            pub fn generate_with<$($r),+>(&self, key: &[u8]) -> Result<i128, Error>
+            where
+                $($s: SpecSegment<i128, $r>),+
+            {
+                Packing::new(self.segment_sizes())?
+                    .join(self.encode_segments_with(key)?)
+                    .map_err(|e| e.with_label(|index| self.segment_label(index)))
+            }
+
+            pub fn decompose<$($r),+>(&self, generated: i128) -> Result<($($r,)+), Error>
+            where
+                $($s: SpecSegment<i128, $r>),+
+            {
+                let values = Packing::new(self.segment_sizes())?.split(generated)?;
+                self.decode_segments(values)
+            }
+
+            /// The IDs' width in bits, which [`generate`](Self::generate) checks against 128.
+            pub fn size<$($r),+>(&self) -> u32
+            where
+                $($s: SpecSegment<i128, $r>),+
+            {
+                0 $(+ u32::from(self.segments.$index.size()))+
+            }
+        }
+
+        impl<$($s,)+ $($r),+> SegmentTuple<{ [$($index),+].len() }, ($($r,)+)> for $spec<$($s),+>
+        where
+            $($s: SpecSegment<i128, $r>),+
+        {
+            segment_tuple_methods!($($r: $index),+);
+        }
+    };
+}
+
+static_spec!(
+    /// [`Spec2`](crate::Spec2) holding its segments by value: no heap allocation to build,
+    /// generate or decompose, and statically dispatched segment calls. The built-in segments'
+    /// constructors are `const fn`, so such specs can be declared as `static`s.
+    StaticSpec2 { S1 / R1: 0, S2 / R2: 1 }
+);
+static_spec!(
+    /// [`Spec3`](crate::Spec3) holding its segments by value; see [`StaticSpec2`].
+    StaticSpec3 { S1 / R1: 0, S2 / R2: 1, S3 / R3: 2 }
+);
+static_spec!(
+    /// [`Spec4`](crate::Spec4) holding its segments by value; see [`StaticSpec2`].
+    StaticSpec4 { S1 / R1: 0, S2 / R2: 1, S3 / R3: 2, S4 / R4: 3 }
+);
+
+#[cfg(test)]
+mod tests {
+    use time::{Date, Duration, Month};
+
+    use super::*;
+    use crate::{ConstantSegment, RandomSegment, TimestampSegment, TimestampUnit};
+
+    const EPOCH: Date = match Date::from_calendar_date(2023, Month::January, 1) {
+        Ok(date) => date,
+        Err(_) => panic!("invalid epoch"),
+    };
+
+    static ORDER_SPEC: StaticSpec3<TimestampSegment, ConstantSegment<i128>, RandomSegment> =
+        StaticSpec3 {
+            segments: (
+                TimestampSegment::new_with_utc_midnight(48, TimestampUnit::Milliseconds, EPOCH)
+                    .with_max_skew(Duration::SECOND),
+                ConstantSegment::new(8, 1),
+                RandomSegment::with_os_rng(72),
+            ),
+        };
+
+    #[test]
+    fn declares_static_specs() {
+        let id = ORDER_SPEC.generate().unwrap();
+        assert_eq!(1, ORDER_SPEC.decompose(id).unwrap().1);
+    }
+
+    #[test]
+    fn generates_and_decomposes_by_value() {
+        let since = Date::from_calendar_date(2023, Month::January, 1).unwrap();
+        let spec = StaticSpec3 {
+            segments: (
+                TimestampSegment::new_with_utc_midnight(40, TimestampUnit::Milliseconds, since),
+                ConstantSegment::new(8, 42),
+                RandomSegment::new(16),
+            ),
+        };
+        assert_eq!(64, spec.size());
+        let id = spec.generate().unwrap();
+        let (timestamp, device, _) = spec.decompose(id).unwrap();
+        assert!(timestamp > since.midnight().assume_utc());
+        assert_eq!(42, device);
+        assert!(matches!(
+            spec.decompose(1 << 64),
+            Err(Error::ValueTooLarge { size: 64, .. })
+        ));
+    }
+}
@@ -0,0 +1,281 @@
+//! Proptest strategies producing valid specs and IDs that fit them, for property-testing code
+//! that stores or transports IDs, and ready-made properties for specs with custom segments.
+
+use core::fmt::Debug;
+
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestCaseError, TestRunner};
+use time::{Date, Duration, Month, Time};
+
+use crate::encoding::{decode_base32, decode_uuid, encode_base32, encode_uuid};
+use crate::{
+    join, mask, DynamicSpec, Error, Layout, SegmentConfig, Spec, Spec2, Spec3, Spec4, SpecConfig,
+    TimestampUnit, Utid, Value,
+};
+
+pub fn timestamp_unit() -> impl Strategy<Value = TimestampUnit> {
+    prop_oneof![
+        Just(TimestampUnit::Seconds),
+        Just(TimestampUnit::Milliseconds),
+        Just(TimestampUnit::Microseconds),
+        Just(TimestampUnit::Nanoseconds),
+    ]
+}
+
+pub fn epoch() -> impl Strategy<Value = Date> {
+    (1970i32..=2030, 1u8..=12, 1u8..=28).prop_map(|(year, month, day)| {
+        Date::from_calendar_date(year, Month::try_from(month).unwrap_or(Month::January), day)
+            .unwrap_or(Date::MIN)
+    })
+}
+
+pub fn segment_config(size: u8) -> impl Strategy<Value = SegmentConfig> {
+    prop_oneof![
+        (timestamp_unit(), epoch(), 0..=3_600_000i64).prop_map(move |(unit, epoch, skew)| {
+            SegmentConfig::Timestamp {
+                size,
+                unit,
+                epoch,
+                max_skew: Duration::milliseconds(skew),
+            }
+        }),
+        any::<bool>().prop_map(move |os_rng| SegmentConfig::Random { size, os_rng }),
+        (0..=mask(size)).prop_map(move |value| SegmentConfig::Constant {
+            size,
+            value: value as i128,
+        }),
+    ]
+}
+
+/// Layouts of one to four non-empty segments that pass [`crate::Layout::validate`].
+pub fn spec_config() -> impl Strategy<Value = SpecConfig> {
+    prop::collection::vec(1u8..=64, 1..=4)
+        .prop_map(|sizes| {
+            let mut total = 0u32;
+            sizes
+                .into_iter()
+                .take_while(|size| {
+                    total += u32::from(*size);
+                    total <= 128
+                })
+                .collect::<Vec<u8>>()
+        })
+        .prop_flat_map(|sizes| sizes.into_iter().map(segment_config).collect::<Vec<_>>())
+        .prop_map(|segments| SpecConfig { segments })
+}
+
+/// Largest timestamp value that still decodes to a representable date.
+fn timestamp_limit(size: u8, unit: TimestampUnit, epoch: Date) -> u128 {
+    let latest = Date::MAX.with_time(Time::MAX).assume_utc();
+    let nanos = (latest - epoch.midnight().assume_utc()).whole_nanoseconds();
+    mask(size).min(unit.from_nano(nanos) as u128)
+}
+
+/// IDs that `config` could have generated at any point of its lifetime.
+pub fn id(config: &SpecConfig) -> impl Strategy<Value = i128> {
+    let sizes: Vec<u8> = config.segments.iter().map(SegmentConfig::size).collect();
+    config
+        .segments
+        .iter()
+        .map(|segment| match segment {
+            SegmentConfig::Timestamp {
+                size, unit, epoch, ..
+            } => (0..=timestamp_limit(*size, *unit, *epoch)).boxed(),
+            SegmentConfig::Random { size, .. } => (0..=mask(*size)).boxed(),
+            SegmentConfig::Constant { value, .. } => Just(*value as u128).boxed(),
+        })
+        .collect::<Vec<_>>()
+        .prop_map(move |values| join(&sizes, &values).unwrap_or_default())
+}
+
+pub fn spec_with_id() -> impl Strategy<Value = (SpecConfig, i128)> {
+    spec_config().prop_flat_map(|config| {
+        let id = id(&config);
+        (Just(config), id)
+    })
+}
+
+/// A spec [`assert_roundtrip`] and [`assert_sortable`] can generate and decompose IDs with.
+pub trait Roundtrip: Layout {
+    type Decomposed: Debug + PartialEq;
+
+    fn generate_id(&self) -> Result<i128, Error>;
+    fn decompose_id(&self, id: i128) -> Result<Self::Decomposed, Error>;
+}
+
+impl Roundtrip for DynamicSpec {
+    type Decomposed = Vec<Value>;
+
+    fn generate_id(&self) -> Result<i128, Error> {
+        self.generate()
+    }
+
+    fn decompose_id(&self, id: i128) -> Result<Vec<Value>, Error> {
+        self.decompose(id)
+    }
+}
+
+impl<R: Debug + PartialEq> Roundtrip for Spec<i128, R> {
+    type Decomposed = R;
+
+    fn generate_id(&self) -> Result<i128, Error> {
+        self.generate()
+    }
+
+    fn decompose_id(&self, id: i128) -> Result<R, Error> {
+        self.decompose(id)
+    }
+}
+
+macro_rules! impl_roundtrip {
+    ($spec:ident<$($r:ident),+>) => {
+        impl<$($r: Debug + PartialEq),+> Roundtrip for $spec<i128, $($r),+> {
+            type Decomposed = ($($r,)+);
+
+            fn generate_id(&self) -> Result<i128, Error> {
+                self.generate()
+            }
+
+            fn decompose_id(&self, id: i128) -> Result<Self::Decomposed, Error> {
+                self.decompose(id)
+            }
+        }
+    };
+}
+
+impl_roundtrip!(Spec2<R1, R2>);
+impl_roundtrip!(Spec3<R1, R2, R3>);
+impl_roundtrip!(Spec4<R1, R2, R3, R4>);
+
+fn check(condition: bool, message: impl FnOnce() -> String) -> Result<(), TestCaseError> {
+    if condition {
+        Ok(())
+    } else {
+        Err(TestCaseError::fail(message()))
+    }
+}
+
+#[allow(clippy::panic)]
+fn run_cases<S: Strategy>(
+    cases: u32,
+    strategy: S,
+    test: impl Fn(S::Value) -> Result<(), TestCaseError>,
+) where
+    S::Value: Debug,
+{
+    let mut runner = TestRunner::new(Config {
+        cases,
+        ..Config::default()
+    });
+    if let Err(error) = runner.run(&strategy, test) {
+        panic!("{}", error);
+    }
+}
+
+/// Panics unless, over `cases` runs, every freshly generated ID fits the spec's width, passes
+/// [`Layout::verify`] and decomposes, and every ID within the width decomposes the same way
+/// twice, if at all, and survives base32, UUID and byte encoding unchanged.
+pub fn assert_roundtrip<S: Roundtrip>(spec: &S, cases: u32) {
+    let size = spec.size();
+    run_cases(cases, 0..=mask(size), |arbitrary| {
+        let generated = spec
+            .generate_id()
+            .map_err(|e| TestCaseError::fail(format!("generate failed: {}", e)))?;
+        check(size == 128 || (generated as u128) >> size == 0, || {
+            format!(
+                "{:#x} has bits set above the {} bits of the spec",
+                generated, size
+            )
+        })?;
+        let verification = spec.verify(generated);
+        check(verification.is_valid(), || {
+            format!(
+                "{:#x} fails verification: {:?}",
+                generated, verification.results
+            )
+        })?;
+        spec.decompose_id(generated)
+            .map_err(|e| TestCaseError::fail(format!("decomposing {:#x}: {}", generated, e)))?;
+
+        let arbitrary = arbitrary as i128;
+        let first = spec.decompose_id(arbitrary).ok();
+        let second = spec.decompose_id(arbitrary).ok();
+        check(first == second, || {
+            format!(
+                "{:#x} decomposes to {:?}, then {:?}",
+                arbitrary, first, second
+            )
+        })?;
+        for id in [generated, arbitrary] {
+            let parsed = [
+                decode_base32(&encode_base32(id, size)).ok(),
+                decode_uuid(&encode_uuid(id)).ok(),
+                Utid::from_bytes(&Utid(id).to_bytes())
+                    .ok()
+                    .map(|utid| utid.0),
+            ];
+            check(parsed.iter().all(|parsed| *parsed == Some(id)), || {
+                format!("{:#x} parses back as {:?}", id, parsed)
+            })?;
+        }
+        Ok(())
+    });
+}
+
+/// Panics unless `encode` orders IDs within the spec's width as their unsigned values order,
+/// both for freshly generated pairs and for any pair inside the width, so encoded IDs can be
+/// sorted as strings.
+pub fn assert_sortable<S: Roundtrip>(spec: &S, cases: u32, encode: impl Fn(i128) -> String) {
+    let size = spec.size();
+    run_cases(cases, (0..=mask(size), 0..=mask(size)), |(a, b)| {
+        let generated = (|| Ok::<_, Error>((spec.generate_id()?, spec.generate_id()?)))()
+            .map_err(|e| TestCaseError::fail(format!("generate failed: {}", e)))?;
+        for (a, b) in [(a as i128, b as i128), generated] {
+            let (first, second) = (encode(a), encode(b));
+            check((a as u128).cmp(&(b as u128)) == first.cmp(&second), || {
+                format!(
+                    "{:#x} and {:#x} encode to {:?} and {:?}, which sort the other way",
+                    a, b, first, second
+                )
+            })?;
+        }
+        Ok(())
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::presets::ulid;
+
+    proptest! {
+        #[test]
+        fn generated_ids_fit_their_spec((config, id) in spec_with_id()) {
+            let spec = config.build().unwrap();
+            let values = spec.decompose(id).unwrap();
+            for (value, segment) in values.iter().zip(&config.segments) {
+                if let crate::SegmentConfig::Constant { value: constant, .. } = segment {
+                    prop_assert_eq!(&Value::Integer(*constant), value);
+                }
+            }
+            prop_assert!(spec.verify(id).results[0].1.is_ok());
+        }
+    }
+
+    #[test]
+    fn properties_hold_for_valid_specs() {
+        let spec = ulid().config().build().unwrap();
+        assert_roundtrip(&spec, 64);
+        assert_sortable(&spec, 64, |id| encode_base32(id, 128));
+        assert_sortable(&spec, 64, encode_uuid);
+    }
+
+    #[test]
+    #[should_panic(expected = "sort the other way")]
+    fn decimal_strings_do_not_sort() {
+        let spec = ulid().config().build().unwrap();
+        assert_sortable(&spec, 64, |id| (id as u128).to_string());
+    }
+}
@@ -0,0 +1,96 @@
+//! [`SystemTime`] interop, for callers that want no date-time dependency of their own. Epochs
+//! convert from [`SystemTime`] and [`TimestampSegment`] decodes to it.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use time::OffsetDateTime;
+
+use crate::{DateTimeType, Error, TimestampSegmentAs};
+
+pub fn from_system_time(time: SystemTime) -> Result<OffsetDateTime, Error> {
+    let nanos = match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => i128::try_from(after.as_nanos()).map_err(|_| Error::Overflow)?,
+        Err(before) => i128::try_from(before.duration().as_nanos())
+            .map_err(|_| Error::Overflow)?
+            .checked_neg()
+            .ok_or(Error::Overflow)?,
+    };
+    Ok(OffsetDateTime::from_unix_timestamp_nanos(nanos)?)
+}
+
+/// Fails with [`Error::Overflow`] where the platform's `SystemTime` is narrower than `time`,
+/// e.g. before 1970 on some targets.
+pub fn to_system_time(datetime: OffsetDateTime) -> Result<SystemTime, Error> {
+    let nanos = datetime.unix_timestamp_nanos();
+    let offset = nanos.unsigned_abs();
+    let offset = Duration::new(
+        u64::try_from(offset / 1_000_000_000).map_err(|_| Error::Overflow)?,
+        (offset % 1_000_000_000) as u32,
+    );
+    if nanos < 0 {
+        UNIX_EPOCH.checked_sub(offset)
+    } else {
+        UNIX_EPOCH.checked_add(offset)
+    }
+    .ok_or(Error::Overflow)
+}
+
+/// A [`crate::TimestampSegment`] that decodes to [`SystemTime`].
+pub type TimestampSegment = TimestampSegmentAs<SystemTime>;
+
+impl DateTimeType for SystemTime {
+    fn from_time(datetime: OffsetDateTime) -> Result<Self, Error> {
+        to_system_time(datetime)
+    }
+
+    fn to_time(&self) -> Result<OffsetDateTime, Error> {
+        from_system_time(*self)
+    }
+
+    /// `time`'s latest date where the platform's `SystemTime` reaches it.
+    fn latest() -> Self {
+        let latest = time::Date::MAX.with_time(time::Time::MAX).assume_utc();
+        to_system_time(latest).unwrap_or(UNIX_EPOCH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RandomSegment, Spec2, TimestampUnit};
+
+    #[test]
+    fn round_trips_instants() {
+        let after = UNIX_EPOCH + Duration::new(1_684_326_645, 123_456_789);
+        let converted = from_system_time(after).unwrap();
+        assert_eq!(1_684_326_645_123_456_789, converted.unix_timestamp_nanos());
+        assert_eq!(after, to_system_time(converted).unwrap());
+
+        let before = UNIX_EPOCH - Duration::new(86_400, 500);
+        let converted = from_system_time(before).unwrap();
+        assert_eq!(-86_400_000_000_500, converted.unix_timestamp_nanos());
+        assert_eq!(before, to_system_time(converted).unwrap());
+    }
+
+    #[test]
+    fn rejects_times_out_of_range() {
+        let far = UNIX_EPOCH + Duration::from_secs(2_000_000 * 366 * 86_400);
+        assert!(matches!(from_system_time(far), Err(Error::Time(_))));
+    }
+
+    #[test]
+    fn decodes_to_system_time() {
+        let epoch = UNIX_EPOCH + Duration::from_secs(1_672_531_200);
+        let spec = Spec2::<i128, SystemTime, i128> {
+            segments: (
+                Box::new(TimestampSegment::new(48, TimestampUnit::Milliseconds, epoch).unwrap()),
+                Box::new(RandomSegment::new(80)),
+            ),
+        };
+        let (timestamp, _) = spec.decompose(spec.generate().unwrap()).unwrap();
+        let elapsed = SystemTime::now().duration_since(timestamp).unwrap();
+        assert!(elapsed < Duration::from_secs(2));
+        assert!(spec.segments.0.config().is_some());
+        assert_eq!(epoch, spec.segments.0.decode(0).unwrap());
+    }
+}
@@ -0,0 +1,170 @@
+use alloc::string::String;
+
+use crate::{mask, Error, SpecSegment};
+
+/// Two-way mapping between tenant names and the small integers IDs carry for them. Values must
+/// never be reassigned, or IDs minted before decode to the wrong tenant.
+pub trait TenantRegistry {
+    fn value(&self, name: &str) -> Option<u128>;
+    fn name(&self, value: u128) -> Option<&str>;
+}
+
+impl TenantRegistry for [(&str, u128)] {
+    fn value(&self, name: &str) -> Option<u128> {
+        self.iter()
+            .find(|(registered, _)| *registered == name)
+            .map(|(_, value)| *value)
+    }
+
+    fn name(&self, value: u128) -> Option<&str> {
+        self.iter()
+            .find(|(_, registered)| *registered == value)
+            .map(|(name, _)| *name)
+    }
+}
+
+impl<const N: usize> TenantRegistry for [(&str, u128); N] {
+    fn value(&self, name: &str) -> Option<u128> {
+        self.as_slice().value(name)
+    }
+
+    fn name(&self, value: u128) -> Option<&str> {
+        self.as_slice().name(value)
+    }
+}
+
+impl<T: TenantRegistry + ?Sized> TenantRegistry for &T {
+    fn value(&self, name: &str) -> Option<u128> {
+        (**self).value(name)
+    }
+
+    fn name(&self, value: u128) -> Option<&str> {
+        (**self).name(value)
+    }
+}
+
+/// Encodes the value `registry` assigns to one tenant and decodes any registered value back to
+/// its tenant's name, so multi-tenant IDs stay compact yet say whose they are when decomposed.
+#[derive(Debug, Clone, Copy)]
+pub struct TenantSegment<T> {
+    size: u8,
+    registry: T,
+    value: u128,
+}
+
+impl<T: TenantRegistry> TenantSegment<T> {
+    /// Fails if `tenant` is not registered or its value doesn't fit in `size` bits.
+    pub fn new(size: u8, registry: T, tenant: &str) -> Result<Self, Error> {
+        let value = registry.value(tenant).ok_or_else(|| Error::UnknownName {
+            name: tenant.into(),
+        })?;
+        if value & !mask(size) != 0 {
+            return Err(Error::ValueTooLarge { value, size });
+        }
+        Ok(Self {
+            size,
+            registry,
+            value,
+        })
+    }
+
+    /// The name of the tenant generated IDs carry.
+    pub fn tenant(&self) -> &str {
+        self.registry.name(self.value).unwrap_or_default()
+    }
+
+    pub fn registry(&self) -> &T {
+        &self.registry
+    }
+}
+
+impl<T: TenantRegistry + Clone> SpecSegment<i128, String> for TenantSegment<T> {
+    fn size(&self) -> u8 {
+        self.size
+    }
+
+    fn upper_bound(&self) -> String {
+        self.tenant().into()
+    }
+
+    fn encode(&self) -> Result<i128, Error> {
+        Ok(self.value as i128)
+    }
+
+    fn decode(&self, encoded: i128) -> Result<String, Error> {
+        let value = encoded as u128;
+        self.registry
+            .name(value)
+            .map(String::from)
+            .ok_or(Error::UnknownValue { value })
+    }
+
+    fn verify(&self, encoded: i128) -> Result<(), Error> {
+        if self.registry.name(encoded as u128).is_none() {
+            return Err(Error::Verification {
+                reason: "tenant is not registered",
+            });
+        }
+        Ok(())
+    }
+
+    crate::clone_box!(i128, String);
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::{Labeled, Layout, RandomSegment, Spec2};
+
+    static TENANTS: [(&str, u128); 3] = [("acme", 1), ("globex", 2), ("initech", 300)];
+
+    #[test]
+    fn decodes_to_tenant_names() {
+        let spec = Spec2::<i128, String, i128> {
+            segments: (
+                Box::new(TenantSegment::new(8, &TENANTS, "globex").unwrap()),
+                Box::new(RandomSegment::new(56)),
+            ),
+        };
+        let id = spec.generate().unwrap();
+        assert_eq!(2, id >> 56);
+        assert_eq!("globex", spec.decompose(id).unwrap().0);
+        assert_eq!("acme", spec.decompose(1 << 56 | id & 0xff).unwrap().0);
+        assert!(spec.verify(1 << 56).is_valid());
+
+        let unknown = 7 << 56;
+        assert!(!spec.verify(unknown).is_valid());
+        assert!(matches!(
+            spec.decompose(unknown),
+            Err(Error::InSegment { index: 0, source, .. })
+                if matches!(*source, Error::UnknownValue { value: 7 })
+        ));
+    }
+
+    #[test]
+    fn rejects_unregistered_and_oversized_tenants() {
+        assert_eq!(
+            "\"umbrella\" is not registered",
+            TenantSegment::new(8, &TENANTS, "umbrella")
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(matches!(
+            TenantSegment::new(8, &TENANTS, "initech"),
+            Err(Error::ValueTooLarge { value: 300, .. })
+        ));
+        let segment = Labeled::new(
+            "tenant",
+            TenantSegment::new(16, TENANTS, "initech").unwrap(),
+        );
+        assert_eq!("initech", segment.segment().tenant());
+        assert_eq!(300, segment.encode().unwrap());
+
+        let local = [("hooli", 5)];
+        let segment = TenantSegment::new(8, &local, "hooli").unwrap();
+        assert_eq!(5, segment.clone_box().unwrap().encode().unwrap());
+    }
+}
@@ -0,0 +1,808 @@
+//! Deterministic stand-ins for the clock and random number generator, for tests asserting
+//! exact ID bits, a clock misbehaving to a script, segments failing on purpose, a harness
+//! checking a spec for collisions at scale, and assertions on the statistical spread of
+//! generated bits.
+
+use std::boxed::Box;
+use std::collections::{HashSet, VecDeque};
+use std::string::String;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::vec::Vec;
+
+use rand::Rng;
+use time::{Duration, OffsetDateTime};
+
+use crate::partition::mix;
+use crate::{mask, ClonedSegment, Error, Layout, SegmentConfig, SpecSegment};
+
+/// A clock that only moves when told to, for
+/// [`TimestampSegment::with_clock`](crate::TimestampSegment::with_clock).
+///
+/// Clocks are passed as function pointers, so declare one `static` per test and pass a function
+/// reading it, such as `|| CLOCK.now()`; tests sharing a clock would move each other's time.
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<OffsetDateTime>,
+}
+
+impl MockClock {
+    pub const fn new(now: OffsetDateTime) -> Self {
+        Self {
+            now: Mutex::new(now),
+        }
+    }
+
+    pub fn now(&self) -> OffsetDateTime {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn set(&self, now: OffsetDateTime) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) = now;
+    }
+
+    /// Moves the clock by `by`, which may be negative to simulate a clock stepping back.
+    /// Returns the new time.
+    pub fn advance(&self, by: Duration) -> OffsetDateTime {
+        let mut now = self.now.lock().unwrap_or_else(PoisonError::into_inner);
+        *now += by;
+        *now
+    }
+}
+
+/// How a [`ScriptedClock`] moves on one read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockStep {
+    /// Moves forward by the duration, as a healthy clock would between reads.
+    Advance(Duration),
+    /// Reads the same time again this many times, as a clock being slewed to a halt does.
+    Stall(u32),
+    /// Steps by the duration, backwards if it is negative, as NTP does on large corrections.
+    Jump(Duration),
+}
+
+#[derive(Debug)]
+struct Script {
+    now: OffsetDateTime,
+    steps: VecDeque<ClockStep>,
+    tick: Duration,
+}
+
+/// A clock following a script of [`ClockStep`]s, one per read, then advancing by a fixed tick
+/// once the script has run out; for testing how generators cope with clocks jumping back,
+/// stalling and leaping forward. Declare it as a `static` and pass `|| CLOCK.now()`.
+#[derive(Debug)]
+pub struct ScriptedClock {
+    script: Mutex<Script>,
+}
+
+impl ScriptedClock {
+    /// Starts at `start`, which the first read returns unless a step moves it first.
+    pub const fn new(start: OffsetDateTime, tick: Duration) -> Self {
+        Self {
+            script: Mutex::new(Script {
+                now: start,
+                steps: VecDeque::new(),
+                tick,
+            }),
+        }
+    }
+
+    /// Appends steps to the script, to apply to the reads after those scripted before.
+    pub fn push(&self, steps: impl IntoIterator<Item = ClockStep>) {
+        self.script
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .steps
+            .extend(steps);
+    }
+
+    pub fn now(&self) -> OffsetDateTime {
+        let mut script = self.script.lock().unwrap_or_else(PoisonError::into_inner);
+        let step = match script.steps.front_mut() {
+            Some(ClockStep::Stall(reads)) if *reads > 1 => {
+                *reads -= 1;
+                return script.now;
+            }
+            Some(_) => script.steps.pop_front(),
+            None => None,
+        };
+        let by = match step {
+            Some(ClockStep::Advance(by)) | Some(ClockStep::Jump(by)) => by,
+            Some(ClockStep::Stall(_)) => Duration::ZERO,
+            None => script.tick,
+        };
+        script.now += by;
+        script.now
+    }
+}
+
+/// A random number generator replaying scripted values, for
+/// [`RandomSegment::with_rng`](crate::RandomSegment::with_rng), so tests can force specific
+/// random bits or collisions. Declare it as a `static` and pass `|bytes| RNG.fill(bytes)`.
+#[derive(Debug)]
+pub struct MockRng {
+    values: Mutex<VecDeque<u128>>,
+    requests: Mutex<Vec<usize>>,
+}
+
+impl MockRng {
+    pub const fn new() -> Self {
+        Self {
+            values: Mutex::new(VecDeque::new()),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Queues values to hand out after the ones queued before.
+    pub fn push(&self, values: impl IntoIterator<Item = u128>) {
+        self.values
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extend(values);
+    }
+
+    /// Writes the next queued value big-endian into the end of `bytes`, zeroing the rest;
+    /// a segment masks it down to its width. Fails with [`Error::Exhausted`] once the script
+    /// has run out.
+    pub fn fill(&self, bytes: &mut [u8]) -> Result<(), Error> {
+        self.requests
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(bytes.len());
+        let value = self
+            .values
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop_front()
+            .ok_or(Error::Exhausted)?;
+        let value = value.to_be_bytes();
+        let (padding, tail) = bytes.split_at_mut(bytes.len().saturating_sub(value.len()));
+        padding.fill(0);
+        tail.copy_from_slice(&value[value.len() - tail.len()..]);
+        Ok(())
+    }
+
+    /// Length of every buffer passed to [`fill`](Self::fill) so far, in order.
+    pub fn requests(&self) -> Vec<usize> {
+        self.requests
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.values
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+}
+
+impl Default for MockRng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An error a [`FaultySegment`] injects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    Exhausted,
+    ClockRegression(Duration),
+}
+
+impl Fault {
+    fn error(self) -> Error {
+        match self {
+            Fault::Exhausted => Error::Exhausted,
+            Fault::ClockRegression(by) => Error::ClockRegression { by },
+        }
+    }
+}
+
+/// Wraps a segment so encoding fails with a [`Fault`] at a given rate, or takes longer than it
+/// should, for testing how services retry or fall back when ID generation errors or stalls.
+/// Everything but [`encode`](SpecSegment::encode) and
+/// [`encode_with`](SpecSegment::encode_with) is passed through unchanged.
+#[derive(Debug)]
+pub struct FaultySegment<S> {
+    inner: S,
+    fault: Fault,
+    probability: f64,
+    latency: std::time::Duration,
+    injected: AtomicU64,
+}
+
+impl<S> FaultySegment<S> {
+    /// Behaves exactly like `inner` until configured otherwise.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            fault: Fault::Exhausted,
+            probability: 0.0,
+            latency: std::time::Duration::ZERO,
+            injected: AtomicU64::new(0),
+        }
+    }
+
+    /// Fails each encoding with `fault` with the given probability, from 0 to 1.
+    pub fn with_failures(mut self, fault: Fault, probability: f64) -> Self {
+        self.fault = fault;
+        self.probability = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sleeps for `latency` before each encoding, failed or not.
+    pub fn with_latency(mut self, latency: std::time::Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Sleeps and fails as configured, before the inner segment encodes.
+    fn inject(&self) -> Result<(), Error> {
+        if !self.latency.is_zero() {
+            thread::sleep(self.latency);
+        }
+        if self.probability > 0.0 && rand::thread_rng().gen_bool(self.probability) {
+            self.injected.fetch_add(1, Ordering::Relaxed);
+            return Err(self.fault.error());
+        }
+        Ok(())
+    }
+
+    /// Number of failures injected so far.
+    pub fn injected(&self) -> u64 {
+        self.injected.load(Ordering::Relaxed)
+    }
+}
+
+/// Clones start with no injected failures counted.
+impl<S: Clone> Clone for FaultySegment<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            fault: self.fault,
+            probability: self.probability,
+            latency: self.latency,
+            injected: AtomicU64::new(0),
+        }
+    }
+}
+
+impl<S: SpecSegment<i128, R>, R> SpecSegment<i128, R> for FaultySegment<S> {
+    fn size(&self) -> u8 {
+        self.inner.size()
+    }
+
+    fn upper_bound(&self) -> R {
+        self.inner.upper_bound()
+    }
+
+    fn encode(&self) -> Result<i128, Error> {
+        self.inject()?;
+        self.inner.encode()
+    }
+
+    fn encode_with(&self, key: &[u8]) -> Result<i128, Error> {
+        self.inject()?;
+        self.inner.encode_with(key)
+    }
+
+    fn decode(&self, encoded: i128) -> Result<R, Error> {
+        self.inner.decode(encoded)
+    }
+
+    fn verify(&self, encoded: i128) -> Result<(), Error> {
+        self.inner.verify(encoded)
+    }
+
+    fn fixed(&self) -> Option<i128> {
+        self.inner.fixed()
+    }
+
+    fn exhaustion_date(&self) -> Option<OffsetDateTime> {
+        self.inner.exhaustion_date()
+    }
+
+    fn config(&self) -> Option<SegmentConfig> {
+        self.inner.config()
+    }
+
+    fn keys_partition(&self) -> bool {
+        self.inner.keys_partition()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.inner.label()
+    }
+
+    fn clone_box<'a>(&self) -> Option<Box<dyn SpecSegment<i128, R> + 'a>>
+    where
+        Self: 'a,
+        R: 'a,
+    {
+        Some(Box::new(FaultySegment {
+            inner: ClonedSegment(self.inner.clone_box()?),
+            fault: self.fault,
+            probability: self.probability,
+            latency: self.latency,
+            injected: AtomicU64::new(0),
+        }))
+    }
+}
+
+/// Outcome of a [`CollisionHarness`] run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollisionReport {
+    pub generated: u64,
+    /// IDs seen more than once, each listed once per repeat.
+    pub duplicates: Vec<i128>,
+    /// Whether every ID was kept, so `duplicates` holds true duplicates only. Otherwise a Bloom
+    /// filter was used and each duplicate may instead be a false positive.
+    pub exact: bool,
+    /// Chance of any single ID being reported as a false positive by the end of the run.
+    pub false_positive_rate: f64,
+}
+
+/// Generates IDs on several threads and reports those generated more than once. Runs keep
+/// every ID while that fits in the memory budget and fall back to a Bloom filter of that size
+/// otherwise.
+#[derive(Debug, Clone)]
+pub struct CollisionHarness {
+    ids: u64,
+    threads: usize,
+    memory: usize,
+}
+
+/// Rough cost of one ID in a `HashSet<i128>`, including spare capacity.
+const BYTES_PER_ID: u64 = 40;
+const SHARDS: usize = 64;
+const CHUNK: usize = 1024;
+
+impl CollisionHarness {
+    /// Generates `ids` IDs on as many threads as the machine runs in parallel, within 256 MiB.
+    pub fn new(ids: u64) -> Self {
+        Self {
+            ids,
+            threads: thread::available_parallelism().map_or(1, usize::from),
+            memory: 256 << 20,
+        }
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Memory in bytes the run may use to remember IDs.
+    pub fn with_memory(mut self, memory: usize) -> Self {
+        self.memory = memory.max(8);
+        self
+    }
+
+    pub fn run(
+        &self,
+        generate: impl Fn() -> Result<i128, Error> + Sync,
+    ) -> Result<CollisionReport, Error> {
+        let seen: Box<dyn Seen> = if self.ids.saturating_mul(BYTES_PER_ID) <= self.memory as u64 {
+            Box::new(Exact(
+                (0..SHARDS).map(|_| Mutex::new(HashSet::new())).collect(),
+            ))
+        } else {
+            Box::new(Bloom::new(self.memory, self.ids))
+        };
+        let next = AtomicU64::new(0);
+        let duplicates = Mutex::new(Vec::new());
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut ids = Vec::with_capacity(CHUNK);
+                        loop {
+                            let start = next.fetch_add(CHUNK as u64, Ordering::Relaxed);
+                            if start >= self.ids {
+                                return Ok(());
+                            }
+                            ids.clear();
+                            for _ in start..self.ids.min(start + CHUNK as u64) {
+                                ids.push(generate()?);
+                            }
+                            let found = seen.insert(&ids);
+                            if !found.is_empty() {
+                                duplicates
+                                    .lock()
+                                    .unwrap_or_else(PoisonError::into_inner)
+                                    .extend(found);
+                            }
+                        }
+                    })
+                })
+                .collect();
+            workers.into_iter().try_for_each(|worker| {
+                worker.join().unwrap_or(Err(Error::Unsupported {
+                    reason: "a collision harness thread panicked",
+                }))
+            })
+        })?;
+        Ok(CollisionReport {
+            generated: self.ids,
+            duplicates: duplicates
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner),
+            exact: seen.false_positive_rate() == 0.0,
+            false_positive_rate: seen.false_positive_rate(),
+        })
+    }
+}
+
+trait Seen: Sync {
+    /// Remembers `ids`, returning those remembered before.
+    fn insert(&self, ids: &[i128]) -> Vec<i128>;
+    fn false_positive_rate(&self) -> f64;
+}
+
+fn hash(id: i128) -> u64 {
+    mix(id as u64 ^ mix((id >> 64) as u64))
+}
+
+struct Exact(Vec<Mutex<HashSet<i128>>>);
+
+impl Seen for Exact {
+    fn insert(&self, ids: &[i128]) -> Vec<i128> {
+        let mut duplicates = Vec::new();
+        for id in ids {
+            let shard = hash(*id) as usize % SHARDS;
+            if !self.0[shard]
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .insert(*id)
+            {
+                duplicates.push(*id);
+            }
+        }
+        duplicates
+    }
+
+    fn false_positive_rate(&self) -> f64 {
+        0.0
+    }
+}
+
+struct Bloom {
+    words: Vec<AtomicU64>,
+    hashes: u32,
+    expected: u64,
+}
+
+impl Bloom {
+    /// Sized to `memory` bytes, with the number of hashes minimizing false positives after
+    /// `expected` insertions.
+    fn new(memory: usize, expected: u64) -> Self {
+        let words = (memory / 8).max(1);
+        let bits_per_id = (words * 64) as f64 / expected.max(1) as f64;
+        Self {
+            words: (0..words).map(|_| AtomicU64::new(0)).collect(),
+            hashes: (bits_per_id * core::f64::consts::LN_2)
+                .round()
+                .clamp(1.0, 16.0) as u32,
+            expected,
+        }
+    }
+}
+
+impl Seen for Bloom {
+    fn insert(&self, ids: &[i128]) -> Vec<i128> {
+        let bits = self.words.len() as u64 * 64;
+        let mut duplicates = Vec::new();
+        for id in ids {
+            // Double hashing: the k bit positions are h1 + i * h2.
+            let first = hash(*id);
+            let second = mix(first) | 1;
+            let mut present = true;
+            for index in 0..u64::from(self.hashes) {
+                let bit = first.wrapping_add(index.wrapping_mul(second)) % bits;
+                let mask = 1 << (bit % 64);
+                let previous = self.words[(bit / 64) as usize].fetch_or(mask, Ordering::Relaxed);
+                present &= previous & mask != 0;
+            }
+            if present {
+                duplicates.push(*id);
+            }
+        }
+        duplicates
+    }
+
+    fn false_positive_rate(&self) -> f64 {
+        let bits = (self.words.len() * 64) as f64;
+        let hashes = f64::from(self.hashes);
+        (1.0 - (-hashes * self.expected as f64 / bits).exp()).powf(hashes)
+    }
+}
+
+/// Share of `samples` generated IDs with each bit set, indexed from the least significant bit.
+pub fn bit_frequencies(
+    samples: usize,
+    mut generate: impl FnMut() -> Result<i128, Error>,
+) -> Result<[f64; 128], Error> {
+    let mut ones = [0u64; 128];
+    for _ in 0..samples {
+        let id = generate()? as u128;
+        for (bit, count) in ones.iter_mut().enumerate() {
+            *count += (id >> bit & 1) as u64;
+        }
+    }
+    Ok(ones.map(|count| count as f64 / samples.max(1) as f64))
+}
+
+/// Asserts that every bit of the random segments of `layout` is set in `0.5 ± tolerance` of
+/// `samples` generated IDs, and that constant bits never change, catching masks that silently
+/// drop entropy. Timestamp and custom segments are not checked.
+#[allow(clippy::panic)]
+pub fn assert_bit_frequencies(
+    layout: &dyn Layout,
+    samples: usize,
+    tolerance: f64,
+    generate: impl FnMut() -> Result<i128, Error>,
+) {
+    let frequencies = match bit_frequencies(samples, generate) {
+        Ok(frequencies) => frequencies,
+        Err(error) => panic!("generating a sample failed: {}", error),
+    };
+    let mut failures = String::new();
+    let mut offset = u32::from(layout.size());
+    for (index, segment) in layout.segments().iter().enumerate() {
+        offset -= u32::from(segment.size());
+        let expected: Box<dyn Fn(u32) -> f64> = match (segment.config(), segment.fixed()) {
+            (Some(SegmentConfig::Random { .. }), _) => Box::new(|_| 0.5),
+            (_, Some(value)) => Box::new(move |bit| (value >> bit & 1) as f64),
+            _ => continue,
+        };
+        for bit in 0..u32::from(segment.size()) {
+            let actual = frequencies[(offset + bit) as usize];
+            let expected = expected(bit);
+            if (actual - expected).abs() > tolerance {
+                failures += &format!(
+                    "\n  segment {} bit {}: set in {:.3} of IDs, expected {} ± {}",
+                    index, bit, actual, expected, tolerance
+                );
+            }
+        }
+    }
+    if !failures.is_empty() {
+        panic!("bit frequencies out of tolerance:{}", failures);
+    }
+}
+
+/// Asserts that the values of segment `index` of `layout` spread evenly over `buckets` equal
+/// ranges, each holding `1 / buckets ± tolerance` of `samples` generated IDs.
+#[allow(clippy::panic)]
+pub fn assert_segment_distribution(
+    layout: &dyn Layout,
+    index: usize,
+    buckets: usize,
+    samples: usize,
+    tolerance: f64,
+    mut generate: impl FnMut() -> Result<i128, Error>,
+) {
+    let sizes = layout.sizes();
+    let Some(size) = sizes.get(index).copied() else {
+        panic!("the layout has no segment {}", index);
+    };
+    let shift: u32 = sizes[index + 1..].iter().map(|size| u32::from(*size)).sum();
+    let buckets = buckets.clamp(1, 1 << size.min(16));
+    let mut counts = vec![0u64; buckets];
+    for _ in 0..samples {
+        let id = match generate() {
+            Ok(id) => id as u128,
+            Err(error) => panic!("generating a sample failed: {}", error),
+        };
+        let value = id.checked_shr(shift).unwrap_or(0) & mask(size);
+        let bucket = value as f64 / (mask(size) as f64 + 1.0) * buckets as f64;
+        counts[(bucket as usize).min(buckets - 1)] += 1;
+    }
+    let expected = 1.0 / buckets as f64;
+    let mut failures = String::new();
+    for (bucket, count) in counts.iter().enumerate() {
+        let actual = *count as f64 / samples.max(1) as f64;
+        if (actual - expected).abs() > tolerance {
+            failures += &format!(
+                "\n  bucket {}: {:.4} of values, expected {:.4} ± {}",
+                bucket, actual, expected, tolerance
+            );
+        }
+    }
+    if !failures.is_empty() {
+        panic!("values of segment {} out of tolerance:{}", index, failures);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ConstantSegment, RandomSegment, ShardSegment, Spec2, SpecSegment, TimestampSegment,
+        TimestampUnit,
+    };
+
+    #[test]
+    fn moves_only_when_told() {
+        static CLOCK: MockClock = MockClock::new(OffsetDateTime::UNIX_EPOCH);
+        let spec = Spec2::<i128, OffsetDateTime, i128> {
+            segments: (
+                Box::new(
+                    TimestampSegment::new(8, TimestampUnit::Seconds, OffsetDateTime::UNIX_EPOCH)
+                        .with_clock(|| CLOCK.now()),
+                ),
+                Box::new(ConstantSegment::new(8, 1)),
+            ),
+        };
+        assert_eq!(1, spec.generate().unwrap());
+
+        CLOCK.set(OffsetDateTime::UNIX_EPOCH + Duration::seconds(5) - Duration::NANOSECOND);
+        assert_eq!(4 << 8 | 1, spec.generate().unwrap());
+        CLOCK.advance(Duration::NANOSECOND);
+        assert_eq!(5 << 8 | 1, spec.generate().unwrap());
+
+        CLOCK.set(OffsetDateTime::UNIX_EPOCH + Duration::seconds(255));
+        assert_eq!(255 << 8 | 1, spec.generate().unwrap());
+        CLOCK.advance(Duration::seconds(1));
+        assert_eq!("value_too_large", spec.generate().unwrap_err().code());
+
+        assert_eq!(
+            OffsetDateTime::UNIX_EPOCH + Duration::seconds(246),
+            CLOCK.advance(Duration::seconds(-10))
+        );
+    }
+
+    #[test]
+    fn replays_scripted_values() {
+        static RNG: MockRng = MockRng::new();
+        let segment = RandomSegment::with_rng(12, |bytes| RNG.fill(bytes));
+        RNG.push([0xabc, 0xabc, u128::MAX]);
+        assert_eq!(0xabc, segment.encode().unwrap());
+        assert_eq!(0xabc, segment.encode().unwrap());
+        assert_eq!(0xfff, segment.encode().unwrap());
+        assert!(matches!(segment.encode(), Err(Error::Exhausted)));
+        assert_eq!(vec![16; 4], RNG.requests());
+        assert_eq!(0, RNG.remaining());
+
+        let rng = MockRng::new();
+        rng.push([0x0102, 0x0304]);
+        let mut bytes = [0xff; 3];
+        rng.fill(&mut bytes).unwrap();
+        assert_eq!([0, 1, 2], bytes);
+        let mut bytes = [0xff; 18];
+        rng.fill(&mut bytes).unwrap();
+        assert_eq!([0, 0, 3, 4], [bytes[0], bytes[1], bytes[16], bytes[17]]);
+        assert_eq!(vec![3, 18], rng.requests());
+    }
+
+    #[test]
+    fn finds_collisions() {
+        let next = AtomicU64::new(0);
+        // Every value comes up twice.
+        let generate = || Ok(i128::from(next.fetch_add(1, Ordering::Relaxed) / 2));
+        let report = CollisionHarness::new(10_000)
+            .with_threads(4)
+            .run(generate)
+            .unwrap();
+        assert_eq!(10_000, report.generated);
+        assert!(report.exact);
+        assert_eq!(5_000, report.duplicates.len());
+
+        next.store(0, Ordering::Relaxed);
+        let report = CollisionHarness::new(10_000)
+            .with_threads(4)
+            .with_memory(16 << 10)
+            .run(generate)
+            .unwrap();
+        assert!(!report.exact);
+        assert!(report.false_positive_rate < 0.01);
+        assert!((5_000..5_100).contains(&report.duplicates.len()));
+    }
+
+    #[test]
+    fn propagates_errors() {
+        let report = CollisionHarness::new(10).run(|| Err(Error::Exhausted));
+        assert!(matches!(report, Err(Error::Exhausted)));
+    }
+
+    #[test]
+    fn checks_bit_spread() {
+        let spec = crate::presets::uuid_v7().config().build().unwrap();
+        assert_bit_frequencies(&spec, 4_000, 0.05, || spec.generate());
+        assert_segment_distribution(&spec, 4, 8, 4_000, 0.03, || spec.generate());
+        assert_segment_distribution(&spec, 2, 16, 4_000, 0.03, || spec.generate());
+
+        // A mask one bit short leaves the top random bit clear.
+        let halved = || spec.generate().map(|id| id & !(1 << 61));
+        let failure = std::panic::catch_unwind(|| {
+            assert_bit_frequencies(&spec, 4_000, 0.05, halved);
+        })
+        .unwrap_err();
+        let message = failure.downcast_ref::<String>().unwrap();
+        assert!(message.contains("segment 4 bit 61: set in 0.000 of IDs, expected 0.5"));
+        assert!(std::panic::catch_unwind(|| {
+            assert_segment_distribution(&spec, 4, 8, 4_000, 0.03, halved);
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn follows_the_script() {
+        let start = OffsetDateTime::UNIX_EPOCH;
+        let ms = Duration::milliseconds;
+        let clock = ScriptedClock::new(start, ms(1));
+        clock.push([
+            ClockStep::Stall(2),
+            ClockStep::Jump(ms(-5)),
+            ClockStep::Advance(ms(2)),
+            ClockStep::Jump(ms(1000)),
+        ]);
+        let reads: Vec<i128> = (0..6)
+            .map(|_| (clock.now() - start).whole_milliseconds())
+            .collect();
+        assert_eq!(vec![0, 0, -5, -3, 997, 998], reads);
+    }
+
+    #[cfg(target_has_atomic = "64")]
+    #[test]
+    fn snowflakes_stay_ordered_when_the_clock_steps_back() {
+        static CLOCK: ScriptedClock =
+            ScriptedClock::new(OffsetDateTime::UNIX_EPOCH, Duration::milliseconds(1));
+        CLOCK.push([ClockStep::Jump(Duration::seconds(10))]);
+        CLOCK.push([ClockStep::Advance(Duration::microseconds(100)); 100]);
+        CLOCK.push([
+            ClockStep::Jump(Duration::seconds(-2)),
+            ClockStep::Stall(100),
+        ]);
+        let epoch = OffsetDateTime::UNIX_EPOCH.date();
+        let spec = crate::presets::snowflake(epoch, 1)
+            .unwrap()
+            .with_clock(|| CLOCK.now());
+        let ids: Vec<i128> = (0..5_000).map(|_| spec.generate().unwrap()).collect();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn faulty_segments_fail_on_demand() {
+        let spec = Spec2::<i128, i128, i128> {
+            segments: (
+                Box::new(ConstantSegment::new(8, 1)),
+                Box::new(
+                    FaultySegment::new(RandomSegment::new(8))
+                        .with_failures(Fault::ClockRegression(Duration::SECOND), 1.0),
+                ),
+            ),
+        };
+        assert!(matches!(
+            spec.generate(),
+            Err(Error::InSegment { index: 1, source, .. }) if matches!(*source, Error::ClockRegression { .. })
+        ));
+
+        let segment = FaultySegment::new(ConstantSegment::new(8, 1))
+            .with_failures(Fault::Exhausted, 0.5)
+            .with_latency(std::time::Duration::from_millis(1));
+        let started = std::time::Instant::now();
+        let failed = (0..200).filter(|_| segment.encode().is_err()).count();
+        assert!(started.elapsed() >= std::time::Duration::from_millis(200));
+        assert_eq!(failed as u64, segment.injected());
+        assert!((50..150).contains(&failed), "{}", failed);
+        assert_eq!(Some(1), segment.fixed());
+
+        let shard = ShardSegment::new(8);
+        let segment = FaultySegment::new(shard);
+        assert_eq!(
+            shard.shard(b"user-7") as i128,
+            segment.encode_with(b"user-7").unwrap()
+        );
+        let segment = segment.with_failures(Fault::Exhausted, 1.0);
+        assert!(matches!(
+            segment.encode_with(b"user-7"),
+            Err(Error::Exhausted)
+        ));
+        assert_eq!(1, segment.injected());
+    }
+}
@@ -0,0 +1,148 @@
+//! `tracing` instrumentation, so operators can see why generation latency spiked: a debug span
+//! around every generation, and debug events for its outcome, for sequences running ahead of a
+//! clock that stepped back or ran out of values, and for IDs that failed to parse; plus an info
+//! event for [`Generator::log_configuration`](crate::Generator::log_configuration). Parse
+//! events carry the input's length, never the input, which may be a secret token.
+
+use tracing::Span;
+
+use crate::Error;
+
+pub(crate) fn generate_span(segments: usize) -> Span {
+    tracing::debug_span!("utid.generate", segments)
+}
+
+pub(crate) fn configuration(configuration: &str) {
+    tracing::info!(configuration, "utid configuration");
+}
+
+pub(crate) fn generated(result: &Result<i128, Error>) {
+    match result {
+        Ok(id) => tracing::debug!(id, "generated ID"),
+        Err(error) => tracing::debug!(code = error.code(), %error, "ID generation failed"),
+    }
+}
+
+/// The clock read `behind` ticks earlier than the last tick handed out.
+pub(crate) fn clock_behind(tick: u64, behind: u64) {
+    tracing::debug!(
+        tick,
+        behind,
+        "clock behind the last tick, continuing from it"
+    );
+}
+
+/// The counter ran out within `tick`, so the sequence borrows the next one.
+pub(crate) fn counter_overflow(tick: u64) {
+    tracing::debug!(tick, "sequence exhausted, borrowing the next tick");
+}
+
+pub(crate) fn parsed(encoding: &'static str, input: &str, result: &Result<i128, Error>) {
+    if let Err(error) = result {
+        tracing::debug!(
+            encoding,
+            len = input.len(),
+            code = error.code(),
+            %error,
+            "ID failed to parse"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::vec::Vec;
+
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    use super::*;
+    use crate::encoding::decode_base32;
+    use crate::{ConstantSegment, Generator, RandomSegment, Sequence, Spec2};
+
+    #[derive(Default)]
+    struct Counts {
+        spans: AtomicUsize,
+        messages: std::sync::Mutex<Vec<std::string::String>>,
+    }
+
+    /// Counts spans and records the message of each event.
+    struct Counter(Arc<Counts>);
+
+    struct Message<'a>(&'a mut std::string::String);
+
+    impl tracing::field::Visit for Message<'_> {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn core::fmt::Debug) {
+            if field.name() == "message" {
+                *self.0 = std::format!("{:?}", value);
+            }
+        }
+    }
+
+    impl Subscriber for Counter {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(self.0.spans.fetch_add(1, Ordering::Relaxed) as u64 + 1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut message = std::string::String::new();
+            event.record(&mut Message(&mut message));
+            self.0.messages.lock().unwrap().push(message);
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn traces_generation_sequences_and_parsing() {
+        let counts = Arc::new(Counts::default());
+        tracing::subscriber::with_default(Counter(counts.clone()), || {
+            let spec = |fill| Spec2::<i128, i128, i128> {
+                segments: (
+                    Box::new(ConstantSegment::new(8, 7)),
+                    Box::new(RandomSegment::with_rng(8, fill)),
+                ),
+            };
+            Generator::new(spec(|_| Ok(())))
+                .unwrap()
+                .generate()
+                .unwrap();
+            Generator::new(spec(|_| Err(Error::Exhausted)))
+                .unwrap()
+                .generate()
+                .unwrap_err();
+
+            let sequence = Sequence::new(1);
+            sequence.next(5).unwrap();
+            sequence.next(5).unwrap();
+            sequence.next(5).unwrap();
+            sequence.next(3).unwrap();
+
+            decode_base32("0U").unwrap_err();
+            decode_base32("01").unwrap();
+        });
+        assert_eq!(2, counts.spans.load(Ordering::Relaxed));
+        assert_eq!(
+            [
+                "generated ID",
+                "ID generation failed",
+                "sequence exhausted, borrowing the next tick",
+                "clock behind the last tick, continuing from it",
+                "ID failed to parse",
+            ][..],
+            counts.messages.lock().unwrap()[..]
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::any::{type_name, TypeId};
+use core::borrow::Borrow;
+
+use crate::{mask, Error, SpecSegment};
+
+/// A Rust type and the tag IDs of its entities carry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypeEntry {
+    pub tag: u128,
+    /// The type's [`type_name`], for logs only: it is not guaranteed to be stable.
+    pub name: &'static str,
+    type_id: TypeId,
+}
+
+impl TypeEntry {
+    pub fn is<T: 'static>(&self) -> bool {
+        self.type_id == TypeId::of::<T>()
+    }
+
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+}
+
+/// Entity tags per Rust type, e.g. `Order` → 3, for polymorphic "global ID" schemes such as
+/// Relay node IDs: any ID tells which type it belongs to. Tags must never be reassigned.
+#[derive(Debug, Clone, Default)]
+pub struct TypeTags {
+    entries: Vec<TypeEntry>,
+}
+
+impl TypeTags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fails if `T` or `tag` is registered already.
+    pub fn register<T: 'static>(mut self, tag: u128) -> Result<Self, Error> {
+        if self.tag::<T>().is_some() {
+            return Err(Error::AlreadyRegistered {
+                name: type_name::<T>().to_string(),
+            });
+        }
+        if self.get(tag).is_some() {
+            return Err(Error::AlreadyRegistered {
+                name: format!("tag {}", tag),
+            });
+        }
+        self.entries.push(TypeEntry {
+            tag,
+            name: type_name::<T>(),
+            type_id: TypeId::of::<T>(),
+        });
+        Ok(self)
+    }
+
+    pub fn tag<T: 'static>(&self) -> Option<u128> {
+        self.entries
+            .iter()
+            .find(|entry| entry.is::<T>())
+            .map(|entry| entry.tag)
+    }
+
+    /// The type `tag` is registered for.
+    pub fn get(&self, tag: u128) -> Option<&TypeEntry> {
+        self.entries.iter().find(|entry| entry.tag == tag)
+    }
+}
+
+/// Encodes the tag `tags` registers for one Rust type and decodes any registered tag back to its
+/// [`TypeEntry`], so a decomposed ID tells which type it belongs to. `T` is typically a
+/// `&TypeTags` or an `Arc<TypeTags>` shared by the segments of every entity type.
+#[derive(Debug, Clone)]
+pub struct TypeTagSegment<T> {
+    size: u8,
+    tags: T,
+    entry: TypeEntry,
+}
+
+impl<T: Borrow<TypeTags>> TypeTagSegment<T> {
+    /// Fails if `E` is not registered or its tag doesn't fit in `size` bits.
+    pub fn new<E: 'static>(size: u8, tags: T) -> Result<Self, Error> {
+        let entry = tags
+            .borrow()
+            .tag::<E>()
+            .and_then(|tag| tags.borrow().get(tag))
+            .copied()
+            .ok_or_else(|| Error::UnknownName {
+                name: type_name::<E>().to_string(),
+            })?;
+        if entry.tag & !mask(size) != 0 {
+            return Err(Error::ValueTooLarge {
+                value: entry.tag,
+                size,
+            });
+        }
+        Ok(Self { size, tags, entry })
+    }
+
+    /// The type generated IDs belong to.
+    pub fn entry(&self) -> &TypeEntry {
+        &self.entry
+    }
+}
+
+impl<T: Borrow<TypeTags> + Clone> SpecSegment<i128, TypeEntry> for TypeTagSegment<T> {
+    fn size(&self) -> u8 {
+        self.size
+    }
+
+    fn upper_bound(&self) -> TypeEntry {
+        self.entry
+    }
+
+    fn encode(&self) -> Result<i128, Error> {
+        Ok(self.entry.tag as i128)
+    }
+
+    fn decode(&self, encoded: i128) -> Result<TypeEntry, Error> {
+        let value = encoded as u128;
+        self.tags
+            .borrow()
+            .get(value)
+            .copied()
+            .ok_or(Error::UnknownValue { value })
+    }
+
+    fn verify(&self, encoded: i128) -> Result<(), Error> {
+        if self.tags.borrow().get(encoded as u128).is_none() {
+            return Err(Error::Verification {
+                reason: "type tag is not registered",
+            });
+        }
+        Ok(())
+    }
+
+    crate::clone_box!(i128, TypeEntry);
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+    use alloc::string::ToString;
+    use alloc::sync::Arc;
+
+    use super::*;
+    use crate::{RandomSegment, Spec2};
+
+    struct Order;
+    struct Customer;
+    struct Invoice;
+
+    fn tags() -> Arc<TypeTags> {
+        Arc::new(
+            TypeTags::new()
+                .register::<Order>(3)
+                .and_then(|tags| tags.register::<Customer>(4))
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn tells_which_type_an_id_belongs_to() {
+        let tags = tags();
+        let spec = |segment| Spec2::<i128, TypeEntry, i128> {
+            segments: (Box::new(segment), Box::new(RandomSegment::new(56))),
+        };
+        let orders = spec(TypeTagSegment::new::<Order>(8, tags.clone()).unwrap());
+        let customers = spec(TypeTagSegment::new::<Customer>(8, tags.clone()).unwrap());
+
+        let order = orders.generate().unwrap();
+        assert_eq!(3, order >> 56);
+        let (entry, _) = customers.decompose(order).unwrap();
+        assert!(entry.is::<Order>());
+        assert!(!entry.is::<Customer>());
+        assert!(entry.name.ends_with("Order"));
+        assert!(orders
+            .decompose(customers.generate().unwrap())
+            .unwrap()
+            .0
+            .is::<Customer>());
+        assert!(matches!(
+            orders.decompose(5 << 56),
+            Err(Error::InSegment { source, .. })
+                if matches!(*source, Error::UnknownValue { value: 5 })
+        ));
+    }
+
+    #[test]
+    fn rejects_conflicting_and_unknown_types() {
+        assert_eq!(
+            "tag 3 is already registered",
+            TypeTags::new()
+                .register::<Order>(3)
+                .and_then(|tags| tags.register::<Invoice>(3))
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(matches!(
+            TypeTags::new()
+                .register::<Order>(3)
+                .and_then(|tags| tags.register::<Order>(5)),
+            Err(Error::AlreadyRegistered { .. })
+        ));
+        assert!(matches!(
+            TypeTagSegment::new::<Invoice>(8, tags()),
+            Err(Error::UnknownName { .. })
+        ));
+        assert!(matches!(
+            TypeTagSegment::new::<Order>(1, tags()),
+            Err(Error::ValueTooLarge { value: 3, size: 1 })
+        ));
+    }
+}
@@ -0,0 +1,169 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::error::SegmentName;
+use crate::{mask, Segment};
+
+/// One problem with a spec's layout; segments are counted from the most significant one and
+/// carry their [label](crate::SpecSegment::label), if they have one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SpecError {
+    /// Segments add up to more than the 128 bits an ID holds.
+    TooWide { size: u32 },
+    ZeroWidth {
+        segment: usize,
+        label: Option<String>,
+    },
+    SegmentTooWide {
+        segment: usize,
+        label: Option<String>,
+        size: u8,
+    },
+    /// A constant segment's value has bits set above its width.
+    ConstantTooLarge {
+        segment: usize,
+        label: Option<String>,
+        value: i128,
+        size: u8,
+    },
+}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpecError::TooWide { size } => {
+                write!(f, "segments add up to {} bits, more than 128", size)
+            }
+            SpecError::ZeroWidth { segment, label } => {
+                write!(f, "{} has no bits", SegmentName(*segment, label.as_deref()))
+            }
+            SpecError::SegmentTooWide {
+                segment,
+                label,
+                size,
+            } => write!(
+                f,
+                "{} is {} bits wide, more than 128",
+                SegmentName(*segment, label.as_deref()),
+                size
+            ),
+            SpecError::ConstantTooLarge {
+                segment,
+                label,
+                value,
+                size,
+            } => write!(
+                f,
+                "constant {} of {} does not fit in {} bits",
+                value,
+                SegmentName(*segment, label.as_deref()),
+                size
+            ),
+        }
+    }
+}
+
+impl core::error::Error for SpecError {}
+
+/// Collects every layout violation instead of stopping at the first one.
+pub(crate) fn validate(segments: &[&dyn Segment]) -> Result<(), Vec<SpecError>> {
+    let mut errors = Vec::new();
+    let total: u32 = segments
+        .iter()
+        .map(|segment| u32::from(segment.size()))
+        .sum();
+    if total > 128 {
+        errors.push(SpecError::TooWide { size: total });
+    }
+    for (index, segment) in segments.iter().enumerate() {
+        let size = segment.size();
+        let label = segment.label().map(String::from);
+        if size == 0 {
+            errors.push(SpecError::ZeroWidth {
+                segment: index,
+                label: label.clone(),
+            });
+        }
+        if size > 128 {
+            errors.push(SpecError::SegmentTooWide {
+                segment: index,
+                label: label.clone(),
+                size,
+            });
+        }
+        if let Some(value) = segment.fixed() {
+            if value as u128 & !mask(size) != 0 {
+                errors.push(SpecError::ConstantTooLarge {
+                    segment: index,
+                    label,
+                    value,
+                    size,
+                });
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ConstantSegment, Labeled, RandomSegment, Spec2, Spec3, SpecError};
+
+    #[test]
+    fn reports_every_violation() {
+        let errors = Spec3::try_new(
+            Box::new(ConstantSegment::new(8, 44444)),
+            Box::new(RandomSegment::new(0)),
+            Box::new(RandomSegment::new(121)),
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            vec![
+                SpecError::TooWide { size: 129 },
+                SpecError::ConstantTooLarge {
+                    segment: 0,
+                    label: None,
+                    value: 44444,
+                    size: 8
+                },
+                SpecError::ZeroWidth {
+                    segment: 1,
+                    label: None
+                },
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn names_labeled_segments() {
+        let errors = Spec2::try_new(
+            Box::new(Labeled::new("tenant", ConstantSegment::new(8, 300))),
+            Box::new(Labeled::new("shard", RandomSegment::new(0))),
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            "constant 300 of tenant segment does not fit in 8 bits",
+            errors[0].to_string()
+        );
+        assert_eq!("shard segment has no bits", errors[1].to_string());
+    }
+
+    #[test]
+    fn accepts_valid_layout() {
+        let spec = Spec2::try_new(
+            Box::new(ConstantSegment::new(8, 255)),
+            Box::new(RandomSegment::new(120)),
+        )
+        .unwrap();
+        assert_eq!(255, spec.decompose(spec.generate().unwrap()).unwrap().0);
+    }
+}
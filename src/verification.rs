@@ -0,0 +1,125 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::encoding::decode_base32;
+use crate::{split, Error, Segment};
+
+/// One integrity check run by [`crate::Layout::verify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    /// The input is valid base32; only run by [`crate::Layout::verify_str`].
+    Encoding,
+    /// No bits are set above the spec's total width.
+    Width,
+    /// The segment at this index, counted from the most significant one, accepts its value.
+    Segment(usize),
+}
+
+#[derive(Debug)]
+pub struct Verification {
+    pub results: Vec<(Check, Result<(), Error>)>,
+}
+
+impl Verification {
+    pub fn is_valid(&self) -> bool {
+        self.results.iter().all(|(_, result)| result.is_ok())
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &(Check, Result<(), Error>)> {
+        self.results.iter().filter(|(_, result)| result.is_err())
+    }
+}
+
+pub(crate) fn verify(segments: &[&dyn Segment], id: i128) -> Verification {
+    let sizes: Vec<u8> = segments.iter().map(|segment| segment.size()).collect();
+    let total: u32 = sizes.iter().map(|size| u32::from(*size)).sum();
+
+    let width = if total < 128 && (id as u128) >> total != 0 {
+        Err(Error::ValueTooLarge {
+            value: id as u128,
+            size: total as u8,
+        })
+    } else {
+        Ok(())
+    };
+    let mut results = vec![(Check::Width, width)];
+    for (index, (segment, value)) in segments.iter().zip(split(&sizes, id)).enumerate() {
+        results.push((Check::Segment(index), segment.verify(value as i128)));
+    }
+    Verification { results }
+}
+
+pub(crate) fn verify_str(segments: &[&dyn Segment], encoded: &str) -> Verification {
+    match decode_base32(encoded) {
+        Ok(id) => {
+            let mut verification = verify(segments, id);
+            verification.results.insert(0, (Check::Encoding, Ok(())));
+            verification
+        }
+        Err(error) => Verification {
+            results: vec![(Check::Encoding, Err(error))],
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Date;
+
+    use crate::encoding::encode_base32;
+    use crate::{
+        Check, ConstantSegment, Error, Layout, RandomSegment, Spec3, TimestampSegment,
+        TimestampUnit,
+    };
+
+    fn spec() -> Spec3<i128, time::OffsetDateTime, i128, i128> {
+        Spec3 {
+            segments: (
+                Box::new(TimestampSegment::new_with_utc_midnight(
+                    48,
+                    TimestampUnit::Milliseconds,
+                    Date::from_calendar_date(2023, time::Month::January, 1).unwrap(),
+                )),
+                Box::new(ConstantSegment::new(8, 3)),
+                Box::new(RandomSegment::new(40)),
+            ),
+        }
+    }
+
+    #[test]
+    fn verify_generated() {
+        let spec = spec();
+        let verification = spec.verify(spec.generate().unwrap());
+        assert!(verification.is_valid());
+        assert_eq!(4, verification.results.len());
+    }
+
+    #[test]
+    fn verify_reports_every_failure() {
+        let spec = spec();
+        let future = ((1i128 << 47) << 48) | (4 << 40);
+        let verification = spec.verify(future | (1 << 100));
+
+        let failed: Vec<Check> = verification.failures().map(|(check, _)| *check).collect();
+        assert_eq!(
+            vec![Check::Width, Check::Segment(0), Check::Segment(1)],
+            failed
+        );
+    }
+
+    #[test]
+    fn verify_str_reports_parse_failures() {
+        let spec = spec();
+        let id = spec.generate().unwrap();
+        let verification = spec.verify_str(&encode_base32(id, 96));
+        assert!(verification.is_valid());
+        assert_eq!(5, verification.results.len());
+
+        let verification = spec.verify_str("01H-U!");
+        assert!(!verification.is_valid());
+        assert!(matches!(
+            verification.results.as_slice(),
+            [(Check::Encoding, Err(Error::ParseError { pos: 4, .. }))]
+        ));
+    }
+}
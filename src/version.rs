@@ -0,0 +1,213 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+
+use crate::{mask, Error, SpecSegment};
+
+/// The schema version of an ID's format, in bits reserved at the front of every layout of an ID
+/// family so the format can evolve. It decodes any version; a [`VersionDispatch`] picks the
+/// layout of the bits after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionSegment {
+    size: u8,
+    version: u32,
+}
+
+impl VersionSegment {
+    /// Fails if `version` doesn't fit in `size` bits.
+    pub fn new(size: u8, version: u32) -> Result<Self, Error> {
+        if u128::from(version) & !mask(size) != 0 {
+            return Err(Error::ValueTooLarge {
+                value: version.into(),
+                size,
+            });
+        }
+        Ok(Self { size, version })
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+}
+
+impl SpecSegment<i128, u32> for VersionSegment {
+    fn size(&self) -> u8 {
+        self.size
+    }
+
+    fn upper_bound(&self) -> u32 {
+        mask(self.size.min(32)) as u32
+    }
+
+    fn encode(&self) -> Result<i128, Error> {
+        Ok(self.version.into())
+    }
+
+    fn decode(&self, encoded: i128) -> Result<u32, Error> {
+        u32::try_from(encoded).map_err(|_| Error::UnknownVersion {
+            version: encoded as u128,
+        })
+    }
+
+    crate::clone_box!(i128, u32);
+}
+
+/// The layout to apply to each version of a `width`-bit ID family whose leading `version_bits`
+/// hold a [`VersionSegment`]. `L` is whatever decodes the remainder: a spec, a [`Layout`]
+/// object or a decoding function.
+///
+/// [`Layout`]: crate::Layout
+#[derive(Debug, Clone)]
+pub struct VersionDispatch<L> {
+    version_bits: u8,
+    width: u8,
+    layouts: BTreeMap<u32, L>,
+}
+
+impl<L> VersionDispatch<L> {
+    /// Fails if the version bits don't fit in `width` or `width` exceeds 128.
+    pub fn new(version_bits: u8, width: u8) -> Result<Self, Error> {
+        if width > 128 {
+            return Err(Error::SizeExceeded {
+                size: width.into(),
+                max: 128,
+            });
+        }
+        if version_bits > width.min(32) {
+            return Err(Error::SizeExceeded {
+                size: version_bits.into(),
+                max: width.min(32).into(),
+            });
+        }
+        Ok(Self {
+            version_bits,
+            width,
+            layouts: BTreeMap::new(),
+        })
+    }
+
+    /// Fails if `version` doesn't fit in the version bits or has a layout already.
+    pub fn register(&mut self, version: u32, layout: L) -> Result<(), Error> {
+        VersionSegment::new(self.version_bits, version)?;
+        if self.layouts.contains_key(&version) {
+            return Err(Error::AlreadyRegistered {
+                name: format!("version {}", version),
+            });
+        }
+        self.layouts.insert(version, layout);
+        Ok(())
+    }
+
+    pub fn latest(&self) -> Option<u32> {
+        self.layouts.keys().next_back().copied()
+    }
+
+    pub fn get(&self, version: u32) -> Option<&L> {
+        self.layouts.get(&version)
+    }
+
+    /// Width of the family's IDs, version bits included.
+    pub fn width(&self) -> u8 {
+        self.width
+    }
+
+    /// The version in `id`'s leading bits, rejecting IDs wider than the family.
+    pub fn version_of(&self, id: i128) -> Result<u32, Error> {
+        let id = id as u128;
+        if id & !mask(self.width) != 0 {
+            return Err(Error::ValueTooLarge {
+                value: id,
+                size: self.width,
+            });
+        }
+        let shift = u32::from(self.width - self.version_bits);
+        Ok(id.checked_shr(shift).unwrap_or(0) as u32)
+    }
+
+    /// `id`'s version and the layout registered for it.
+    pub fn select(&self, id: i128) -> Result<(u32, &L), Error> {
+        let version = self.version_of(id)?;
+        self.layouts
+            .get(&version)
+            .map(|layout| (version, layout))
+            .ok_or(Error::UnknownVersion {
+                version: version.into(),
+            })
+    }
+
+    /// `id` with its version bits cleared, for layouts that describe only the bits after them.
+    pub fn remainder(&self, id: i128) -> i128 {
+        (id as u128 & mask(self.width - self.version_bits)) as i128
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+
+    use super::*;
+    use crate::{ConstantSegment, Layout, RandomSegment, Spec2, Spec3};
+
+    fn v1() -> Spec2<i128, u32, i128> {
+        Spec2 {
+            segments: (
+                Box::new(VersionSegment::new(4, 1).unwrap()),
+                Box::new(RandomSegment::new(60)),
+            ),
+        }
+    }
+
+    fn v2() -> Spec3<i128, u32, i128, i128> {
+        Spec3 {
+            segments: (
+                Box::new(VersionSegment::new(4, 2).unwrap()),
+                Box::new(ConstantSegment::new(12, 9)),
+                Box::new(RandomSegment::new(48)),
+            ),
+        }
+    }
+
+    #[test]
+    fn dispatches_on_the_version() {
+        let mut dispatch: VersionDispatch<Box<dyn Layout>> = VersionDispatch::new(4, 64).unwrap();
+        dispatch.register(1, Box::new(v1())).unwrap();
+        dispatch.register(2, Box::new(v2())).unwrap();
+        assert_eq!(Some(2), dispatch.latest());
+
+        let old = v1().generate().unwrap();
+        let new = v2().generate().unwrap();
+        assert_eq!(1, dispatch.select(old).unwrap().0);
+        let (version, layout) = dispatch.select(new).unwrap();
+        assert_eq!(2, version);
+        assert_eq!(vec![4, 12, 48], layout.sizes());
+        assert_eq!(2, v2().decompose(new).unwrap().0);
+        assert_eq!(new & mask(60) as i128, dispatch.remainder(new));
+
+        assert!(matches!(
+            dispatch.select(3 << 60),
+            Err(Error::UnknownVersion { version: 3 })
+        ));
+        assert!(matches!(
+            dispatch.select(1 << 64),
+            Err(Error::ValueTooLarge { size: 64, .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_conflicting_versions() {
+        let mut dispatch = VersionDispatch::new(2, 64).unwrap();
+        dispatch.register(3, "v3").unwrap();
+        assert!(matches!(
+            dispatch.register(3, "again"),
+            Err(Error::AlreadyRegistered { .. })
+        ));
+        assert!(matches!(
+            dispatch.register(4, "too wide"),
+            Err(Error::ValueTooLarge { value: 4, size: 2 })
+        ));
+        assert!(matches!(
+            VersionDispatch::<()>::new(65, 64),
+            Err(Error::SizeExceeded { size: 65, .. })
+        ));
+        assert!(VersionSegment::new(2, 4).is_err());
+    }
+}
@@ -0,0 +1,38 @@
+//! Clocks for `wasm32-unknown-unknown`, where there is no system clock to read. The default
+//! clock of [`TimestampSegment`](crate::TimestampSegment) already reads `Date.now()` there;
+//! [`performance_now`] adds sub-millisecond resolution for use with `with_clock`.
+
+use js_sys::{Date, Function, Reflect};
+use time::{Duration, OffsetDateTime};
+use wasm_bindgen::{JsCast, JsValue};
+
+/// Wall-clock time from `Date.now()`, in whole milliseconds.
+pub fn date_now() -> OffsetDateTime {
+    from_millis(Date::now())
+}
+
+/// Wall-clock time from `performance.timeOrigin + performance.now()`, which browsers and Node
+/// report with microsecond resolution or coarser (to mitigate timing attacks). Falls back to
+/// [`date_now`] where `performance` is unavailable.
+pub fn performance_now() -> OffsetDateTime {
+    high_resolution_millis().map_or_else(date_now, from_millis)
+}
+
+fn high_resolution_millis() -> Option<f64> {
+    let performance = Reflect::get(&js_sys::global(), &JsValue::from_str("performance")).ok()?;
+    let origin = Reflect::get(&performance, &JsValue::from_str("timeOrigin"))
+        .ok()?
+        .as_f64()?;
+    let now = Reflect::get(&performance, &JsValue::from_str("now"))
+        .ok()?
+        .dyn_into::<Function>()
+        .ok()?
+        .call0(&performance)
+        .ok()?
+        .as_f64()?;
+    Some(origin + now)
+}
+
+fn from_millis(millis: f64) -> OffsetDateTime {
+    OffsetDateTime::UNIX_EPOCH + Duration::seconds_f64(millis / 1000.0)
+}
@@ -0,0 +1,122 @@
+//! Generation must not touch the heap once a spec is built, so latency-sensitive services can
+//! mint IDs without allocator contention.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use time::{Date, Duration, OffsetDateTime};
+use utid::encoding::encode_base32_into;
+use utid::{
+    ConstantSegment, Generator, RandomSegment, SegmentConfig, Spec3, SpecConfig, StaticSpec3,
+    TimestampSegment, TimestampUnit,
+};
+
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Allocations `f` makes on this thread, after one warm-up call for lazily initialized state
+/// such as the thread-local RNG.
+fn allocations<T>(mut f: impl FnMut() -> T) -> usize {
+    f();
+    let before = ALLOCATIONS.with(Cell::get);
+    for _ in 0..100 {
+        f();
+    }
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+fn since() -> Date {
+    Date::from_calendar_date(2023, time::Month::January, 1).unwrap()
+}
+
+#[test]
+fn boxed_specs_generate_without_allocating() {
+    let spec = Spec3::<i128, OffsetDateTime, i128, i128> {
+        segments: (
+            Box::new(TimestampSegment::new_with_utc_midnight(
+                48,
+                TimestampUnit::Milliseconds,
+                since(),
+            )),
+            Box::new(ConstantSegment::new(16, 7)),
+            Box::new(RandomSegment::new(64)),
+        ),
+    };
+    assert_eq!(0, allocations(|| spec.generate().unwrap()));
+    let id = spec.generate().unwrap();
+    assert_eq!(0, allocations(|| spec.decompose(id).unwrap()));
+
+    let generator = Generator::new(spec).unwrap();
+    assert_eq!(0, allocations(|| generator.generate().unwrap()));
+    assert_eq!(0, allocations(|| generator.decompose(id).unwrap()));
+    let mut ids = [0; 64];
+    assert_eq!(
+        0,
+        allocations(|| generator.generate_into(&mut ids).unwrap())
+    );
+    assert!(ids.iter().all(|id| id >> 64 & 0xffff == 7));
+}
+
+#[test]
+fn static_specs_generate_without_allocating() {
+    let spec = StaticSpec3 {
+        segments: (
+            TimestampSegment::new_with_utc_midnight(40, TimestampUnit::Milliseconds, since()),
+            ConstantSegment::new(8, 42),
+            RandomSegment::with_os_rng(16),
+        ),
+    };
+    let mut buf = [0; 13];
+    assert_eq!(
+        0,
+        allocations(|| {
+            let id = spec.generate().unwrap();
+            encode_base32_into(id, 64, &mut buf).unwrap().len()
+        })
+    );
+}
+
+#[test]
+fn dynamic_specs_generate_without_allocating() {
+    let spec = SpecConfig {
+        segments: vec![
+            SegmentConfig::Timestamp {
+                size: 48,
+                unit: TimestampUnit::Milliseconds,
+                epoch: since(),
+                max_skew: Duration::MINUTE,
+            },
+            SegmentConfig::Constant { size: 16, value: 7 },
+            SegmentConfig::Random {
+                size: 64,
+                os_rng: false,
+            },
+        ],
+    }
+    .build()
+    .unwrap();
+    assert_eq!(0, allocations(|| spec.generate().unwrap()));
+    let mut ids = [[0; 16]; 64];
+    assert_eq!(
+        0,
+        allocations(|| spec.generate_bytes_into(&mut ids).unwrap())
+    );
+    assert!(ids.iter().all(|id| id[6..8] == [0, 7]));
+}
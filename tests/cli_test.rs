@@ -0,0 +1,360 @@
+#![cfg(feature = "cli")]
+
+use std::process::{Command, Output};
+
+fn utid(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_utid"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn lines(output: &Output) -> Vec<String> {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout.clone())
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+fn spec_file(name: &str, contents: &str) -> String {
+    let path = std::env::temp_dir().join(format!("utid-cli-{}-{}", std::process::id(), name));
+    std::fs::write(&path, contents).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn generates_presets() {
+    let ids = lines(&utid(&["gen", "--preset", "ulid", "-n", "3"]));
+    assert_eq!(3, ids.len());
+    assert!(ids.iter().all(|id| id.len() == 26));
+
+    let ids = lines(&utid(&["gen", "--preset", "uuid-v7"]));
+    assert_eq!(Some('7'), ids[0].chars().nth(14));
+
+    let ids = lines(&utid(&["gen", "--preset", "object-id"]));
+    assert_eq!(24, ids[0].len());
+}
+
+#[test]
+fn generates_from_spec_files() {
+    let spec = spec_file(
+        "gen.toml",
+        r#"
+[[segments]]
+kind = "timestamp"
+size = 40
+unit = "milliseconds"
+epoch = "2024-01-01"
+
+[[segments]]
+kind = "random"
+size = 24
+"#,
+    );
+    let ids = lines(&utid(&["gen", "--spec", &spec, "-n", "2"]));
+    assert_eq!(2, ids.len());
+    assert!(ids.iter().all(|id| id.len() == 13));
+}
+
+#[test]
+fn requires_a_layout() {
+    assert!(!utid(&["gen"]).status.success());
+    let output = utid(&["gen", "--spec", "missing.toml"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .starts_with("error: missing.toml: cannot read spec config"));
+}
+
+#[test]
+fn inspects_ids_in_any_encoding() {
+    let spec = spec_file(
+        "inspect.toml",
+        r#"
+[[segments]]
+kind = "timestamp"
+size = 40
+unit = "milliseconds"
+epoch = "2024-01-01"
+
+[[segments]]
+kind = "constant"
+size = 8
+value = 7
+
+[[segments]]
+kind = "random"
+size = 16
+"#,
+    );
+    let id = (1000u64 << 24) | (7 << 16) | 0xbeef;
+    for input in [id.to_string(), format!("{:#x}", id), format!("{:x}", id)] {
+        let output = lines(&utid(&["inspect", &input, "--spec", &spec]));
+        assert_eq!(format!("decimal  {}", id), output[1]);
+        let rows: Vec<Vec<&str>> = output[5..]
+            .iter()
+            .map(|row| row.split_whitespace().collect())
+            .collect();
+        assert_eq!(
+            vec!["timestamp", "(ms)", "63-24", "1000", "2024-01-01T00:00:01Z"],
+            rows[0]
+        );
+        assert_eq!(vec!["constant", "23-16", "7", "7"], rows[1]);
+        assert_eq!(vec!["random", "15-0", "48879", "48879"], rows[2]);
+    }
+
+    let ulid = lines(&utid(&["gen", "--preset", "ulid"])).remove(0);
+    let output = lines(&utid(&["inspect", &ulid, "--preset", "ulid"]));
+    assert_eq!(format!("id       {}", ulid), output[0]);
+
+    let uuid = lines(&utid(&["gen", "--preset", "uuid-v7"])).remove(0);
+    let output = lines(&utid(&["inspect", &uuid, "--preset", "uuid-v7"]));
+    assert_eq!(format!("id       {}", uuid), output[0]);
+}
+
+#[test]
+fn rejects_malformed_ids() {
+    let output = utid(&["inspect", "0U", "--preset", "ulid"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        "error: unexpected character 'U' at position 1\n",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn checks_spec_files() {
+    let output = lines(&utid(&["check", "--preset", "ulid"]));
+    assert_eq!("ok: 2 segments, 128 bits", output[0]);
+
+    let spec = spec_file(
+        "check.json",
+        r#"{
+  "segments": [
+    {"kind": "timestamp", "size": 48, "unit": "milliseconds", "epoch": "2024-01-01"},
+    {"kind": "constant", "size": 8, "value": 300},
+    {"kind": "random", "size": 0}
+  ]
+}"#,
+    );
+    let output = utid(&["check", "--spec", &spec]);
+    assert!(!output.status.success());
+    assert_eq!(
+        format!(
+            "error: {spec}: invalid spec config\n  \
+             {spec}:4: segments[1].value: constant 300 of segment 1 does not fit in 8 bits\n  \
+             {spec}:5: segments[2].size: segment 2 has no bits\n",
+            spec = spec
+        ),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn generates_in_bulk_formats() {
+    let rows = lines(&utid(&[
+        "gen", "--preset", "ulid", "-n", "2", "--output", "csv", "--format", "hex",
+    ]));
+    assert_eq!("id,timestamp_0,random_1", rows[0]);
+    assert_eq!(3, rows.len());
+    let fields: Vec<&str> = rows[1].split(',').collect();
+    assert_eq!(32, fields[0].len());
+    assert!(fields[1].ends_with('Z'));
+
+    let rows = lines(&utid(&["gen", "--preset", "uuid-v7", "--output", "ndjson"]));
+    let row: serde_json::Value = serde_json::from_str(&rows[0]).unwrap();
+    assert_eq!(36, row["id"].as_str().unwrap().len());
+    assert_eq!("timestamp", row["segments"][0]["kind"]);
+    assert_eq!("7", row["segments"][1]["value"]);
+
+    let file = spec_file("ids.txt", "");
+    assert!(lines(&utid(&[
+        "gen", "--preset", "ulid", "-n", "5", "--file", &file
+    ]))
+    .is_empty());
+    assert_eq!(5, std::fs::read_to_string(&file).unwrap().lines().count());
+}
+
+#[test]
+fn simulates_collisions() {
+    let output = lines(&utid(&[
+        "simulate",
+        "--preset",
+        "object-id",
+        "--rate",
+        "50000/s",
+        "--duration",
+        "30d",
+    ]));
+    assert_eq!("ids generated          1.2960e11", output[0]);
+    assert_eq!("ids per tick           5.0000e4 (1s ticks)", output[1]);
+    assert_eq!("random bits            64", output[2]);
+    assert_eq!("collision probability  1.7562e-4", output[4]);
+
+    let output = utid(&["simulate", "--preset", "ulid", "--rate", "fast"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn benchmarks_generation() {
+    let output = lines(&utid(&[
+        "bench",
+        "--preset",
+        "ulid",
+        "--threads",
+        "2",
+        "--duration",
+        "50ms",
+    ]));
+    assert_eq!("threads     2", output[0]);
+    assert!(
+        output[1]
+            .trim_start_matches("ids")
+            .trim()
+            .parse::<u64>()
+            .unwrap()
+            > 0
+    );
+    assert!(output[4].starts_with("p99"));
+    assert!(
+        !utid(&["bench", "--preset", "ulid", "--duration", "0.0000000001s"])
+            .status
+            .success()
+    );
+}
+
+#[test]
+fn prints_window_bounds() {
+    let spec = spec_file(
+        "range.toml",
+        r#"
+[[segments]]
+kind = "timestamp"
+size = 32
+unit = "seconds"
+epoch = "2024-01-01"
+
+[[segments]]
+kind = "constant"
+size = 8
+value = 7
+
+[[segments]]
+kind = "random"
+size = 24
+"#,
+    );
+    let output = lines(&utid(&[
+        "range",
+        "--spec",
+        &spec,
+        "--from",
+        "2024-01-02",
+        "--to",
+        "2024-01-02T00:00:10Z",
+    ]));
+    assert_eq!(
+        vec![
+            format!("min  decimal  {}", (86_400u64 << 32) | (7 << 24)),
+            "min  hex      0001518007000000".to_string(),
+            "min  base32   000AHG03G0000".to_string(),
+            format!(
+                "max  decimal  {}",
+                (86_409u64 << 32) | (7 << 24) | 0xff_ffff
+            ),
+            "max  hex      0001518907ffffff".to_string(),
+            "max  base32   000AHH43ZZZZZ".to_string(),
+        ],
+        output
+    );
+
+    let output = utid(&[
+        "range",
+        "--preset",
+        "ulid",
+        "--from",
+        "1960-01-01",
+        "--to",
+        "2024-01-01",
+    ]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn validates_ids() {
+    let id = lines(&utid(&["gen", "--preset", "uuid-v7"])).remove(0);
+    assert_eq!(
+        vec!["valid"],
+        lines(&utid(&["validate", &id, "--preset", "uuid-v7"]))
+    );
+
+    let output = utid(&["validate", "0x1", "--preset", "uuid-v7"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        "segment 1 (constant): verification failed: constant does not match\n\
+         segment 3 (constant): verification failed: constant does not match\n",
+        String::from_utf8_lossy(&output.stdout)
+    );
+
+    let ids = lines(&utid(&["gen", "--preset", "ulid", "-n", "3"])).join("\n");
+    let file = spec_file("validate.txt", &format!("{}\n\n0U\n", ids));
+    let output = utid(&["validate", &file, "--preset", "ulid"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("line 5: 0U: parse: unexpected character 'U' at position 1\n"));
+    assert!(stdout.contains("checked 4, valid 3, invalid 1\n"));
+    assert_eq!(
+        "error: 1 of 4 IDs are invalid\n",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn converts_between_encodings() {
+    let uuid = "01a13db5-1397-7c52-9e1f-ecc3706796eb";
+    let base32 = lines(&utid(&["convert", uuid, "--to", "base32"])).remove(0);
+    assert_eq!(26, base32.len());
+    assert_eq!(
+        vec![uuid],
+        lines(&utid(&[
+            "convert", &base32, "--from", "base32", "--to", "uuid"
+        ]))
+    );
+    assert_eq!(
+        vec!["01a13db513977c529e1fecc3706796eb"],
+        lines(&utid(&["convert", uuid, "--to", "hex"]))
+    );
+    assert_eq!(
+        vec!["00ff"],
+        lines(&utid(&["convert", "255", "--to", "hex", "--bits", "16"]))
+    );
+    // As long as a 128-bit base32 ID, but only digits.
+    assert_eq!(
+        vec!["12345678901234567890123456"],
+        lines(&utid(&[
+            "convert",
+            "12345678901234567890123456",
+            "--to",
+            "decimal"
+        ]))
+    );
+    assert!(!utid(&["convert", "256", "--to", "hex", "--bits", "8"])
+        .status
+        .success());
+}
+
+#[test]
+fn documents_layouts() {
+    let output = lines(&utid(&["doc", "--preset", "ulid"]));
+    assert_eq!("```text", output[0]);
+    assert!(output.contains(&"| # | Segment | Bits | Range | Epoch | Example |".to_string()));
+
+    let output = lines(&utid(&["doc", "--preset", "ulid", "--format", "svg"]));
+    assert!(output[0].starts_with("<svg "));
+    assert_eq!(Some(&"</svg>".to_string()), output.last());
+}
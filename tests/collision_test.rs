@@ -0,0 +1,30 @@
+#![cfg(feature = "test-util")]
+
+use time::Date;
+use utid::presets::{snowflake, ulid, uuid_v7};
+use utid::test_util::CollisionHarness;
+
+#[test]
+fn presets_do_not_collide() {
+    let harness = CollisionHarness::new(200_000).with_threads(4);
+
+    let spec = ulid();
+    let report = harness.run(|| spec.generate()).unwrap();
+    assert!(report.exact);
+    assert_eq!(Vec::<i128>::new(), report.duplicates);
+
+    let spec = uuid_v7();
+    assert!(harness
+        .run(|| spec.generate())
+        .unwrap()
+        .duplicates
+        .is_empty());
+
+    let epoch = Date::from_calendar_date(2024, time::Month::January, 1).unwrap();
+    let spec = snowflake(epoch, 1).unwrap();
+    assert!(harness
+        .run(|| spec.generate())
+        .unwrap()
+        .duplicates
+        .is_empty());
+}
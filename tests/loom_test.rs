@@ -0,0 +1,56 @@
+#![cfg(utid_loom)]
+
+use loom::sync::Arc;
+use loom::thread;
+use utid::Sequence;
+
+/// Runs `ticks` through one sequence per thread, concurrently, and returns what each thread got.
+fn run(bits: u8, ticks: [&'static [u64]; 2]) -> Vec<Vec<(u64, u64)>> {
+    let sequence = Arc::new(Sequence::new(bits));
+    let handles: Vec<_> = ticks
+        .into_iter()
+        .map(|ticks| {
+            let sequence = sequence.clone();
+            thread::spawn(move || {
+                ticks
+                    .iter()
+                    .map(|tick| sequence.next(*tick).unwrap())
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+    handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect()
+}
+
+fn assert_unique_and_ordered(results: Vec<Vec<(u64, u64)>>) {
+    for pairs in &results {
+        assert!(
+            pairs.windows(2).all(|pair| pair[0] < pair[1]),
+            "{:?}",
+            results
+        );
+    }
+    let mut all: Vec<_> = results.concat();
+    let len = all.len();
+    all.sort_unstable();
+    all.dedup();
+    assert_eq!(len, all.len(), "{:?}", results);
+}
+
+#[test]
+fn same_tick() {
+    loom::model(|| assert_unique_and_ordered(run(4, [&[7, 7], &[7, 7]])));
+}
+
+#[test]
+fn counter_overflow_borrows_the_next_tick() {
+    loom::model(|| assert_unique_and_ordered(run(1, [&[7, 7], &[7, 8]])));
+}
+
+#[test]
+fn clock_stepping_back() {
+    loom::model(|| assert_unique_and_ordered(run(2, [&[9, 3], &[5, 9]])));
+}
@@ -0,0 +1,84 @@
+use rand::Rng;
+use time::Date;
+use utid::{
+    ConstantSegment, Layout, NameHash, RandomSegment, Redaction, Spec, Spec2, Spec3, Spec4,
+    TimestampSegment, TimestampUnit,
+};
+
+fn since() -> Date {
+    Date::from_calendar_date(2023, time::Month::January, 1).unwrap()
+}
+
+fn inputs() -> impl Iterator<Item = i128> {
+    let mut rng = rand::thread_rng();
+    [0, 1, -1, i128::MIN, i128::MAX, 1 << 64, 1 << 127]
+        .into_iter()
+        .chain((0..2_000).map(move |_| rng.gen::<i128>()))
+}
+
+#[test]
+fn spec_operations_never_panic() {
+    let specs: Vec<Box<dyn Layout>> = vec![
+        Box::new(Spec {
+            segment: Box::new(TimestampSegment::new_with_utc_midnight(
+                128,
+                TimestampUnit::Nanoseconds,
+                since(),
+            )),
+        }),
+        Box::new(Spec2 {
+            segments: (
+                Box::new(ConstantSegment::new(1, 1)),
+                Box::new(RandomSegment::new(127)),
+            ),
+        }),
+        Box::new(Spec3 {
+            segments: (
+                Box::new(TimestampSegment::new_with_utc_midnight(
+                    96,
+                    TimestampUnit::Seconds,
+                    since(),
+                )),
+                Box::new(ConstantSegment::new(8, 300)),
+                Box::new(RandomSegment::new(24)),
+            ),
+        }),
+        Box::new(Spec4 {
+            segments: (
+                Box::new(RandomSegment::new(64)),
+                Box::new(RandomSegment::new(64)),
+                Box::new(RandomSegment::new(1)),
+                Box::new(RandomSegment::new(0)),
+            ),
+        }),
+    ];
+    for spec in &specs {
+        let _ = spec.validate();
+        let _ = spec.timestamp_exhaustion_date();
+        let _ = spec.derive(NameHash::Sha1, 1, b"name");
+        let redactions = vec![Redaction::Hash; spec.segments().len()];
+        for input in inputs() {
+            let _ = spec.verify(input);
+            let _ = spec.redact(input, &redactions, b"salt");
+        }
+    }
+}
+
+#[test]
+fn decompose_never_panics() {
+    let spec = Spec3 {
+        segments: (
+            Box::new(TimestampSegment::new_with_utc_midnight(
+                100,
+                TimestampUnit::Milliseconds,
+                since(),
+            )),
+            Box::new(ConstantSegment::new(8, 300)),
+            Box::new(RandomSegment::new(20)),
+        ),
+    };
+    let _ = spec.generate();
+    for input in inputs() {
+        let _ = spec.decompose(input);
+    }
+}
@@ -0,0 +1,84 @@
+#![cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+
+use time::{Date, Month, OffsetDateTime};
+use utid::{RandomSegment, Spec2, TimestampSegment, TimestampUnit};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn since() -> Date {
+    Date::from_calendar_date(2023, Month::January, 1).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn generates_with_browser_time_and_entropy() {
+    let spec = Spec2 {
+        segments: (
+            Box::new(TimestampSegment::new_with_utc_midnight(
+                48,
+                TimestampUnit::Milliseconds,
+                since(),
+            )),
+            Box::new(RandomSegment::with_os_rng(80)),
+        ),
+    };
+    let (a, b) = (spec.generate().unwrap(), spec.generate().unwrap());
+    assert_ne!(a, b);
+    let (timestamp, _) = spec.decompose(a).unwrap();
+    assert!((OffsetDateTime::now_utc() - timestamp).whole_seconds() < 2);
+}
+
+#[wasm_bindgen_test]
+fn performance_clock_tracks_date() {
+    let drift = utid::wasm::performance_now() - utid::wasm::date_now();
+    assert!(drift.abs().whole_seconds() < 1);
+
+    let spec = Spec2 {
+        segments: (
+            Box::new(
+                TimestampSegment::new_with_utc_midnight(48, TimestampUnit::Microseconds, since())
+                    .with_clock(utid::wasm::performance_now),
+            ),
+            Box::new(RandomSegment::new(80)),
+        ),
+    };
+    assert!(spec.generate().unwrap() > 0);
+}
+
+#[cfg(feature = "js")]
+mod js {
+    use js_sys::{BigInt, Date};
+    use utid::js::{from_decimal, to_decimal, to_uuid, UtidGenerator};
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    const CONFIG: &str = r#"{"segments":[
+        {"kind":"timestamp","size":48,"unit":"milliseconds","epoch":"2023-01-01"},
+        {"kind":"constant","size":16,"value":7},
+        {"kind":"random","size":64}
+    ]}"#;
+
+    #[wasm_bindgen_test]
+    fn generates_and_decomposes() {
+        let generator = UtidGenerator::new(CONFIG).unwrap();
+        let id = generator.generate().unwrap();
+        assert_eq!(26, id.len());
+
+        let values = generator.decompose(&id).unwrap();
+        assert_eq!(3, values.length());
+        let timestamp: Date = values.get(0).dyn_into().unwrap();
+        assert!((Date::now() - timestamp.get_time()).abs() < 2000.0);
+        let constant: BigInt = values.get(1).dyn_into().unwrap();
+        assert_eq!(BigInt::from(7), constant);
+    }
+
+    #[wasm_bindgen_test]
+    fn converts_encodings() {
+        assert_eq!("385", to_decimal("000000000000000000000000C1").unwrap());
+        assert_eq!("000000000000000000000000C1", from_decimal("385").unwrap());
+        assert_eq!(
+            "00000000-0000-0000-0000-000000000181",
+            to_uuid("000000000000000000000000C1").unwrap()
+        );
+        assert!(UtidGenerator::new("{}").is_err());
+        assert!(to_decimal("0U").is_err());
+    }
+}